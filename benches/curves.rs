@@ -0,0 +1,85 @@
+// cargo bench, or cargo bench -- mandelbrot/Feigenbaum to run a single group or view
+use std::f32::consts::TAU;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use glam::{DVec2, Vec2};
+use lissajous::{
+    DeJongParams, GraphParams, Harmonograph, InteractiveCurve, LissajouParams, MandelbrotParams, MandelbrotSet, GRAPH_MODES,
+    REMARKABLE_POINTS,
+};
+
+const VIEW_SIZE: f32 = 256.0;
+const MANDELBROT_SPAN: f64 = 0.05;
+const DWELLS: [usize; 3] = [100, 1_000, 10_000];
+const LISSAJOU_NB_POINTS: [usize; 3] = [500, 2_000, 5_000];
+
+fn mandelbrot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mandelbrot");
+    group.sample_size(10);
+    let size = Vec2::splat(VIEW_SIZE);
+
+    for (center, label) in REMARKABLE_POINTS {
+        for max_iterations in DWELLS {
+            let params = MandelbrotParams {
+                box_center: center,
+                box_size: DVec2::splat(MANDELBROT_SPAN),
+                max_iterations,
+            };
+            group.bench_with_input(BenchmarkId::new(label, max_iterations), &params, |b, params| {
+                // A new set each time, so that nothing is cached from a previous run
+                b.iter_batched(
+                    || MandelbrotSet::with_params(*params),
+                    |mut set| set.compute_drawables(size / 2.0, size).len(),
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+fn dejong(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dejong");
+    for nb_iter in [80_000, 640_000] {
+        let params = DeJongParams { nb_iter, ..DeJongParams::default() };
+        group.bench_with_input(BenchmarkId::new("points", nb_iter), &params, |b, params| {
+            b.iter(|| params.points().fold(Vec2::ZERO, |acc, pt| acc + pt))
+        });
+    }
+    group.finish();
+}
+
+fn harmonograph(c: &mut Criterion) {
+    let size = Vec2::splat(VIEW_SIZE);
+    let mut harmonograph = Harmonograph::new();
+    c.bench_function("harmonograph/mesh", |b| {
+        b.iter(|| harmonograph.compute_drawables(size / 2.0, size).len())
+    });
+}
+
+fn lissajou_neighbors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lissajou_neighbors");
+    group.sample_size(20);
+    let radius = VIEW_SIZE / 2.0;
+
+    for nb_points in LISSAJOU_NB_POINTS {
+        let params = LissajouParams { nb_points, ..LissajouParams::default() };
+        let points = params.points(radius, TAU);
+        let screen_points: Vec<Vec2> = points.iter().map(|pt| Vec2::new(pt[0], pt[1])).collect();
+
+        for mode in GRAPH_MODES {
+            let graph_params = GraphParams {
+                mode,
+                k: 6,
+                max_distance: 0.2 * VIEW_SIZE,
+                max_edges: 100_000,
+            };
+            group.bench_with_input(BenchmarkId::new(format!("{:?}", mode), nb_points), &graph_params, |b, graph_params| {
+                b.iter(|| graph_params.edges(&points, &screen_points).len())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, mandelbrot, dejong, harmonograph, lissajou_neighbors);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+#[cfg(feature = "gui")]
+use ggegui::egui;
+#[cfg(feature = "gui")]
+use ggegui::egui::Ui;
+#[cfg(feature = "gui")]
+use crate::i18n::tr;
+
+// Drives the "pen" of curves parameterized by time: instead of drawing the whole curve at once,
+// only the part up to the current time is drawn, like a real harmonograph or an oscilloscope.
+pub struct Animation {
+    enabled: bool,
+    playing: bool,
+    t: f32,
+    speed: f32, // Curve time units per second
+    phase_drift: f32, // Radians per second
+}
+
+impl Animation {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            enabled: false,
+            playing: true,
+            t: 0.0,
+            speed,
+            phase_drift: 0.0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.enabled && self.playing
+    }
+
+    // Time elapsed since the start of the curve
+    pub fn t(&self) -> f32 {
+        self.t
+    }
+
+    // Advances the pen by dt seconds, looping back at end_t.
+    // Returns the phase increment to apply to the curve.
+    pub fn tick(&mut self, dt: f32, end_t: f32) -> f32 {
+        if !self.is_running() {
+            return 0.0;
+        }
+        self.t += self.speed * dt;
+        if self.t > end_t {
+            self.t = 0.0;
+        }
+        self.phase_drift * dt
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn restart(&mut self) {
+        self.t = 0.0;
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn update_ui(&mut self, ui: &mut Ui, max_speed: f32) {
+        ui.checkbox(&mut self.enabled, tr("animation.enabled"));
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(tr(if self.playing { "animation.pause" } else { "animation.play" })).clicked() {
+                    self.playing = !self.playing;
+                }
+                if ui.button("|<").clicked() {
+                    self.restart();
+                }
+            });
+            egui::Grid::new(ui.next_auto_id())
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(tr("animation.speed"));
+                    ui.add(egui::Slider::new(&mut self.speed, 0.0..=max_speed).logarithmic(true));
+                    ui.end_row();
+                    ui.label(tr("animation.phase_drift"));
+                    ui.add(egui::Slider::new(&mut self.phase_drift, -1.0..=1.0).fixed_decimals(2).suffix(" rad/s"));
+                    ui.end_row();
+                });
+        });
+    }
+}
@@ -0,0 +1,181 @@
+use ggez::{conf, event, Context, GameResult};
+use std::path::PathBuf;
+use ggez::event::EventLoop;
+use crate::bookmarks;
+use crate::config;
+use crate::curve_registry::CurveRegistry;
+use crate::i18n::{self, Language};
+use crate::interactive_curve::InteractiveCurve;
+use crate::layout::Layout;
+use crate::lissajou_app::LissajouApp;
+
+const DEFAULT_TITLE: &str = "Lissajous";
+const DEFAULT_WINDOW_SIZE: f32 = 1024.0;
+
+/// Configures the window and the curves of the application.
+///
+/// ```no_run
+/// lissajous::AppBuilder::new()
+///     .title("My curves")
+///     .window_size(1280.0, 800.0)
+///     .run()
+///     .unwrap();
+/// ```
+pub struct AppBuilder {
+    title: String,
+    window_size: (f32, f32),
+    resizable: bool,
+    registry: CurveRegistry,
+    initial_curve: usize,
+    bookmarks_file: PathBuf,
+    config_file: PathBuf,
+    language: Option<Language>,
+    audio_file: Option<PathBuf>,
+    osc_port: Option<u16>,
+    http_port: Option<u16>,
+    layout: Option<Layout>,
+}
+
+impl AppBuilder {
+    pub fn new() -> Self {
+        Self {
+            title: DEFAULT_TITLE.to_string(),
+            window_size: (DEFAULT_WINDOW_SIZE, DEFAULT_WINDOW_SIZE),
+            resizable: true,
+            registry: CurveRegistry::default(),
+            initial_curve: 0,
+            bookmarks_file: PathBuf::from(bookmarks::DEFAULT_FILE),
+            config_file: PathBuf::from(config::DEFAULT_FILE),
+            language: None,
+            audio_file: None,
+            osc_port: None,
+            http_port: None,
+            layout: None,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn window_size(mut self, width: f32, height: f32) -> Self {
+        self.window_size = (width, height);
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Replaces the built-in curves
+    pub fn curves(mut self, registry: CurveRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Adds a curve to the ones already registered
+    pub fn register_curve<F>(mut self, name: &str, category: &str, factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn InteractiveCurve> + Send + Sync + 'static
+    {
+        self.registry.register(name, category, factory);
+        self
+    }
+
+    /// Index, in registration order, of the curve displayed at startup
+    pub fn initial_curve(mut self, index: usize) -> Self {
+        self.initial_curve = index;
+        self
+    }
+
+    /// JSON file where the bookmarks are saved, in the current directory by default
+    pub fn bookmarks_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.bookmarks_file = path.into();
+        self
+    }
+
+    /// JSON file of the user settings, like the language and the input bindings, in the current directory by default
+    pub fn config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = path.into();
+        self
+    }
+
+    /// Language of the interface at startup, in place of the one of the config file. It can be changed from the side panel.
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// WAV file played at startup for the audio-reactive mode, in place of a live audio input
+    pub fn audio_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audio_file = Some(path.into());
+        self
+    }
+
+    /// UDP port of an OSC server driving the curves, e.g. from TouchOSC or Max/MSP. No server by default.
+    pub fn osc_port(mut self, port: u16) -> Self {
+        self.osc_port = Some(port);
+        self
+    }
+
+    /// Port of a JSON API on localhost, to script the application over HTTP. No server by default.
+    pub fn http_port(mut self, port: u16) -> Self {
+        self.http_port = Some(port);
+        self
+    }
+
+    /// Split screen at startup, in place of the one of the config file, e.g. 2 by 2 to show the four curves for a demo
+    pub fn layout(mut self, columns: usize, rows: usize) -> Self {
+        self.layout = Some(Layout::new(columns, rows));
+        self
+    }
+
+    /// Creates the window and the application without running it, to embed it in another event loop.
+    pub fn build(self) -> GameResult<(Context, EventLoop<()>, LissajouApp)> {
+        let (mut ctx, event_loop) = ggez::ContextBuilder::new("lissajous", "rcurves")
+            .window_setup(conf::WindowSetup::default().title(&self.title))
+            .window_mode(
+            conf::WindowMode::default()
+                    .fullscreen_type(conf::FullscreenType::Windowed)
+                    .dimensions(self.window_size.0, self.window_size.1)
+                    .resizable(self.resizable),
+            )
+            .build()?;
+
+        let mut app = LissajouApp::new(&mut ctx, &self.title, self.registry);
+        app.select_curve(self.initial_curve);
+        app.load_config(&self.config_file);
+        if let Some(language) = self.language {
+            i18n::set_language(language);
+        }
+        if let Some(layout) = self.layout {
+            app.set_layout(layout);
+        }
+        app.load_bookmarks(&self.bookmarks_file);
+        if let Some(path) = &self.audio_file {
+            app.open_audio_file(path);
+        }
+        if let Some(port) = self.osc_port {
+            app.start_osc(port);
+        }
+        if let Some(port) = self.http_port {
+            app.start_http(port);
+        }
+
+        Ok((ctx, event_loop, app))
+    }
+
+    pub fn run(self) -> GameResult {
+        let (ctx, event_loop, app) = self.build()?;
+
+        event::run(ctx, event_loop, app)
+    }
+}
+
+impl Default for AppBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,430 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+use rustfft::{Fft, FftPlanner};
+use rustfft::num_complex::Complex;
+use serde::{Deserialize, Serialize};
+
+// Samples per analysis, about 46 ms at 44.1 kHz
+pub const FFT_SIZE: usize = 2048;
+// Frequency ranges of the bass, mid and treble bands, in Hz
+const BANDS: [(f32, f32); 3] = [(20.0, 250.0), (250.0, 4000.0), (4000.0, 20000.0)];
+// A beat is an energy this many times the recent average
+const BEAT_RATIO: f32 = 1.8;
+// Under this energy, e.g. the noise between two songs, there is no beat
+const BEAT_MIN_ENERGY: f32 = 1e-3;
+// Recent analyses the beats are compared to
+const BEAT_HISTORY: usize = 40;
+
+// Signals of the sound that can drive a parameter, all in [0, 1] before the gain
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AudioSignal {
+    Level,
+    Bass,
+    Mid,
+    Treble,
+    Beat,
+}
+
+pub const AUDIO_SIGNALS: [AudioSignal; 5] = [AudioSignal::Level, AudioSignal::Bass, AudioSignal::Mid, AudioSignal::Treble, AudioSignal::Beat];
+
+impl AudioSignal {
+    // Message key, see i18n
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioSignal::Level => "audio.level",
+            AudioSignal::Bass => "audio.bass",
+            AudioSignal::Mid => "audio.mid",
+            AudioSignal::Treble => "audio.treble",
+            AudioSignal::Beat => "audio.beat",
+        }
+    }
+}
+
+// Result of the analysis of the last samples. Amplitudes are the ones of a sine of the same energy, 1 at full scale.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct AudioFrame {
+    pub level: f32,
+    pub bands: [f32; 3], // Bass, mid, treble
+    pub beat: bool,
+}
+
+impl AudioFrame {
+    pub fn signal(&self, signal: AudioSignal) -> f32 {
+        let value = match signal {
+            AudioSignal::Level => self.level,
+            AudioSignal::Bass => self.bands[0],
+            AudioSignal::Mid => self.bands[1],
+            AudioSignal::Treble => self.bands[2],
+            AudioSignal::Beat => if self.beat { 1.0 } else { 0.0 },
+        };
+        value.min(1.0)
+    }
+}
+
+pub struct AudioAnalyzer {
+    sample_rate: u32,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>, // Hann
+    energies: VecDeque<f32>, // Of the last analyses, for the beats
+    in_beat: bool,
+}
+
+impl AudioAnalyzer {
+    pub fn new(sample_rate: u32) -> Self {
+        let window = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * f32::cos(2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32))
+            .collect();
+        Self {
+            sample_rate,
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            window,
+            energies: VecDeque::with_capacity(BEAT_HISTORY),
+            in_beat: false,
+        }
+    }
+
+    // Analyzes the last FFT_SIZE samples, mono in [-1, 1]. Missing samples are silence.
+    pub fn analyze(&mut self, samples: &[f32]) -> AudioFrame {
+        let samples = &samples[samples.len().saturating_sub(FFT_SIZE)..];
+        let mut buffer: Vec<Complex<f32>> = vec![Complex::default(); FFT_SIZE - samples.len()];
+        buffer.extend(samples.iter().map(|sample| Complex::new(*sample, 0.0)));
+        for (value, weight) in buffer.iter_mut().zip(&self.window) {
+            *value *= weight;
+        }
+        self.fft.process(&mut buffer);
+
+        // Amplitude of each bin, so that a full scale sine has an amplitude of 1
+        let scale = 2.0 / self.window.iter().sum::<f32>();
+        let bin_width = self.sample_rate as f32 / FFT_SIZE as f32;
+        let mut bands = [0.0; 3];
+        for (bin, value) in buffer.iter().enumerate().take(FFT_SIZE / 2).skip(1) {
+            let frequency = bin as f32 * bin_width;
+            if let Some(band) = BANDS.iter().position(|(low, high)| frequency >= *low && frequency < *high) {
+                bands[band] += (value.norm() * scale).powi(2);
+            }
+        }
+        // The Hann window spreads a sine over 1.5 bins of energy
+        let bands = bands.map(|energy: f32| (energy / 1.5).sqrt());
+
+        let energy = samples.iter().map(|sample| sample * sample).sum::<f32>() / FFT_SIZE as f32;
+        let level = (2.0 * energy).sqrt();
+
+        AudioFrame { level, bands, beat: self.detect_beat(energy) }
+    }
+
+    // Onset of a beat: the energy jumps over the recent average. Reported once, until the energy falls back.
+    fn detect_beat(&mut self, energy: f32) -> bool {
+        let average = if self.energies.is_empty() {
+            0.0
+        } else {
+            self.energies.iter().sum::<f32>() / self.energies.len() as f32
+        };
+        let loud = energy > BEAT_MIN_ENERGY && energy > BEAT_RATIO * average;
+        let beat = loud && !self.in_beat;
+        self.in_beat = loud;
+
+        if self.energies.len() == BEAT_HISTORY {
+            self.energies.pop_front();
+        }
+        self.energies.push_back(energy);
+        beat
+    }
+}
+
+// A WAV file played in real time, in a loop
+pub struct WavPlayer {
+    pub name: String,
+    samples: Vec<f32>, // Mono
+    sample_rate: u32,
+    position: f32, // In samples
+}
+
+impl WavPlayer {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let error = |err: hound::Error| format!("{}: {}", path.display(), err);
+        let reader = hound::WavReader::open(path).map_err(error)?;
+        let spec = reader.spec();
+        let interleaved: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>().map_err(error)?,
+            hound::SampleFormat::Int => {
+                let full_scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|sample| sample.map(|sample| sample as f32 / full_scale))
+                    .collect::<Result<_, _>>()
+                    .map_err(error)?
+            },
+        };
+        Ok(Self::new(
+            &path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+            mix_down(&interleaved, spec.channels as usize),
+            spec.sample_rate,
+        ))
+    }
+
+    pub fn new(name: &str, samples: Vec<f32>, sample_rate: u32) -> Self {
+        Self { name: name.to_string(), samples, sample_rate, position: 0.0 }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    // Plays dt seconds, then returns the last FFT_SIZE samples
+    pub fn advance(&mut self, dt: f32) -> Vec<f32> {
+        if self.samples.is_empty() {
+            return vec![];
+        }
+        let length = self.samples.len();
+        self.position = (self.position + dt * self.sample_rate as f32) % length as f32;
+        // Wraps around to the end of the file at the start of a loop
+        let window = FFT_SIZE.min(length);
+        let start = self.position as usize + length - window;
+        (start..start + window).map(|i| self.samples[i % length]).collect()
+    }
+}
+
+fn mix_down(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    interleaved
+        .chunks(channels.max(1))
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+// The default input device of the system, e.g. a microphone or a line in
+pub struct LiveInput {
+    pub name: String,
+    #[cfg(feature = "audio_input")]
+    _stream: cpal::Stream,
+    #[cfg(feature = "audio_input")]
+    samples: Arc<std::sync::Mutex<VecDeque<f32>>>,
+    sample_rate: u32,
+}
+
+#[cfg(feature = "audio_input")]
+impl LiveInput {
+    pub fn open() -> Result<Self, String> {
+        use std::sync::Mutex;
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+        let device = cpal::default_host().default_input_device().ok_or("No audio input device")?;
+        let config = device.default_input_config().map_err(|err| err.to_string())?;
+        let channels = config.channels() as usize;
+        let samples = Arc::new(Mutex::new(VecDeque::with_capacity(FFT_SIZE)));
+        let push = {
+            let samples = samples.clone();
+            move |mono: Vec<f32>| {
+                let mut samples = samples.lock().unwrap();
+                samples.extend(mono);
+                let excess = samples.len().saturating_sub(FFT_SIZE);
+                samples.drain(..excess);
+            }
+        };
+        let on_error = |err| eprintln!("Audio input: {}", err);
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => device.build_input_stream(
+                &config.config(),
+                move |data: &[f32], _: &_| push(mix_down(data, channels)),
+                on_error,
+                None,
+            ),
+            cpal::SampleFormat::I16 => device.build_input_stream(
+                &config.config(),
+                move |data: &[i16], _: &_| {
+                    let data: Vec<f32> = data.iter().map(|sample| *sample as f32 / i16::MAX as f32).collect();
+                    push(mix_down(&data, channels))
+                },
+                on_error,
+                None,
+            ),
+            format => return Err(format!("Unsupported sample format {}", format)),
+        }.map_err(|err| err.to_string())?;
+        stream.play().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            name: device.name().unwrap_or_default(),
+            _stream: stream,
+            samples,
+            sample_rate: config.sample_rate().0,
+        })
+    }
+
+    pub fn latest(&self) -> Vec<f32> {
+        self.samples.lock().unwrap().iter().copied().collect()
+    }
+}
+
+#[cfg(not(feature = "audio_input"))]
+impl LiveInput {
+    pub fn open() -> Result<Self, String> {
+        Err("Built without the audio_input feature".to_string())
+    }
+
+    pub fn latest(&self) -> Vec<f32> {
+        vec![]
+    }
+}
+
+impl LiveInput {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+pub enum AudioSource {
+    File(WavPlayer),
+    Live(LiveInput),
+}
+
+impl AudioSource {
+    pub fn name(&self) -> &str {
+        match self {
+            AudioSource::File(player) => &player.name,
+            AudioSource::Live(input) => &input.name,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        match self {
+            AudioSource::File(player) => player.sample_rate(),
+            AudioSource::Live(input) => input.sample_rate(),
+        }
+    }
+
+    // The last samples, after dt more seconds
+    pub fn samples(&mut self, dt: f32) -> Vec<f32> {
+        match self {
+            AudioSource::File(player) => player.advance(dt),
+            AudioSource::Live(input) => input.latest(),
+        }
+    }
+}
+
+// Drives a parameter of the curve with a signal of the sound
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AudioRoute {
+    pub signal: AudioSignal,
+    pub target: String, // Id of the parameter of the curve
+    pub gain: f32,
+    pub smoothing: f32, // Time constant, in seconds. 0 follows the signal as is.
+}
+
+impl AudioRoute {
+    pub fn new(signal: AudioSignal, target: &str) -> Self {
+        Self { signal, target: target.to_string(), gain: 1.0, smoothing: 0.1 }
+    }
+
+    // The smoothed signal after dt seconds, from its previous value
+    pub fn smooth(&self, previous: f32, frame: &AudioFrame, dt: f32) -> f32 {
+        let signal = frame.signal(self.signal);
+        if self.smoothing <= 0.0 {
+            return signal;
+        }
+        previous + (signal - previous) * (1.0 - f32::exp(-dt / self.smoothing))
+    }
+
+    // Position of the parameter in its range, in [0, 1]
+    pub fn position(&self, smoothed: f32) -> f32 {
+        let position = self.gain * smoothed;
+        if position.is_nan() { 0.0 } else { position.clamp(0.0, 1.0) }
+    }
+}
+
+// The audio routes per curve registration name
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AudioRoutes {
+    curves: BTreeMap<String, Vec<AudioRoute>>,
+}
+
+impl AudioRoutes {
+    pub fn routes(&self, curve: &str) -> &[AudioRoute] {
+        self.curves.get(curve).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn routes_mut(&mut self, curve: &str) -> &mut Vec<AudioRoute> {
+        self.curves.entry(curve.to_string()).or_default()
+    }
+
+    // Removes the curves without routes, before saving
+    pub fn prune(&mut self) {
+        self.curves.retain(|_, routes| !routes.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    fn sine(frequency: f32, amplitude: f32, length: usize) -> Vec<f32> {
+        (0..length)
+            .map(|i| amplitude * f32::sin(2.0 * std::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE as f32))
+            .collect()
+    }
+
+    #[test]
+    fn sines_fall_in_their_band() {
+        let mut analyzer = AudioAnalyzer::new(SAMPLE_RATE);
+        let frame = analyzer.analyze(&sine(100.0, 0.5, FFT_SIZE));
+        assert!((frame.bands[0] - 0.5).abs() < 0.05, "{:?}", frame);
+        assert!(frame.bands[1] < 0.05 && frame.bands[2] < 0.05, "{:?}", frame);
+        assert!((frame.level - 0.5).abs() < 0.01, "{:?}", frame);
+
+        let frame = analyzer.analyze(&sine(8000.0, 1.0, FFT_SIZE));
+        assert!((frame.bands[2] - 1.0).abs() < 0.1, "{:?}", frame);
+        assert!(frame.bands[0] < 0.05, "{:?}", frame);
+    }
+
+    #[test]
+    fn beats_are_onsets() {
+        let mut analyzer = AudioAnalyzer::new(SAMPLE_RATE);
+        let quiet = sine(100.0, 0.02, FFT_SIZE);
+        let loud = sine(100.0, 0.8, FFT_SIZE);
+        for _ in 0..10 {
+            assert!(!analyzer.analyze(&quiet).beat);
+        }
+        assert!(analyzer.analyze(&loud).beat);
+        // Still loud, not a new beat
+        assert!(!analyzer.analyze(&loud).beat);
+        assert!(!analyzer.analyze(&vec![0.0; FFT_SIZE]).beat);
+    }
+
+    #[test]
+    fn wav_files_are_played_in_a_loop() {
+        let path = std::env::temp_dir().join("lissajous_audio_test.wav");
+        let spec = hound::WavSpec { channels: 2, sample_rate: SAMPLE_RATE, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for sample in sine(100.0, 0.5, SAMPLE_RATE as usize) {
+            let sample = (sample * i16::MAX as f32) as i16;
+            writer.write_sample(sample).unwrap();
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let mut player = WavPlayer::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(player.sample_rate(), SAMPLE_RATE);
+        assert_eq!(player.advance(0.5).len(), FFT_SIZE);
+        // Past the end
+        assert_eq!(player.advance(0.51).len(), FFT_SIZE);
+        let frame = AudioAnalyzer::new(SAMPLE_RATE).analyze(&player.advance(0.1));
+        assert!((frame.bands[0] - 0.5).abs() < 0.05, "{:?}", frame);
+    }
+
+    #[test]
+    fn routes_smooth_and_scale_the_signal() {
+        let mut route = AudioRoute::new(AudioSignal::Beat, "lissajou.phase_x");
+        route.gain = 0.5;
+        route.smoothing = 0.0;
+        let beat = AudioFrame { beat: true, ..AudioFrame::default() };
+        assert_eq!(route.position(route.smooth(0.0, &beat, 0.1)), 0.5);
+
+        route.smoothing = 0.1;
+        let smoothed = route.smooth(0.0, &beat, 0.1);
+        assert!((smoothed - (1.0 - f32::exp(-1.0))).abs() < 1e-6);
+        assert!(route.smooth(smoothed, &AudioFrame::default(), 0.1) < smoothed);
+    }
+}
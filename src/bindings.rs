@@ -0,0 +1,278 @@
+#[cfg(feature = "gui")]
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+const MOUSE_PREFIX: &str = "Mouse ";
+const BUTTON_PREFIX: &str = "Pad ";
+const AXIS_PREFIX: &str = "Axis ";
+
+// An input the user can bind to an action. Names are the ones of the ggez input enums, e.g. Key1, DPadLeft, LeftStickX.
+// Written as text in the config file: "Ctrl+Z", "Mouse Right", "Pad South", "Axis LeftStickX".
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Input {
+    Key { name: String, ctrl: bool, shift: bool, alt: bool },
+    Mouse(String),
+    Button(String),
+    Axis(String),
+}
+
+impl Input {
+    pub fn key(name: &str, ctrl: bool, shift: bool, alt: bool) -> Self {
+        Input::Key { name: name.to_string(), ctrl, shift, alt }
+    }
+
+    pub fn is_axis(&self) -> bool {
+        matches!(self, Input::Axis(_))
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::Key { name, ctrl, shift, alt } => {
+                for (pressed, modifier) in [(ctrl, "Ctrl+"), (shift, "Shift+"), (alt, "Alt+")] {
+                    if *pressed {
+                        write!(f, "{}", modifier)?;
+                    }
+                }
+                write!(f, "{}", name)
+            },
+            Input::Mouse(name) => write!(f, "{}{}", MOUSE_PREFIX, name),
+            Input::Button(name) => write!(f, "{}{}", BUTTON_PREFIX, name),
+            Input::Axis(name) => write!(f, "{}{}", AXIS_PREFIX, name),
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("Empty input".to_string());
+        }
+        if let Some(name) = text.strip_prefix(MOUSE_PREFIX) {
+            return Ok(Input::Mouse(name.to_string()));
+        }
+        if let Some(name) = text.strip_prefix(BUTTON_PREFIX) {
+            return Ok(Input::Button(name.to_string()));
+        }
+        if let Some(name) = text.strip_prefix(AXIS_PREFIX) {
+            return Ok(Input::Axis(name.to_string()));
+        }
+
+        let mut parts: Vec<&str> = text.split('+').collect();
+        let name = parts.pop().filter(|name| !name.is_empty()).ok_or(format!("Missing key in '{}'", text))?;
+        let (mut ctrl, mut shift, mut alt) = (false, false, false);
+        for modifier in parts {
+            match modifier {
+                "Ctrl" => ctrl = true,
+                "Shift" => shift = true,
+                "Alt" => alt = true,
+                _ => return Err(format!("Unknown modifier '{}' in '{}'", modifier, text)),
+            }
+        }
+        Ok(Input::key(name, ctrl, shift, alt))
+    }
+}
+
+impl TryFrom<String> for Input {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<Input> for String {
+    fn from(input: Input) -> Self {
+        input.to_string()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ActionKind {
+    Trigger, // Fired once per key, mouse or gamepad button press
+    Axis, // Follows the position, in [-1, 1], of a gamepad axis
+}
+
+// Something the user can do with an input. The id is also the message key of its label.
+#[derive(Clone, Copy, Debug)]
+pub struct ActionSpec {
+    pub id: &'static str,
+    pub kind: ActionKind,
+    defaults: &'static [&'static str],
+}
+
+impl ActionSpec {
+    pub const fn trigger(id: &'static str, defaults: &'static [&'static str]) -> Self {
+        Self { id, kind: ActionKind::Trigger, defaults }
+    }
+
+    pub const fn axis(id: &'static str, defaults: &'static [&'static str]) -> Self {
+        Self { id, kind: ActionKind::Axis, defaults }
+    }
+
+    pub fn default_inputs(&self) -> Vec<Input> {
+        self.defaults
+            .iter()
+            .map(|text| text.parse().unwrap_or_else(|err| panic!("Default input of {}: {}", self.id, err)))
+            .collect()
+    }
+
+    // Axes only drive axis actions, keys and buttons only trigger actions
+    pub fn accepts(&self, input: &Input) -> bool {
+        input.is_axis() == (self.kind == ActionKind::Axis)
+    }
+}
+
+// A subset of the actions of a curve, for one of the gamepads when several people play together.
+// The id is also the message key of its label.
+#[derive(Clone, Copy, Debug)]
+pub struct GamepadRole {
+    pub id: &'static str,
+    pub actions: &'static [&'static str],
+}
+
+impl GamepadRole {
+    pub const fn new(id: &'static str, actions: &'static [&'static str]) -> Self {
+        Self { id, actions }
+    }
+
+    pub fn allows(&self, action: &str) -> bool {
+        self.actions.contains(&action)
+    }
+}
+
+// The inputs the user bound to actions in place of the default ones
+#[cfg(feature = "gui")]
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bindings {
+    overrides: BTreeMap<String, Vec<Input>>,
+}
+
+#[cfg(feature = "gui")]
+impl Bindings {
+    pub fn inputs(&self, action: &ActionSpec) -> Vec<Input> {
+        match self.overrides.get(action.id) {
+            Some(inputs) => inputs.clone(),
+            None => action.default_inputs(),
+        }
+    }
+
+    pub fn is_default(&self, action: &ActionSpec) -> bool {
+        !self.overrides.contains_key(action.id)
+    }
+
+    // Actions bound to the input, among the given ones
+    pub fn triggered<'a>(&self, input: &Input, actions: &'a [ActionSpec]) -> Vec<&'a ActionSpec> {
+        actions
+            .iter()
+            .filter(|action| action.accepts(input) && self.inputs(action).contains(input))
+            .collect()
+    }
+
+    // Inputs of the action, for the help and the hints
+    pub fn describe(&self, action: &ActionSpec) -> String {
+        self.inputs(action)
+            .iter()
+            .map(Input::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // Returns false when the input cannot drive this kind of action
+    pub fn bind(&mut self, action: &ActionSpec, input: Input) -> bool {
+        if !action.accepts(&input) {
+            return false;
+        }
+        let mut inputs = self.inputs(action);
+        if !inputs.contains(&input) {
+            inputs.push(input);
+        }
+        self.overrides.insert(action.id.to_string(), inputs);
+        true
+    }
+
+    pub fn unbind(&mut self, action: &ActionSpec, input: &Input) {
+        let mut inputs = self.inputs(action);
+        inputs.retain(|bound| bound != input);
+        self.overrides.insert(action.id.to_string(), inputs);
+    }
+
+    pub fn reset(&mut self, action: &ActionSpec) {
+        self.overrides.remove(action.id);
+    }
+
+    pub fn reset_all(&mut self) {
+        self.overrides.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "gui")]
+    const ACTIONS: [ActionSpec; 3] = [
+        ActionSpec::trigger("action.test.undo", &["Ctrl+Z", "Pad LeftThumb"]),
+        ActionSpec::trigger("action.test.zoom", &["Z"]),
+        ActionSpec::axis("action.test.param", &["Axis LeftStickX"]),
+    ];
+
+    #[test]
+    fn inputs_are_written_and_parsed() {
+        for text in ["Ctrl+Shift+Z", "Key1", "Mouse Right", "Pad DPadLeft", "Axis RightStickY"] {
+            let input: Input = text.parse().unwrap();
+            assert_eq!(input.to_string(), text);
+        }
+        assert_eq!("Ctrl+Z".parse(), Ok(Input::key("Z", true, false, false)));
+        assert!("Hyper+Z".parse::<Input>().is_err());
+        assert!("Ctrl+".parse::<Input>().is_err());
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn modifiers_must_match() {
+        let bindings = Bindings::default();
+        let ids = |input: &str| -> Vec<&str> {
+            bindings.triggered(&input.parse().unwrap(), &ACTIONS).iter().map(|action| action.id).collect()
+        };
+        assert_eq!(ids("Ctrl+Z"), ["action.test.undo"]);
+        assert_eq!(ids("Z"), ["action.test.zoom"]);
+        assert!(ids("Shift+Z").is_empty());
+        assert_eq!(ids("Axis LeftStickX"), ["action.test.param"]);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn rebinding_replaces_the_defaults() {
+        let mut bindings = Bindings::default();
+        let zoom = &ACTIONS[1];
+        assert!(bindings.bind(zoom, "Pad East".parse().unwrap()));
+        bindings.unbind(zoom, &"Z".parse().unwrap());
+        assert!(!bindings.bind(zoom, "Axis LeftStickY".parse().unwrap()));
+
+        assert_eq!(bindings.describe(zoom), "Pad East");
+        assert!(bindings.triggered(&"Z".parse().unwrap(), &ACTIONS).is_empty());
+        assert!(!bindings.is_default(zoom));
+
+        bindings.reset(zoom);
+        assert_eq!(bindings.describe(zoom), "Z");
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn bindings_serialization() {
+        let mut bindings = Bindings::default();
+        bindings.bind(&ACTIONS[1], "Mouse Middle".parse().unwrap());
+        let json = serde_json::to_string(&bindings).unwrap();
+        assert_eq!(json, r#"{"action.test.zoom":["Z","Mouse Middle"]}"#);
+        assert_eq!(serde_json::from_str::<Bindings>(&json).unwrap(), bindings);
+    }
+}
@@ -0,0 +1,157 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::curve_registry::{CurveRegistration, CurveRegistry};
+use crate::export;
+
+pub const DEFAULT_FILE: &str = "lissajous_bookmarks.json";
+pub const THUMBNAIL_SIZE: u32 = 72;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub curve: String, // Name of the curve registration
+    pub state: Value,
+    pub thumbnail: String, // PNG, base64 encoded
+}
+
+impl Bookmark {
+    // The thumbnail is rendered by a new instance of the curve, so that the displayed one keeps its caches
+    pub fn new(name: &str, registration: &CurveRegistration, state: Value) -> Result<Self, String> {
+        let mut curve = (registration.factory)();
+        curve.restore_state(&state)?;
+        let png = export::render_raster(curve.as_mut(), THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .encode_png()
+            .map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            name: name.to_string(),
+            curve: registration.name.clone(),
+            state,
+            thumbnail: BASE64.encode(png),
+        })
+    }
+
+    pub fn thumbnail_png(&self) -> Option<Vec<u8>> {
+        BASE64.decode(&self.thumbnail).ok()
+    }
+}
+
+// Bookmarks of all the curves, saved in a JSON file after each change
+pub struct Bookmarks {
+    path: Option<PathBuf>,
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    // Not saved anywhere
+    pub fn in_memory(bookmarks: Vec<Bookmark>) -> Self {
+        Self {
+            path: None,
+            bookmarks,
+        }
+    }
+
+    // Reads the file, or starts from the default bookmarks of the curves when it does not exist yet
+    pub fn load(path: &Path, registry: &CurveRegistry) -> Result<Self, String> {
+        let bookmarks = if path.exists() {
+            let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+            serde_json::from_str(&json).map_err(|err| format!("{}: {}", path.display(), err))?
+        } else {
+            Self::defaults(registry)
+        };
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            bookmarks,
+        })
+    }
+
+    pub fn defaults(registry: &CurveRegistry) -> Vec<Bookmark> {
+        let mut bookmarks = vec![];
+        for registration in registry.registrations() {
+            for (name, state) in (registration.factory)().default_bookmarks() {
+                match Bookmark::new(&name, registration, state) {
+                    Ok(bookmark) => bookmarks.push(bookmark),
+                    Err(err) => eprintln!("Default bookmark {} of {} ignored: {}", name, registration.name, err),
+                }
+            }
+        }
+        bookmarks
+    }
+
+    pub fn all(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Bookmark> {
+        self.bookmarks.get(index)
+    }
+
+    // Indices of the bookmarks of a curve
+    pub fn of_curve<'a>(&'a self, curve: &'a str) -> impl Iterator<Item = usize> + 'a {
+        self.bookmarks
+            .iter()
+            .enumerate()
+            .filter(move |(_, bookmark)| bookmark.curve == curve)
+            .map(|(i, _)| i)
+    }
+
+    pub fn add(&mut self, bookmark: Bookmark) -> Result<(), String> {
+        self.bookmarks.push(bookmark);
+        self.save()
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<(), String> {
+        if index < self.bookmarks.len() {
+            self.bookmarks.remove(index);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        match &self.path {
+            Some(path) => {
+                let json = serde_json::to_string_pretty(&self.bookmarks).map_err(|err| err.to_string())?;
+                fs::write(path, json).map_err(|err| format!("{}: {}", path.display(), err))
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remarkable_points_are_seeded() {
+        let registry = CurveRegistry::default();
+        let bookmarks = Bookmarks::in_memory(Bookmarks::defaults(&registry));
+        assert_eq!(bookmarks.of_curve("curve.mandelbrot").count(), 7);
+        assert!(bookmarks.all().iter().all(|bookmark| bookmark.thumbnail_png().is_some()));
+    }
+
+    #[test]
+    fn bookmarks_are_saved_and_loaded() {
+        let registry = CurveRegistry::default();
+        let path = std::env::temp_dir().join(format!("lissajous_bookmarks_test_{}.json", std::process::id()));
+        let registration = &registry.registrations()[0];
+        let state = (registration.factory)().save_state();
+
+        let mut bookmarks = Bookmarks::load(&path, &registry).unwrap();
+        let nb_defaults = bookmarks.all().len();
+        bookmarks.add(Bookmark::new("Mon favori", registration, state.clone()).unwrap()).unwrap();
+
+        let loaded = Bookmarks::load(&path, &registry).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.all().len(), nb_defaults + 1);
+        let bookmark = loaded.all().last().unwrap();
+        assert_eq!(bookmark.name, "Mon favori");
+        assert_eq!(bookmark.curve, registration.name);
+        assert_eq!(bookmark.state, state);
+    }
+}
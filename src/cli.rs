@@ -0,0 +1,123 @@
+use std::path::PathBuf;
+use crate::curve_registry::CurveRegistry;
+use crate::export;
+use crate::i18n::tr;
+
+const DEFAULT_SIZE: u32 = 1024;
+const DEFAULT_SAMPLE_RATE: u32 = 48000;
+const DEFAULT_DURATION: f32 = 10.0;
+const DEFAULT_DRAWINGS_PER_SECOND: f32 = 50.0;
+
+pub const USAGE: &str = "\
+Usage:
+  lissajous                                    open the window (requires the gui feature)
+  lissajous list                               list the curves
+  lissajous render <curve> [options]           draw a curve into a file
+  lissajous oscilloscope <curve> [options]     write the pen path of a curve as XY stereo audio
+
+Render options:
+  --output <file>      .png or .svg file, named after the curve parameters by default
+  --size <w>x<h>       size of the image, 1024x1024 by default
+
+Oscilloscope options, for the curves drawn by a pen:
+  --output <file>      .wav file, named after the curve parameters by default
+  --rate <hz>          sample rate, 48000 by default
+  --duration <s>       length of the sound, 10 seconds by default
+  --frequency <hz>     drawings per second, 50 by default
+
+<curve> is a curve name, in any language, or its number in the list.";
+
+pub fn run(args: &[String], registry: &CurveRegistry) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        Some("list") => {
+            list(registry);
+            Ok(())
+        },
+        Some("render") => render(&args[1..], registry),
+        Some("oscilloscope") => oscilloscope(&args[1..], registry),
+        Some("help") | Some("--help") | Some("-h") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        Some(command) => Err(format!("Unknown command '{}'\n\n{}", command, USAGE)),
+    }
+}
+
+fn list(registry: &CurveRegistry) {
+    for category in registry.categories() {
+        println!("{}", tr(category));
+        for (i, registration) in registry.registrations().iter().enumerate() {
+            if registration.category == category {
+                println!("  {}. {}", i + 1, tr(&registration.name));
+            }
+        }
+    }
+}
+
+fn render(args: &[String], registry: &CurveRegistry) -> Result<(), String> {
+    let curve_arg = args.first().ok_or("Missing curve")?;
+    let index = registry.find(curve_arg).ok_or(format!("Unknown curve '{}'", curve_arg))?;
+    let mut output = None;
+    let (mut width, mut height) = (DEFAULT_SIZE, DEFAULT_SIZE);
+
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().ok_or(format!("Missing value for {}", option));
+        match option.as_str() {
+            "--output" | "-o" => output = Some(PathBuf::from(value()?)),
+            "--size" => (width, height) = parse_size(value()?)?,
+            _ => return Err(format!("Unknown option '{}'", option)),
+        }
+    }
+
+    let mut curve = (registry.registrations()[index].factory)();
+    let output = output.unwrap_or_else(|| PathBuf::from(curve.screenshot_file_name()).with_extension("png"));
+    export::export(curve.as_mut(), &output, width, height)?;
+    println!("{} written to {}", tr(curve.name()), output.display());
+    Ok(())
+}
+
+fn oscilloscope(args: &[String], registry: &CurveRegistry) -> Result<(), String> {
+    let curve_arg = args.first().ok_or("Missing curve")?;
+    let index = registry.find(curve_arg).ok_or(format!("Unknown curve '{}'", curve_arg))?;
+    let mut output = None;
+    let mut sample_rate = DEFAULT_SAMPLE_RATE;
+    let mut duration = DEFAULT_DURATION;
+    let mut frequency = DEFAULT_DRAWINGS_PER_SECOND;
+
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().ok_or(format!("Missing value for {}", option));
+        match option.as_str() {
+            "--output" | "-o" => output = Some(PathBuf::from(value()?)),
+            "--rate" => sample_rate = parse_positive(option, value()?)?,
+            "--duration" => duration = parse_positive(option, value()?)?,
+            "--frequency" => frequency = parse_positive(option, value()?)?,
+            _ => return Err(format!("Unknown option '{}'", option)),
+        }
+    }
+
+    let curve = (registry.registrations()[index].factory)();
+    let output = output.unwrap_or_else(|| PathBuf::from(curve.screenshot_file_name()).with_extension("wav"));
+    export::export_xy_wav(curve.as_ref(), &output, sample_rate, duration, frequency)?;
+    println!("{} written to {}", tr(curve.name()), output.display());
+    Ok(())
+}
+
+fn parse_positive<T: std::str::FromStr + PartialOrd + Default>(option: &str, value: &str) -> Result<T, String> {
+    match value.parse() {
+        Ok(number) if number > T::default() => Ok(number),
+        _ => Err(format!("Invalid value '{}' for {}, expected a positive number", value, option)),
+    }
+}
+
+fn parse_size(size: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("Invalid size '{}', expected <width>x<height>", size);
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+    let width = width.parse().map_err(|_| invalid())?;
+    let height = height.parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width, height))
+}
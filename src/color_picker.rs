@@ -1,24 +1,37 @@
-use ggez::event::{Axis, Button, MouseButton};
-use ggez::GameResult;
-use ggez::glam::Vec2;
-use ggez::graphics::{Color, DrawMode, DrawParam, MeshBuilder, Rect};
-use ggez::input::keyboard::{KeyCode, KeyInput};
+#[cfg(feature = "gui")]
+use ggez::event::MouseButton;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "gui")]
 use ggegui::egui::Color32;
-use crate::interactive_curve::DrawData;
-use crate::interactive_curve::DrawData::Meshes;
+use crate::bindings::ActionSpec;
+use crate::render::{DrawData, Rgba};
+#[cfg(feature = "gui")]
 use crate::utils;
 
 const SPACE_SIZE: f32 = 0.75;
 const TARGET_SIZE: f32 = 0.02;
-const TARGET_TOLERANCE: f32 = 0.0001;
 const TARGET_STROKE_WIDTH: f32 = 0.005;
-const TARGET_COLOR: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+const TARGET_COLOR: Rgba = Rgba::new(0.0, 0.0, 0.0, 1.0);
 const MARGIN: f32 = 0.05;
 const STEPS_H: usize = 360;
 const STEPS_V: usize = 64;
 const STEPS_X: f32 = SPACE_SIZE / (STEPS_H as f32);
 const STEPS_Y: f32 = SPACE_SIZE / (STEPS_V as f32);
+pub const ACTION_HUE: &str = "action.color.hue";
+pub const ACTION_SATURATION: &str = "action.color.saturation";
+pub const ACTION_VALUE_DOWN: &str = "action.color.value_down";
+pub const ACTION_VALUE_UP: &str = "action.color.value_up";
+// To add to the actions of the curves displaying a picker
+pub const ACTIONS: [ActionSpec; 4] = [
+    ActionSpec::axis(ACTION_HUE, &["Axis LeftStickX"]),
+    ActionSpec::axis(ACTION_SATURATION, &["Axis LeftStickY"]),
+    ActionSpec::trigger(ACTION_VALUE_DOWN, &["Down", "Pad South"]),
+    ActionSpec::trigger(ACTION_VALUE_UP, &["Up", "Pad East"]),
+];
 
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub struct HSV {
     hue: f32,
     saturation: f32,
@@ -27,7 +40,7 @@ pub struct HSV {
 
 impl HSV {
     pub fn new(hue: f32, saturation: f32, value: f32) -> HSV {
-        if hue < 0.0 || hue >= 360.0 {
+        if !(0.0..360.0).contains(&hue) {
             panic!("h must be between 0 and 360");
         }
         HSV{ hue, saturation, value }
@@ -39,27 +52,28 @@ impl HSV {
         let m = value * (1.0 - saturation);
         let x = c * (1.0 - f32::abs((hue / 60.0) % 2.0 - 1.0));
         let (r, g, b) = match hue {
-            _ if 0.0   <= hue && hue < 60.0  => (c, x, 0.0),
-            _ if 60.0  <= hue && hue < 120.0 => (x, c, 0.0),
-            _ if 120.0 <= hue && hue < 180.0 => (0.0, c, x),
-            _ if 180.0 <= hue && hue < 240.0 => (0.0, x, c),
-            _ if 240.0 <= hue && hue < 300.0 => (x, 0.0, c),
-            _ if 300.0 <= hue && hue < 360.0 => (c, 0.0, x),
+            _ if (0.0..60.0).contains(&hue) => (c, x, 0.0),
+            _ if (60.0..120.0).contains(&hue) => (x, c, 0.0),
+            _ if (120.0..180.0).contains(&hue) => (0.0, c, x),
+            _ if (180.0..240.0).contains(&hue) => (0.0, x, c),
+            _ if (240.0..300.0).contains(&hue) => (x, 0.0, c),
+            _ if (300.0..360.0).contains(&hue) => (c, 0.0, x),
             _ => panic!("h should be between 0 and 360")
         };
 
         (r + m, g + m, b + m)
     }
 
-    pub fn color(hue: f32, saturation: f32, value: f32) -> Color {
+    pub fn color(hue: f32, saturation: f32, value: f32) -> Rgba {
         let rgb = Self::rgb(hue, saturation, value);
-        Color::new(rgb.0, rgb.1, rgb.2, 1.0)
+        Rgba::rgb(rgb.0, rgb.1, rgb.2)
     }
 
-    pub fn to_color(&self) -> Color {
+    pub fn to_color(&self) -> Rgba {
         Self::color(self.hue, self.saturation, self.value)
     }
 
+    #[cfg(feature = "gui")]
     pub fn to_color32(&self) -> Color32 {
         let (r, g, b) = Self::rgb(self.hue, self.saturation, self.value);
         Color32::from_rgb((r * 255.).round() as u8, (g * 255.).round() as u8, (b * 255.).round() as u8)
@@ -87,10 +101,8 @@ impl ColorPicker {
         }
     }
 
-    fn params(&self, size: f32, dest: Vec2) -> DrawParam {
-        let scaling = Vec2::new(size, size);
-        let left_top_dest = dest - size / 2.0;
-        DrawParam::new().dest(left_top_dest).scale(scaling)
+    fn left_top_dest(&self) -> Vec2 {
+        self.last_dest - self.last_size / 2.0
     }
 
     pub fn set_view(&mut self, screen_size: Vec2, screen_dest: Vec2) {
@@ -104,8 +116,10 @@ impl ColorPicker {
         self.last_dest = dest;
     }
 
-    pub fn meshes(&self) -> GameResult<DrawData> {
-        let mut builder = MeshBuilder::new();
+    pub fn meshes(&self) -> DrawData<'static> {
+        let mut builder = DrawData::new()
+            .dest(self.left_top_dest())
+            .scale(Vec2::splat(self.last_size));
 
         // Color space
         for hi in 0..STEPS_H {
@@ -115,80 +129,101 @@ impl ColorPicker {
                 let x = hue * STEPS_X;
                 let y = (si as f32) * STEPS_Y;
                 let color = HSV::color(hue, saturation, self.current_pick.value);
-                builder.rectangle(DrawMode::fill(), Rect::new(x, y, STEPS_X, STEPS_Y), color)?;
+                builder.rectangle(Vec2::new(x, y), Vec2::new(STEPS_X, STEPS_Y), color);
             }
         }
 
         // Target
         let target_center = Vec2::new(self.current_pick.hue / 360.0 * SPACE_SIZE, self.current_pick.saturation * SPACE_SIZE);
-        builder.circle(DrawMode::stroke(TARGET_STROKE_WIDTH), target_center, TARGET_SIZE, TARGET_TOLERANCE, TARGET_COLOR)?;
-        builder.rectangle(DrawMode::fill(), Rect::new(SPACE_SIZE, (1.0 - self.current_pick.value) * SPACE_SIZE, MARGIN, TARGET_STROKE_WIDTH), TARGET_COLOR)?;
+        builder.circle(target_center, TARGET_SIZE, Some(TARGET_STROKE_WIDTH), TARGET_COLOR);
+        builder.rectangle(Vec2::new(SPACE_SIZE, (1.0 - self.current_pick.value) * SPACE_SIZE), Vec2::new(MARGIN, TARGET_STROKE_WIDTH), TARGET_COLOR);
 
         // Picked color
         let picked_color = self.current_pick.to_color();
-        builder.rectangle(DrawMode::fill(), Rect::new(0.0, SPACE_SIZE + MARGIN, 1.0, 1.0 - SPACE_SIZE - MARGIN), picked_color)?;
-        builder.rectangle(DrawMode::fill(), Rect::new(SPACE_SIZE + MARGIN, 0.0, 1.0 - SPACE_SIZE - MARGIN, SPACE_SIZE + MARGIN), picked_color)?;
+        builder.rectangle(Vec2::new(0.0, SPACE_SIZE + MARGIN), Vec2::new(1.0, 1.0 - SPACE_SIZE - MARGIN), picked_color);
+        builder.rectangle(Vec2::new(SPACE_SIZE + MARGIN, 0.0), Vec2::new(1.0 - SPACE_SIZE - MARGIN, SPACE_SIZE + MARGIN), picked_color);
 
-        Ok(Meshes(builder, self.params(self.last_size, self.last_dest)))
+        builder
     }
 
+    #[cfg(feature = "gui")]
     fn disp(&self) {
         println!("{}(hue: {} sat: {} val: {})", self.name, self.current_pick.hue, self.current_pick.saturation, self.current_pick.value);
     }
 
-    pub fn color(&self) -> Color {
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    pub fn hsv(&self) -> HSV {
+        self.current_pick
+    }
+
+    pub fn set_hsv(&mut self, hsv: HSV) {
+        self.current_pick = hsv;
+    }
+
+    pub fn color(&self) -> Rgba {
         self.current_pick.to_color()
     }
 
+    #[cfg(feature = "gui")]
     pub fn color32(&self) -> Color32 {
         self.current_pick.to_color32()
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_hue(&mut self, hue: f32) {
         self.current_pick.hue = hue;
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_saturation(&mut self, saturation: f32) {
         self.current_pick.saturation = saturation;
     }
 
+    #[cfg(feature = "gui")]
     fn incr_value(&mut self, incr: f32) {
         if (self.current_pick.value > 0.0 && incr < 0.0) || (self.current_pick.value < 1.0 && incr > 0.0) {
             self.current_pick.value += incr;
         }
     }
 
-    pub fn adjust_for_button(self: &mut Self, btn: Button) {
-        match btn {
-            Button::South => self.incr_value(-0.25),
-            Button::East => self.incr_value(0.25),
+    #[cfg(feature = "gui")]
+    pub fn on_trigger(&mut self, action: &str) {
+        match action {
+            ACTION_VALUE_DOWN => self.incr_value(-0.25),
+            ACTION_VALUE_UP => self.incr_value(0.25),
             _ => ()
         }
     }
 
-    pub fn adjust_for_key(self: &mut Self, key: KeyInput) {
-        match key.keycode {
-            Some(KeyCode::Down) => self.incr_value(-0.25),
-            Some(KeyCode::Up) => self.incr_value(0.25),
+    #[cfg(feature = "gui")]
+    pub fn on_axis(&mut self, action: &str, value: f32) {
+        match action {
+            ACTION_HUE          => self.adjust_hue(utils::normalize(value, 359.9)),
+            ACTION_SATURATION   => self.adjust_saturation(1.0 - utils::normalize(value, 1.0)),
             _ => ()
         }
     }
 
-    pub fn adjust_for_axis(&mut self, axis: Axis, value: f32) {
-        match axis {
-            Axis::LeftStickX    => self.adjust_hue(utils::normalize(value, 359.9)),
-            Axis::LeftStickY    => self.adjust_saturation(1.0 - utils::normalize(value, 1.0)),
+    #[cfg(feature = "gui")]
+    pub fn on_axis_relative(&mut self, action: &str, delta: f32) {
+        match action {
+            ACTION_HUE          => self.adjust_hue((self.current_pick.hue + delta * 360.0).rem_euclid(360.0).min(359.9)),
+            ACTION_SATURATION   => self.adjust_saturation((self.current_pick.saturation - delta).clamp(0.0, 1.0)),
             _ => ()
         }
     }
 
+    #[cfg(feature = "gui")]
     pub fn adjust_for_click(&mut self, button: MouseButton, x: f32, y: f32) {
         if button == MouseButton::Left {
-            let left_top_dest = self.last_dest - self.last_size / 2.0;
-            let space_area = Rect::new(left_top_dest.x, left_top_dest.y, self.last_size * SPACE_SIZE, self.last_size * SPACE_SIZE);
-            if space_area.contains(Vec2::new(x, y)) {
-                let diff_x = x - left_top_dest.x;
-                let diff_y = y - left_top_dest.y;
+            let left_top_dest = self.left_top_dest();
+            let diff_x = x - left_top_dest.x;
+            let diff_y = y - left_top_dest.y;
+            let space_size = self.last_size * SPACE_SIZE;
+            if (0.0..=space_size).contains(&diff_x) && (0.0..=space_size).contains(&diff_y) {
                 self.adjust_hue(diff_x / (self.last_size * SPACE_SIZE) * 360.0);
                 self.adjust_saturation(diff_y / (self.last_size * SPACE_SIZE));
                 self.disp();
@@ -197,3 +232,47 @@ impl ColorPicker {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rgb(actual: (f32, f32, f32), expected: (f32, f32, f32)) {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        assert!(
+            close(actual.0, expected.0) && close(actual.1, expected.1) && close(actual.2, expected.2),
+            "{:?} != {:?}", actual, expected
+        );
+    }
+
+    #[test]
+    fn rgb_of_primary_and_secondary_hues() {
+        assert_rgb(HSV::rgb(0., 1., 1.), (1., 0., 0.));
+        assert_rgb(HSV::rgb(60., 1., 1.), (1., 1., 0.));
+        assert_rgb(HSV::rgb(120., 1., 1.), (0., 1., 0.));
+        assert_rgb(HSV::rgb(180., 1., 1.), (0., 1., 1.));
+        assert_rgb(HSV::rgb(240., 1., 1.), (0., 0., 1.));
+        assert_rgb(HSV::rgb(300., 1., 1.), (1., 0., 1.));
+    }
+
+    #[test]
+    fn rgb_of_greys() {
+        assert_rgb(HSV::rgb(0., 0., 0.), (0., 0., 0.));
+        assert_rgb(HSV::rgb(200., 0., 0.5), (0.5, 0.5, 0.5));
+        assert_rgb(HSV::rgb(359., 0., 1.), (1., 1., 1.));
+    }
+
+    #[test]
+    fn rgb_reference_values() {
+        // From https://www.rapidtables.com/convert/color/hsv-to-rgb.html
+        assert_rgb(HSV::rgb(30., 0.5, 0.8), (0.8, 0.6, 0.4));
+        assert_rgb(HSV::rgb(210., 0.75, 0.6), (0.15, 0.375, 0.6));
+        assert_rgb(HSV::rgb(330., 1., 0.5), (0.5, 0., 0.25));
+    }
+
+    #[test]
+    #[should_panic]
+    fn hue_must_be_below_360() {
+        HSV::new(360., 1., 1.);
+    }
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::audio::AudioRoutes;
+use crate::bindings::Bindings;
+use crate::gamepad::GamepadSettings;
+use crate::i18n::Language;
+use crate::layout::Layout;
+use crate::midi::MidiMappings;
+
+pub const DEFAULT_FILE: &str = "lissajous_config.json";
+
+// User settings of the application, saved as JSON after each change. Missing fields take their default value.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub language: Language,
+    pub bindings: Bindings,
+    pub gamepad: GamepadSettings,
+    pub midi: MidiMappings,
+    pub audio: AudioRoutes,
+    pub layout: Layout,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            language: Language::French,
+            bindings: Bindings::default(),
+            gamepad: GamepadSettings::default(),
+            midi: MidiMappings::default(),
+            audio: AudioRoutes::default(),
+            layout: Layout::default(),
+        }
+    }
+}
+
+impl Config {
+    // The default config when the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        serde_json::from_str(&json).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|err| err.to_string())?;
+        fs::write(path, json).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_take_their_default() {
+        let config: Config = serde_json::from_str(r#"{"language": "English"}"#).unwrap();
+        assert_eq!(config.language, Language::English);
+        assert_eq!(config.bindings, Bindings::default());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::i18n::{self, LANGUAGES};
+use crate::interactive_curve::InteractiveCurve;
+use crate::dejong_curve::DeJongAttractor;
+use crate::harmonograph_curve::Harmonograph;
+use crate::lissajou_curve::Lissajou;
+use crate::mandelbrot_curve::MandelbrotSet;
+
+// Shared by the threads that render thumbnails
+pub type CurveFactory = Box<dyn Fn() -> Box<dyn InteractiveCurve> + Send + Sync>;
+
+pub struct CurveRegistration {
+    pub name: String,
+    pub category: String,
+    pub factory: CurveFactory,
+}
+
+// The curves the application can display, grouped by category.
+// Other crates can register their own curves before running the application.
+pub struct CurveRegistry {
+    registrations: Vec<CurveRegistration>,
+}
+
+impl CurveRegistry {
+    pub fn empty() -> Self {
+        Self {
+            registrations: vec![],
+        }
+    }
+
+    pub fn register<F>(&mut self, name: &str, category: &str, factory: F) -> &mut Self
+    where
+        F: Fn() -> Box<dyn InteractiveCurve> + Send + Sync + 'static
+    {
+        self.registrations.push(CurveRegistration {
+            name: name.to_string(),
+            category: category.to_string(),
+            factory: Box::new(factory),
+        });
+        self
+    }
+
+    pub fn registrations(&self) -> &[CurveRegistration] {
+        &self.registrations
+    }
+
+    pub fn len(&self) -> usize {
+        self.registrations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registrations.is_empty()
+    }
+
+    // Categories, in registration order
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = vec![];
+        for registration in &self.registrations {
+            if !categories.contains(&registration.category.as_str()) {
+                categories.push(&registration.category);
+            }
+        }
+        categories
+    }
+
+    // Index of the curve with this number, from 1 in registration order, or with this name. The name is the
+    // message key, or the name in any language.
+    pub fn find(&self, name_or_number: &str) -> Option<usize> {
+        if let Ok(number) = name_or_number.parse::<usize>() {
+            return (1..=self.len()).contains(&number).then(|| number - 1);
+        }
+        let name = name_or_number.to_lowercase();
+        self.registrations
+            .iter()
+            .position(|registration| {
+                registration.name.to_lowercase() == name
+                    || LANGUAGES.iter().any(|language| i18n::tr_in(*language, &registration.name).to_lowercase() == name)
+            })
+    }
+
+    pub fn instantiate(&self) -> Vec<Box<dyn InteractiveCurve>> {
+        self.registrations
+            .iter()
+            .map(|registration| (registration.factory)())
+            .collect()
+    }
+}
+
+impl Default for CurveRegistry {
+    // The curves shipped with this crate
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        DeJongAttractor::register(&mut registry);
+        Lissajou::register(&mut registry);
+        Harmonograph::register(&mut registry);
+        MandelbrotSet::register(&mut registry);
+        registry
+    }
+}
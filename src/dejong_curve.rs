@@ -1,91 +1,164 @@
+#[cfg(feature = "gui")]
 use std::collections::HashMap;
 use std::f32::consts::PI;
 use std::fmt::{Display, Formatter};
+#[cfg(feature = "gui")]
 use ggegui::egui::{Color32, Pos2, Rect, Rounding, Stroke, Vec2 as EGVec2};
+#[cfg(feature = "gui")]
 use ggegui::egui::{Sense, Ui};
-use ggez::event::{Axis, Button};
-use ggez::{Context, GameResult};
-use ggez::glam::Vec2;
-use ggez::graphics::{Color, DrawParam, MeshBuilder};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::bindings::{ActionSpec, GamepadRole};
+use crate::curve_registry::CurveRegistry;
 use crate::interactive_curve::{DrawData, InteractiveCurve};
-use crate::interactive_curve::DrawData::Meshes;
+use crate::params::ParamSpec;
+use crate::render::Rgba;
+use crate::i18n::tr_args;
+#[cfg(feature = "gui")]
+use crate::i18n::tr;
 
 // Inspiration: http://paulbourke.net/fractals/peterdejong/
 
+#[cfg(feature = "gui")]
 const EPSILON: f32 = 0.01;
 const MAX_TRIANGLES: u32 = 2_560_000;
 const SIZE_RATIO: f32 = 0.9;
+const NAME: &str = "curve.dejong";
+const CATEGORY: &str = "category.attractors";
+const ACTION_A: &str = "action.dejong.a";
+const ACTION_B: &str = "action.dejong.b";
+const ACTION_C: &str = "action.dejong.c";
+const ACTION_D: &str = "action.dejong.d";
+const ACTION_PIN: &str = "action.dejong.pin";
+const ACTION_RESET_ITERATIONS: &str = "action.dejong.reset_iterations";
+const ACTION_FEWER_ITERATIONS: &str = "action.dejong.fewer_iterations";
+const ACTION_MORE_ITERATIONS: &str = "action.dejong.more_iterations";
+const ACTIONS: [ActionSpec; 8] = [
+    ActionSpec::axis(ACTION_A, &["Axis LeftStickX"]),
+    ActionSpec::axis(ACTION_B, &["Axis LeftStickY"]),
+    ActionSpec::axis(ACTION_C, &["Axis RightStickX"]),
+    ActionSpec::axis(ACTION_D, &["Axis RightStickY"]),
+    ActionSpec::trigger(ACTION_PIN, &["Pad LeftTrigger", "Pad RightTrigger"]),
+    ActionSpec::trigger(ACTION_RESET_ITERATIONS, &["Pad North"]),
+    ActionSpec::trigger(ACTION_FEWER_ITERATIONS, &["Pad South"]),
+    ActionSpec::trigger(ACTION_MORE_ITERATIONS, &["Pad East"]),
+];
+const ROLES: [GamepadRole; 2] = [
+    GamepadRole::new("role.dejong.ab", &[ACTION_A, ACTION_B, ACTION_PIN, ACTION_RESET_ITERATIONS, ACTION_FEWER_ITERATIONS, ACTION_MORE_ITERATIONS]),
+    GamepadRole::new("role.dejong.cd", &[ACTION_C, ACTION_D, ACTION_PIN]),
+];
 const DEFAULT_ITERATIONS: u32 = 80000;
 
-pub struct DeJongAttractor {
-    a: f32,
-    b: f32,
-    c: f32,
-    d: f32,
-    nb_iter: u32,
-    pinning_values: bool,
-    values: HashMap<Axis, f32>,
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct DeJongParams {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub nb_iter: u32,
 }
 
-impl DeJongAttractor {
-    pub fn new() -> Self {
+impl DeJongParams {
+    pub fn next_point(&self, prev: Vec2) -> Vec2 {
+        Vec2::new(
+            f32::sin(self.a * prev.y) - f32::cos(self.b * prev.x),
+            f32::sin(self.c * prev.x) - f32::cos(self.d * prev.y),
+        )
+    }
+
+    /// The nb_iter first points of the attractor, starting from the origin. Coordinates are in [-2, 2].
+    pub fn points(&self) -> impl Iterator<Item = Vec2> + '_ {
+        std::iter::successors(Some(Vec2::ZERO), |pt| Some(self.next_point(*pt)))
+            .take(self.nb_iter as usize)
+    }
+}
+
+impl Default for DeJongParams {
+    fn default() -> Self {
         Self {
             a: 1.4,
             b: -2.3,
             c: 2.4,
             d: -2.1,
             nb_iter: DEFAULT_ITERATIONS,
+        }
+    }
+}
+
+pub struct DeJongAttractor {
+    params: DeJongParams,
+    #[cfg(feature = "gui")]
+    pinning_values: bool,
+    #[cfg(feature = "gui")]
+    values: HashMap<String, f32>, // Per axis action
+}
+
+impl DeJongAttractor {
+    pub fn register(registry: &mut CurveRegistry) {
+        registry.register(NAME, CATEGORY, || Box::new(Self::new()));
+    }
+
+    pub fn new() -> Self {
+        Self::with_params(DeJongParams::default())
+    }
+
+    pub fn with_params(params: DeJongParams) -> Self {
+        Self {
+            params,
+            #[cfg(feature = "gui")]
             pinning_values: false,
+            #[cfg(feature = "gui")]
             values: HashMap::new(),
         }
     }
 
-    fn next_point(self: &Self, prev: Vec2) -> Vec2 {
-        return Vec2::new(
-            f32::sin(self.a * prev.y) - f32::cos(self.b * prev.x),
-            f32::sin(self.c * prev.x) - f32::cos(self.d * prev.y),
-        )
+    pub fn params(&self) -> &DeJongParams {
+        &self.params
     }
 
-    fn normalize(value: f32, lower: f32, upper: f32) -> f32 {
-        let norm = (value + 1.0) / 2.0;
-        return lower + norm * (upper - lower);
+    pub fn params_mut(&mut self) -> &mut DeJongParams {
+        &mut self.params
     }
 
-    fn adjust_param_for_axis(&mut self, axis: Axis, value: f32) {
-        let new_value = DeJongAttractor::normalize(value, -PI, PI);
-
-        match axis {
-            Axis::LeftStickX => self.a = new_value,
-            Axis::LeftStickY => self.b = new_value,
-            Axis::RightStickX => self.c = new_value,
-            Axis::RightStickY => self.d = new_value,
-            _ => ()
+    fn param_mut(&mut self, action: &str) -> Option<&mut f32> {
+        match action {
+            ACTION_A => Some(&mut self.params.a),
+            ACTION_B => Some(&mut self.params.b),
+            ACTION_C => Some(&mut self.params.c),
+            ACTION_D => Some(&mut self.params.d),
+            _ => None
         }
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_ab(&mut self, params: EGVec2) {
-        self.a = params.x;
-        self.b = -params.y;
+        self.params.a = params.x;
+        self.params.b = -params.y;
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_cd(&mut self, params: EGVec2) {
-        self.c = params.x;
-        self.d = -params.y;
+        self.params.c = params.x;
+        self.params.d = -params.y;
+    }
+}
+
+impl Default for DeJongAttractor {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Display for DeJongAttractor {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "DE JONG   a {:<6.1} b {:<6.1} c {:<6.1} d {:<6.1}   iter {} (A / B / Y)",
-            self.a, self.b, self.c, self.d, self.nb_iter,
-        )
+        let [a, b, c, d] = [self.params.a, self.params.b, self.params.c, self.params.d].map(|p| format!("{:<6.1}", p));
+        write!(f, "{}", tr_args("dejong.status", &[&a, &b, &c, &d, &self.params.nb_iter]))
     }
 }
 
 impl InteractiveCurve for DeJongAttractor {
+    #[cfg(feature = "gui")]
     fn update_ui(&mut self, ui: &mut Ui) {
         let size = ui.available_size().x / 2.0;
         let radius = size / 2.0;
@@ -93,8 +166,8 @@ impl InteractiveCurve for DeJongAttractor {
         let needle_stroke = Stroke::new(2.0, Color32::BLACK);
         let rounding = Rounding::from(5.0);
         let params = [
-            ("a", "b", EGVec2::new(self.a, -self.b), Color32::DARK_BLUE),
-            ("c", "d", EGVec2::new(self.c, -self.d), Color32::DARK_GREEN),
+            ("a", "b", EGVec2::new(self.params.a, -self.params.b), Color32::DARK_BLUE),
+            ("c", "d", EGVec2::new(self.params.c, -self.params.d), Color32::DARK_GREEN),
         ];
 
         ui.horizontal(|ui| {
@@ -128,79 +201,118 @@ impl InteractiveCurve for DeJongAttractor {
         });
 
         ui.horizontal(|ui| {
-            ui.label("Iterations:");
+            ui.label(tr("common.iterations"));
             if ui.button("-").clicked() {
-                self.nb_iter /= 2;
+                self.params.nb_iter /= 2;
             }
-            ui.label(format!("{}", self.nb_iter));
+            ui.label(format!("{}", self.params.nb_iter));
             if ui.button("+").clicked() {
-                self.nb_iter *= 2;
+                self.params.nb_iter *= 2;
             }
             if ui.button("r").clicked() {
-                self.nb_iter = DEFAULT_ITERATIONS;
+                self.params.nb_iter = DEFAULT_ITERATIONS;
             }
         });
 
     }
 
-    fn compute_drawables(&mut self, _ctx: &mut Context, dest: Vec2, size: Vec2) -> GameResult<Vec<DrawData>> {
+    fn compute_drawables(&mut self, dest: Vec2, size: Vec2) -> Vec<DrawData<'_>> {
         let radius = (SIZE_RATIO * size / 5.0).min_element();
         let tri_size = 1.0 / radius;
-        let color = if self.nb_iter == 80000 { Color::BLACK } else { Color::new(0.3, 0.3, 0.3, 0.4) };
+        let color = if self.params.nb_iter == 80000 { Rgba::BLACK } else { Rgba::new(0.3, 0.3, 0.3, 0.4) };
         let mut result : Vec<DrawData> = vec!();
         let mut pt = Vec2::new(0.0, 0.0);
-        let n_batches = self.nb_iter / MAX_TRIANGLES + 1;
+        let n_batches = self.params.nb_iter / MAX_TRIANGLES + 1;
 
         for batch_nb in 0..n_batches {
-            let mut builder = MeshBuilder::new();
+            let mut vertices = vec![];
             let mut n_triangles = 0;
-            while batch_nb * MAX_TRIANGLES + n_triangles < self.nb_iter && n_triangles < MAX_TRIANGLES {
-                builder.triangles(&[pt, pt + Vec2::new(tri_size, 0.0), pt + Vec2::new(0.0, tri_size)], color)?;
-                pt = self.next_point(pt);
+            while batch_nb * MAX_TRIANGLES + n_triangles < self.params.nb_iter && n_triangles < MAX_TRIANGLES {
+                vertices.extend([pt, pt + Vec2::new(tri_size, 0.0), pt + Vec2::new(0.0, tri_size)]);
+                pt = self.params.next_point(pt);
                 n_triangles += 1;
             }
-            result.push(Meshes(builder, DrawParam::new().dest(dest).scale(Vec2::new(radius, radius))));
+            let mut data = DrawData::new().dest(dest).scale(Vec2::new(radius, radius));
+            data.triangles(vertices, color);
+            result.push(data);
+        }
+
+        result
+    }
+
+    fn actions(&self) -> Vec<ActionSpec> {
+        ACTIONS.to_vec()
+    }
+
+    fn gamepad_roles(&self) -> Vec<GamepadRole> {
+        ROLES.to_vec()
+    }
+
+    // The parameters of the axis actions
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        [ACTION_A, ACTION_B, ACTION_C, ACTION_D].into_iter().map(|id| ParamSpec::new(id, -PI, PI)).collect()
+    }
+
+    fn param(&self, id: &str) -> Option<f32> {
+        match id {
+            ACTION_A => Some(self.params.a),
+            ACTION_B => Some(self.params.b),
+            ACTION_C => Some(self.params.c),
+            ACTION_D => Some(self.params.d),
+            _ => None
         }
+    }
 
-        Ok(result)
+    fn set_param(&mut self, id: &str, value: f32) {
+        if let Some(param) = self.param_mut(id) {
+            *param = value;
+        }
     }
 
-    fn adjust_for_button(self: &mut Self, btn: Button) {
-        match btn {
-            Button::LeftTrigger | Button::RightTrigger => self.pinning_values = true,
-            Button::North => self.nb_iter = DEFAULT_ITERATIONS,
-            Button::South => self.nb_iter /= 2,
-            Button::East => self.nb_iter *= 2,
+    #[cfg(feature = "gui")]
+    fn on_trigger(&mut self, action: &str, _pointer: Vec2) {
+        match action {
+            ACTION_PIN => self.pinning_values = true,
+            ACTION_RESET_ITERATIONS => self.params.nb_iter = DEFAULT_ITERATIONS,
+            ACTION_FEWER_ITERATIONS => self.params.nb_iter /= 2,
+            ACTION_MORE_ITERATIONS => self.params.nb_iter *= 2,
             _ => ()
         }
     }
 
-    fn adjust_for_axis(self: &mut Self, axis: Axis, value: f32) {
-        self.values.insert(axis, value);
+    #[cfg(feature = "gui")]
+    fn on_axis(&mut self, action: &str, value: f32) {
+        self.values.insert(action.to_string(), value);
 
         if self.pinning_values {
             let all_zeroes = self.values.values().all(|v| v.abs() < EPSILON);
             if all_zeroes {
                 self.pinning_values = false;
             }
-            else {
-                return
-            }
         }
         else {
-            self.adjust_param_for_axis(axis, value);
+            self.set_param_position(action, (value + 1.0) / 2.0);
         }
     }
 
+    fn save_state(&self) -> Value {
+        serde_json::to_value(self.params).unwrap()
+    }
+
+    fn restore_state(&mut self, state: &Value) -> Result<(), String> {
+        self.params = serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        Ok(())
+    }
+
     fn screenshot_file_name(&self) -> String {
         format!(
             "dejong_a{}_b{}_c{}_d{}_iter{}",
-            self.a, self.b, self.c, self.d, self.nb_iter,
+            self.params.a, self.params.b, self.params.c, self.params.d, self.params.nb_iter,
         )
     }
 
     fn name(&self) -> &str {
-        "Attracteur de DeJong"
+        NAME
     }
 
     fn inspiration_url(&self) -> &str {
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use rand::Rng;
+use rayon::prelude::*;
+use serde_json::Value;
+use crate::curve_registry::CurveRegistration;
+use crate::export;
+use crate::interactive_curve::InteractiveCurve;
+use crate::params;
+use crate::render_raster::RasterImage;
+
+const MAX_FAVORITES: usize = 2;
+
+// Moves each parameter by up to strength times its range, along its scale
+pub fn mutate(curve: &mut dyn InteractiveCurve, strength: f32, rng: &mut impl Rng) {
+    for spec in curve.param_specs() {
+        let Some(value) = curve.param(spec.id) else { continue };
+        let position = spec.position(value) + rng.gen_range(-strength..=strength);
+        curve.set_param(spec.id, spec.snap(spec.value_at(position)));
+    }
+}
+
+// Each parameter comes from one of the two parents. The rest of the state is the one of the curve.
+pub fn crossover(curve: &mut dyn InteractiveCurve, other: &BTreeMap<String, f32>, rng: &mut impl Rng) {
+    for spec in curve.param_specs() {
+        if let Some(value) = other.get(spec.id).filter(|_| rng.gen_bool(0.5)) {
+            curve.set_param(spec.id, *value);
+        }
+    }
+}
+
+// Genetic exploration of the parameters of a curve: a parent state and its variants, from which the next parent is
+// chosen, or two favorites to cross over. Works for every curve through its parameter descriptions.
+pub struct Exploration {
+    curve: String, // Name of the curve registration
+    pub states: Vec<Value>, // The parent first, then its variants
+    pub favorites: Vec<usize>, // Indices in states, the oldest first
+    pub generation: usize,
+}
+
+impl Exploration {
+    pub fn new(
+        registration: &CurveRegistration,
+        parent: Value,
+        variants: usize,
+        strength: f32,
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        let states = Self::offspring(registration, &parent, None, variants, strength, rng)?;
+        Ok(Self { curve: registration.name.clone(), states, favorites: vec![], generation: 0 })
+    }
+
+    pub fn curve(&self) -> &str {
+        &self.curve
+    }
+
+    // The state at this index becomes the parent of the next generation
+    pub fn breed(
+        &mut self,
+        registration: &CurveRegistration,
+        index: usize,
+        variants: usize,
+        strength: f32,
+        rng: &mut impl Rng,
+    ) -> Result<(), String> {
+        let parent = self.states.get(index).ok_or("No such variant")?.clone();
+        self.next_generation(Self::offspring(registration, &parent, None, variants, strength, rng)?);
+        Ok(())
+    }
+
+    // The next generation comes from the two favorites
+    pub fn cross(
+        &mut self,
+        registration: &CurveRegistration,
+        variants: usize,
+        strength: f32,
+        rng: &mut impl Rng,
+    ) -> Result<(), String> {
+        let [first, second] = self.favorites[..] else {
+            return Err(format!("Cross over needs {} favorites", MAX_FAVORITES));
+        };
+        let mut other = (registration.factory)();
+        other.restore_state(&self.states[second])?;
+        let other = params::values(other.as_ref());
+        let parent = self.states[first].clone();
+        self.next_generation(Self::offspring(registration, &parent, Some(&other), variants, strength, rng)?);
+        Ok(())
+    }
+
+    // At most two favorites: the oldest one is replaced
+    pub fn toggle_favorite(&mut self, index: usize) {
+        if let Some(position) = self.favorites.iter().position(|favorite| *favorite == index) {
+            self.favorites.remove(position);
+        } else if index < self.states.len() {
+            if self.favorites.len() == MAX_FAVORITES {
+                self.favorites.remove(0);
+            }
+            self.favorites.push(index);
+        }
+    }
+
+    // Rendered in parallel, each by its own instance of the curve
+    pub fn render_thumbnails(&self, registration: &CurveRegistration, size: u32) -> Result<Vec<RasterImage>, String> {
+        self.states
+            .par_iter()
+            .map(|state| {
+                let mut curve = (registration.factory)();
+                curve.restore_state(state)?;
+                Ok(export::render_raster(curve.as_mut(), size, size))
+            })
+            .collect()
+    }
+
+    fn next_generation(&mut self, states: Vec<Value>) {
+        self.states = states;
+        self.favorites.clear();
+        self.generation += 1;
+    }
+
+    // The parent, crossed over with the other one if any, then the variants
+    fn offspring(
+        registration: &CurveRegistration,
+        parent: &Value,
+        other: Option<&BTreeMap<String, f32>>,
+        variants: usize,
+        strength: f32,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<Value>, String> {
+        let mut curve = (registration.factory)();
+        let mut states = vec![];
+        for variant in 0..=variants {
+            curve.restore_state(parent)?;
+            if let Some(other) = other {
+                crossover(curve.as_mut(), other, rng);
+            }
+            if variant > 0 {
+                mutate(curve.as_mut(), strength, rng);
+            }
+            states.push(curve.save_state());
+        }
+        Ok(states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::curve_registry::CurveRegistry;
+
+    const STRENGTH: f32 = 0.1;
+
+    #[test]
+    fn favorites_are_at_most_two() {
+        let registry = CurveRegistry::default();
+        let registration = &registry.registrations()[0];
+        let parent = (registration.factory)().save_state();
+        let mut exploration = Exploration::new(registration, parent, 4, STRENGTH, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(exploration.cross(registration, 4, STRENGTH, &mut StdRng::seed_from_u64(1)).is_err());
+
+        for index in [1, 2, 3] {
+            exploration.toggle_favorite(index);
+        }
+        assert_eq!(exploration.favorites, [2, 3]);
+        exploration.toggle_favorite(2);
+        assert_eq!(exploration.favorites, [3]);
+        exploration.toggle_favorite(9);
+        assert_eq!(exploration.favorites, [3]);
+    }
+}
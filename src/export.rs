@@ -0,0 +1,102 @@
+use std::path::Path;
+use glam::Vec2;
+use crate::i18n::tr;
+use crate::interactive_curve::InteractiveCurve;
+use crate::render::{DrawData, Rgba};
+use crate::render_raster::RasterImage;
+use crate::render_svg;
+
+const BACKGROUND: Rgba = Rgba::WHITE;
+// Peak of the oscilloscope signal, under the full scale to leave some headroom
+const XY_AMPLITUDE: f32 = 0.9;
+
+// Draws the curve centered in a width x height area, as the application would without its side panel
+fn drawables(curve: &mut dyn InteractiveCurve, width: u32, height: u32) -> Vec<DrawData<'_>> {
+    let size = Vec2::new(width as f32, height as f32);
+    curve.compute_drawables(size / 2.0, size)
+}
+
+pub fn render_raster(curve: &mut dyn InteractiveCurve, width: u32, height: u32) -> RasterImage {
+    let mut image = RasterImage::new(width, height, BACKGROUND);
+    image.draw(&drawables(curve, width, height));
+    image
+}
+
+pub fn render_svg(curve: &mut dyn InteractiveCurve, width: u32, height: u32) -> String {
+    render_svg::to_svg(width, height, Some(BACKGROUND), &drawables(curve, width, height))
+}
+
+// The format is chosen from the extension: .svg, or .png by default
+pub fn export(curve: &mut dyn InteractiveCurve, path: &Path, width: u32, height: u32) -> Result<(), String> {
+    let is_svg = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        std::fs::write(path, render_svg(curve, width, height)).map_err(|err| err.to_string())
+    } else {
+        render_raster(curve, width, height).save_png(path).map_err(|err| err.to_string())
+    }
+}
+
+// Stereo samples tracing the pen path on an XY oscilloscope: left is X, right is Y upwards.
+// The drawing is traced drawings_per_second times per second, going linearly from one point of the path to the next.
+pub fn xy_samples(path: &[Vec2], sample_rate: u32, duration: f32, drawings_per_second: f32) -> Vec<[f32; 2]> {
+    if path.is_empty() {
+        return vec![];
+    }
+    // Same scale on both axes, so that the drawing keeps its proportions
+    let extent = path.iter().map(|point| point.abs().max_element()).fold(0.0, f32::max);
+    let scale = if extent > 0.0 { XY_AMPLITUDE / extent } else { 0.0 };
+    let segments = (path.len() - 1) as f32;
+
+    let count = (sample_rate as f32 * duration).round() as usize;
+    (0..count)
+        .map(|n| {
+            let progress = (n as f32 * drawings_per_second / sample_rate as f32).fract() * segments;
+            let index = progress as usize;
+            let next = path[(index + 1).min(path.len() - 1)];
+            let point = path[index].lerp(next, progress.fract()) * scale;
+            [point.x, -point.y]
+        })
+        .collect()
+}
+
+// 16 bits stereo WAV file of the pen path, for an XY oscilloscope
+pub fn export_xy_wav(
+    curve: &dyn InteractiveCurve,
+    path: &Path,
+    sample_rate: u32,
+    duration: f32,
+    drawings_per_second: f32,
+) -> Result<(), String> {
+    let pen_path = curve.pen_path();
+    if pen_path.is_empty() {
+        return Err(format!("{} is not drawn by a pen", tr(curve.name())));
+    }
+    let spec = hound::WavSpec { channels: 2, sample_rate, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+    let error = |err: hound::Error| format!("{}: {}", path.display(), err);
+    let mut writer = hound::WavWriter::create(path, spec).map_err(error)?;
+    for frame in xy_samples(&pen_path, sample_rate, duration, drawings_per_second) {
+        for sample in frame {
+            writer.write_sample((sample * i16::MAX as f32) as i16).map_err(error)?;
+        }
+    }
+    writer.finalize().map_err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_trace_the_path_with_y_upwards() {
+        // A square, drawn once per 8 samples
+        let square = [Vec2::new(-1.0, -1.0), Vec2::new(1.0, -1.0), Vec2::new(1.0, 1.0), Vec2::new(-1.0, 1.0), Vec2::new(-1.0, -1.0)];
+        let samples = xy_samples(&square, 8000, 0.002, 1000.0);
+        assert_eq!(samples.len(), 16);
+        let a = XY_AMPLITUDE;
+        assert_eq!(samples[0], [-a, a]);
+        assert_eq!(samples[1], [0.0, a]);
+        assert_eq!(samples[2], [a, a]);
+        assert_eq!(samples[4], [a, -a]);
+        assert_eq!(samples[8], samples[0]);
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum AxisMode {
+    // The stick position is the parameter value, over its whole range
+    #[default]
+    Absolute,
+    // The stick deflection is the rate of change of the parameter, for fine tuning
+    Relative,
+}
+
+pub const AXIS_MODES: [AxisMode; 2] = [AxisMode::Absolute, AxisMode::Relative];
+
+impl AxisMode {
+    // Message key, see i18n
+    pub fn name(&self) -> &'static str {
+        match self {
+            AxisMode::Absolute => "gamepad.absolute",
+            AxisMode::Relative => "gamepad.relative",
+        }
+    }
+}
+
+// How the deflection out of the dead zone is turned into a command. The steeper curves give finer control around the center.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    Quadratic,
+    Cubic,
+}
+
+pub const RESPONSE_CURVES: [ResponseCurve; 3] = [ResponseCurve::Linear, ResponseCurve::Quadratic, ResponseCurve::Cubic];
+
+impl ResponseCurve {
+    // Message key, see i18n
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResponseCurve::Linear => "gamepad.linear",
+            ResponseCurve::Quadratic => "gamepad.quadratic",
+            ResponseCurve::Cubic => "gamepad.cubic",
+        }
+    }
+
+    fn apply(&self, value: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => value,
+            ResponseCurve::Quadratic => value * value,
+            ResponseCurve::Cubic => value * value * value,
+        }
+    }
+}
+
+// How the axes drive the parameters of a curve
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AxisSettings {
+    pub mode: AxisMode,
+    pub dead_zone: f32, // In relative mode, deflections under it are ignored
+    pub response: ResponseCurve, // In relative mode
+    pub sensitivity: f32, // In relative mode, fraction of the parameter range per second at full deflection
+}
+
+impl Default for AxisSettings {
+    fn default() -> Self {
+        Self {
+            mode: AxisMode::Absolute,
+            dead_zone: 0.1,
+            response: ResponseCurve::Quadratic,
+            sensitivity: 0.25,
+        }
+    }
+}
+
+impl AxisSettings {
+    // Axis value in [-1, 1] after the dead zone and the response curve. The dead zone is removed from the range,
+    // so that the command starts from 0 at its edge. In absolute mode the stick position maps linearly to the value,
+    // so it is kept as is.
+    pub fn shape(&self, value: f32) -> f32 {
+        if self.mode == AxisMode::Absolute {
+            return value;
+        }
+        let deflection = value.abs().min(1.0);
+        if deflection <= self.dead_zone {
+            return 0.0;
+        }
+        let deflection = (deflection - self.dead_zone) / (1.0 - self.dead_zone);
+        value.signum() * self.response.apply(deflection)
+    }
+
+    // Change of the parameter, as a fraction of its range, after dt seconds at this shaped value
+    pub fn rate(&self, shaped: f32, dt: f32) -> f32 {
+        shaped * self.sensitivity * dt
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadSettings {
+    pub axes: BTreeMap<String, AxisSettings>, // Per curve registration name, the defaults when missing
+    pub roles: BTreeMap<String, BTreeMap<usize, String>>, // Role id per curve registration name and pad number. Pads without a role drive everything.
+}
+
+impl GamepadSettings {
+    pub fn axes(&self, curve: &str) -> AxisSettings {
+        self.axes.get(curve).copied().unwrap_or_default()
+    }
+
+    pub fn set_axes(&mut self, curve: &str, settings: AxisSettings) {
+        if settings == AxisSettings::default() {
+            self.axes.remove(curve);
+        } else {
+            self.axes.insert(curve.to_string(), settings);
+        }
+    }
+
+    pub fn role(&self, curve: &str, pad: usize) -> Option<&str> {
+        self.roles.get(curve)?.get(&pad).map(String::as_str)
+    }
+
+    pub fn set_role(&mut self, curve: &str, pad: usize, role: Option<&str>) {
+        let roles = self.roles.entry(curve.to_string()).or_default();
+        match role {
+            Some(role) => roles.insert(pad, role.to_string()),
+            None => roles.remove(&pad),
+        };
+        if roles.is_empty() {
+            self.roles.remove(curve);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(dead_zone: f32, response: ResponseCurve) -> AxisSettings {
+        AxisSettings { mode: AxisMode::Relative, dead_zone, response, ..AxisSettings::default() }
+    }
+
+    #[test]
+    fn dead_zone_is_removed_from_the_range() {
+        let settings = relative(0.2, ResponseCurve::Linear);
+        assert_eq!(settings.shape(0.15), 0.0);
+        assert_eq!(settings.shape(-0.2), 0.0);
+        assert!((settings.shape(0.6) - 0.5).abs() < 1e-6);
+        assert_eq!(settings.shape(1.0), 1.0);
+        assert_eq!(settings.shape(-1.0), -1.0);
+    }
+
+    #[test]
+    fn response_curves_keep_the_sign() {
+        assert_eq!(relative(0.0, ResponseCurve::Quadratic).shape(-0.5), -0.25);
+        assert_eq!(relative(0.0, ResponseCurve::Cubic).shape(0.5), 0.125);
+        assert_eq!(relative(0.0, ResponseCurve::Cubic).shape(-1.0), -1.0);
+    }
+
+    #[test]
+    fn absolute_axes_stay_linear() {
+        let settings = AxisSettings::default();
+        assert_eq!(settings.mode, AxisMode::Absolute);
+        for value in [-1.0, -0.5, -0.05, 0.0, 0.05, 0.3, 1.0] {
+            assert_eq!(settings.shape(value), value);
+        }
+    }
+
+    #[test]
+    fn axes_are_per_curve() {
+        let mut settings = GamepadSettings::default();
+        assert_eq!(settings.axes("curve.dejong"), AxisSettings::default());
+        let tuned = AxisSettings { sensitivity: 0.5, ..relative(0.2, ResponseCurve::Cubic) };
+        settings.set_axes("curve.dejong", tuned);
+        assert_eq!(settings.axes("curve.dejong"), tuned);
+        assert_eq!(settings.axes("curve.harmonograph"), AxisSettings::default());
+        settings.set_axes("curve.dejong", AxisSettings::default());
+        assert!(settings.axes.is_empty());
+    }
+
+    #[test]
+    fn roles_are_per_curve_and_pad() {
+        let mut settings = GamepadSettings::default();
+        settings.set_role("curve.harmonograph", 2, Some("role.harmonograph.pen"));
+        assert_eq!(settings.role("curve.harmonograph", 2), Some("role.harmonograph.pen"));
+        assert_eq!(settings.role("curve.harmonograph", 1), None);
+        assert_eq!(settings.role("curve.dejong", 2), None);
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(serde_json::from_str::<GamepadSettings>(&json).unwrap(), settings);
+
+        settings.set_role("curve.harmonograph", 2, None);
+        assert!(settings.roles.is_empty());
+    }
+}
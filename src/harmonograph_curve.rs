@@ -1,15 +1,32 @@
+#[cfg(feature = "gui")]
 use std::collections::HashMap;
 use std::f32::consts::PI;
 use std::fmt::{Display, Formatter};
-use ggez::event::{Axis, Button, MouseButton};
-use ggez::{Context, GameResult};
-use ggez::glam::Vec2;
-use ggez::graphics::{Color, DrawParam, MeshBuilder};
+#[cfg(feature = "gui")]
+use ggez::event::MouseButton;
+use glam::Vec2;
+#[cfg(feature = "gui")]
+use ggegui::egui;
+#[cfg(feature = "gui")]
+use ggegui::egui::Ui;
+#[cfg(feature = "gui")]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use crate::utils;
+use crate::animation::Animation;
+use crate::bindings::{ActionSpec, GamepadRole};
+use crate::curve_registry::CurveRegistry;
 use crate::interactive_curve::{DrawData, InteractiveCurve};
-use crate::color_picker::{ColorPicker, HSV};
-use crate::interactive_curve::DrawData::Meshes;
+use crate::params::ParamSpec;
+#[cfg(feature = "gui")]
+use crate::params;
+use crate::color_picker::{self, ColorPicker, HSV};
+use crate::render::Rgba;
+use crate::i18n::{tr, tr_args};
 
+const NAME: &str = "curve.harmonograph";
+const CATEGORY: &str = "category.oscillations";
 const SIZE_RATIO: f32 = 0.9;
 const PAPERX: usize = 0;
 const PAPERY: usize = 1;
@@ -21,8 +38,9 @@ const PHASE: usize = 2;
 const DECAY: usize = 3;
 const START_COLOR: usize = 4;
 const END_COLOR: usize = 5;
+#[cfg(feature = "gui")]
 const EPSILON: f32 = 0.01;
-const PARAM_NAMES: [&'static str; 6] = [
+const PARAM_NAMES: [&str; 6] = [
     "< [amp]  freq   phase   decay   startColor   endColor >",
     "<  amp  [freq]  phase   decay   startColor   endColor >",
     "<  amp   freq  [phase]  decay   startColor   endColor >",
@@ -31,25 +49,84 @@ const PARAM_NAMES: [&'static str; 6] = [
     "<  amp   freq   phase   decay   startColor  [endColor]>",
 ];
 
-const NB_ITER: u32 = 30000;
-const T_STEP: f32 = 0.015;
+const PENDULUM_NAMES: [&str; 4] = ["harmonograph.paper_x", "harmonograph.paper_y", "harmonograph.pen_x", "harmonograph.pen_y"];
+#[cfg(feature = "gui")]
+const AXIS_NAMES: [&str; 2] = ["X", "Y"];
+// Per pendulum
+const PENDULUM_ACTIONS: [&str; 4] = [
+    "action.harmonograph.paper_x",
+    "action.harmonograph.paper_y",
+    "action.harmonograph.pen_x",
+    "action.harmonograph.pen_y",
+];
+const ACTION_PREVIOUS_PARAM: &str = "action.harmonograph.previous_param";
+const ACTION_NEXT_PARAM: &str = "action.harmonograph.next_param";
+const ACTION_PIN: &str = "action.harmonograph.pin";
+const ACTIONS: [ActionSpec; 7] = [
+    ActionSpec::axis(PENDULUM_ACTIONS[PAPERX], &["Axis LeftStickX"]),
+    ActionSpec::axis(PENDULUM_ACTIONS[PAPERY], &["Axis LeftStickY"]),
+    ActionSpec::axis(PENDULUM_ACTIONS[PENX], &["Axis RightStickX"]),
+    ActionSpec::axis(PENDULUM_ACTIONS[PENY], &["Axis RightStickY"]),
+    ActionSpec::trigger(ACTION_PREVIOUS_PARAM, &["Pad DPadLeft"]),
+    ActionSpec::trigger(ACTION_NEXT_PARAM, &["Pad DPadRight"]),
+    ActionSpec::trigger(ACTION_PIN, &["Pad LeftTrigger", "Pad RightTrigger"]),
+];
+// The paper gamepad also chooses the parameter and the colors
+const ROLES: [GamepadRole; 2] = [
+    GamepadRole::new("role.harmonograph.paper", &[
+        PENDULUM_ACTIONS[PAPERX], PENDULUM_ACTIONS[PAPERY], ACTION_PREVIOUS_PARAM, ACTION_NEXT_PARAM, ACTION_PIN,
+        color_picker::ACTION_HUE, color_picker::ACTION_SATURATION, color_picker::ACTION_VALUE_DOWN, color_picker::ACTION_VALUE_UP,
+    ]),
+    GamepadRole::new("role.harmonograph.pen", &[PENDULUM_ACTIONS[PENX], PENDULUM_ACTIONS[PENY], ACTION_PIN]),
+];
+const PENDULUM_PARAM_LABELS: [&str; 4] = ["harmonograph.amp", "harmonograph.freq", "harmonograph.phase", "harmonograph.decay"];
+// Per pendulum, then per parameter
+const PARAM_IDS: [[&str; 4]; 4] = [
+    ["harmonograph.paper_x.amp", "harmonograph.paper_x.freq", "harmonograph.paper_x.phase", "harmonograph.paper_x.decay"],
+    ["harmonograph.paper_y.amp", "harmonograph.paper_y.freq", "harmonograph.paper_y.phase", "harmonograph.paper_y.decay"],
+    ["harmonograph.pen_x.amp", "harmonograph.pen_x.freq", "harmonograph.pen_x.phase", "harmonograph.pen_x.decay"],
+    ["harmonograph.pen_y.amp", "harmonograph.pen_y.freq", "harmonograph.pen_y.phase", "harmonograph.pen_y.decay"],
+];
+const PARAM_NB_ITER: &str = "harmonograph.iterations";
+const PARAM_T_STEP: &str = "harmonograph.time_step";
+const MAX_FREQ: f32 = 10.0;
+const MAX_PHASE: f32 = PI / 2.0;
+const MAX_DECAY: f32 = 0.002;
+const NB_ITER_RANGE: (f32, f32) = (1.0, 1_000_000.0);
+const T_STEP_RANGE: (f32, f32) = (0.0001, 1.0);
 
-struct Pendulum {
-    amp: f32, // Note: 2 pendulum in the same axis must have the sum of their amp equal 1.0
-    freq: f32,
-    phase: f32,
-    decay: f32, // Damp factor in exp(-decay*t)
+const DEFAULT_NB_ITER: u32 = 30000;
+const DEFAULT_T_STEP: f32 = 0.015;
+const DEFAULT_ANIMATION_SPEED: f32 = 30.0;
+#[cfg(feature = "gui")]
+const MAX_ANIMATION_SPEED: f32 = 500.0;
+const PEN_SIZE: f32 = 4.0;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Pendulum {
+    pub amp: f32, // Note: 2 pendulum in the same axis must have the sum of their amp equal 1.0
+    pub freq: f32,
+    pub phase: f32,
+    pub decay: f32, // Damp factor in exp(-decay*t)
 }
 
 impl Pendulum {
-    fn new(amp: f32, freq: f32, phase: f32, decay: f32) -> Self {
+    pub fn new(amp: f32, freq: f32, phase: f32, decay: f32) -> Self {
         Self { amp, freq, phase, decay }
     }
 
-    fn position(&self, t: f32) -> f32 {
+    pub fn position(&self, t: f32) -> f32 {
         self.amp * f32::sin(self.freq * t + self.phase) * f32::exp(-self.decay * t)
     }
 
+    #[cfg(feature = "gui")]
+    fn randomize(&mut self, rng: &mut impl Rng) {
+        self.amp = rng.gen_range(0.0..=1.0);
+        self.freq = rng.gen_range(0..=(2.0 * MAX_FREQ) as u32) as f32 / 2.0;
+        self.phase = rng.gen_range(0.0..=MAX_PHASE);
+        self.decay = rng.gen_range(0.0..=MAX_DECAY);
+    }
+
     fn param_value(&self, i: usize) -> f32 {
         match i {
             0 => self.amp,
@@ -61,47 +138,167 @@ impl Pendulum {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct HarmonographParams {
+    pub pendulums: [Pendulum; 4], // Paper X, paper Y, pen X, pen Y
+    pub nb_iter: u32,
+    pub t_step: f32,
+}
+
+impl HarmonographParams {
+    /// Position of the pen on the paper at time t, in [-radius, radius] when amplitudes sum to 1 on each axis.
+    pub fn point(&self, radius_x: f32, radius_y: f32, t: f32) -> Vec2 {
+        Vec2::new(
+            radius_x * (self.pendulums[PAPERX].position(t) + self.pendulums[PENX].position(t)),
+            radius_y * (self.pendulums[PAPERY].position(t) + self.pendulums[PENY].position(t)),
+        )
+    }
+
+    /// The nb_iter first positions of the pen, one every t_step.
+    pub fn points(&self, radius_x: f32, radius_y: f32) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.nb_iter).map(move |i| self.point(radius_x, radius_y, (i as f32) * self.t_step))
+    }
+}
+
+impl Default for HarmonographParams {
+    fn default() -> Self {
+        Self {
+            pendulums: [
+                Pendulum::new(0.25, 7.5, 0.0, 0.0004),
+                Pendulum::new(0.25, 4.0, 0.0, 0.0004),
+                Pendulum::new(0.75, 1.0, 0.0, 0.0004),
+                Pendulum::new(0.75, 2.0, 0.0, 0.0004),
+            ],
+            nb_iter: DEFAULT_NB_ITER,
+            t_step: DEFAULT_T_STEP,
+        }
+    }
+}
+
+// What is saved for undo and bookmarks
+#[derive(Serialize, Deserialize)]
+struct HarmonographState {
+    params: HarmonographParams,
+    start_color: HSV,
+    end_color: HSV,
+    amp_locked: [bool; 2],
+}
+
 pub struct Harmonograph {
-    pendulums: [Pendulum; 4],
+    params: HarmonographParams,
     displayed_param: usize,
+    #[cfg(feature = "gui")]
     pinning_values: bool,
-    values: HashMap<Axis, f32>,
-    axis_to_pendulum: HashMap<Axis, usize>,
+    #[cfg(feature = "gui")]
+    values: HashMap<String, f32>, // Per axis action
     start_color_picker: ColorPicker,
     end_color_picker: ColorPicker,
+    amp_locked: [bool; 2], // Per axis (X, Y): keeps paper amp + pen amp equal to 1.0
+    animation: Animation,
 }
 
 impl Harmonograph {
+    pub fn register(registry: &mut CurveRegistry) {
+        registry.register(NAME, CATEGORY, || Box::new(Self::new()));
+    }
+
     pub fn new() -> Self {
+        Self::with_params(HarmonographParams::default())
+    }
+
+    pub fn with_params(params: HarmonographParams) -> Self {
         Self {
-            pendulums: [
-                Pendulum::new(0.25, 7.5, 0.0, 0.0004),
-                Pendulum::new(0.25, 4.0, 0.0, 0.0004),
-                Pendulum::new(0.75, 1.0, 0.0, 0.0004),
-                Pendulum::new(0.75, 2.0, 0.0, 0.0004),
-            ],
+            params,
             displayed_param: AMP,
+            #[cfg(feature = "gui")]
             pinning_values: false,
+            #[cfg(feature = "gui")]
             values: HashMap::new(),
-            axis_to_pendulum: [
-                (Axis::LeftStickX, PAPERX),
-                (Axis::LeftStickY, PAPERY),
-                (Axis::RightStickX, PENX),
-                (Axis::RightStickY, PENY),
-            ].iter().cloned().collect(),
             start_color_picker: ColorPicker::new("Start", HSV::new(180.0, 0.75, 0.75), 1./3., Vec2::new(-1./4., 0.)),
             end_color_picker: ColorPicker::new("End", HSV::new(60.0, 0.75, 0.75), 1./3., Vec2::new(1./4., 0.)),
+            amp_locked: [true; 2],
+            animation: Animation::new(DEFAULT_ANIMATION_SPEED),
         }
     }
 
-    fn point(self: &Self, radius_x: f32, radius_y: f32, t: f32) -> Vec2 {
-        return Vec2::new(
-            radius_x * (self.pendulums[PAPERX].position(t) + self.pendulums[PENX].position(t)),
-            radius_y * (self.pendulums[PAPERY].position(t) + self.pendulums[PENY].position(t)),
-        )
+    pub fn params(&self) -> &HarmonographParams {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut HarmonographParams {
+        &mut self.params
+    }
+
+    fn axis_of(pendulum: usize) -> usize {
+        // PAPERX and PENX are even, PAPERY and PENY are odd
+        pendulum % 2
+    }
+
+    fn partner_of(pendulum: usize) -> usize {
+        // The other pendulum on the same axis
+        (pendulum + 2) % 4
+    }
+
+    // Same ranges as the sliders
+    fn param_spec(pendulum: usize, param: usize) -> ParamSpec {
+        let id = PARAM_IDS[pendulum][param];
+        let spec = match param {
+            AMP => ParamSpec::new(id, 0.0, 1.0),
+            FREQ => ParamSpec::new(id, 0.0, MAX_FREQ).step(0.5),
+            PHASE => ParamSpec::new(id, 0.0, MAX_PHASE).unit("rad"),
+            _ => ParamSpec::new(id, 0.0, MAX_DECAY),
+        };
+        spec.label(format!("{} {}", tr(PENDULUM_NAMES[pendulum]), tr(PENDULUM_PARAM_LABELS[param]).trim_end_matches(':')))
+    }
+
+    fn time_spec(id: &'static str) -> ParamSpec {
+        match id {
+            PARAM_NB_ITER => ParamSpec::new(id, NB_ITER_RANGE.0, NB_ITER_RANGE.1).log().step(1.0),
+            _ => ParamSpec::new(id, T_STEP_RANGE.0, T_STEP_RANGE.1).log(),
+        }
+    }
+
+    // The pendulum and the parameter of an id
+    fn param_index(id: &str) -> Option<(usize, usize)> {
+        PARAM_IDS
+            .iter()
+            .enumerate()
+            .find_map(|(pendulum, ids)| Some((pendulum, ids.iter().position(|param_id| *param_id == id)?)))
+    }
+
+    fn adjust_amp(&mut self, pendulum: usize, amp: f32) {
+        self.params.pendulums[pendulum].amp = amp;
+        if self.amp_locked[Harmonograph::axis_of(pendulum)] {
+            self.params.pendulums[Harmonograph::partner_of(pendulum)].amp = 1.0 - amp;
+        }
+    }
+
+    fn end_t(&self) -> f32 {
+        (self.params.nb_iter as f32) * self.params.t_step
+    }
+
+    fn drawn_iterations(&self) -> u32 {
+        if self.animation.is_enabled() {
+            u32::min(self.params.nb_iter, (self.animation.t() / self.params.t_step) as u32 + 1)
+        } else {
+            self.params.nb_iter
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    fn randomize(&mut self) {
+        let mut rng = rand::thread_rng();
+        for pendulum in self.params.pendulums.iter_mut() {
+            pendulum.randomize(&mut rng);
+        }
+        for paper in [PAPERX, PAPERY] {
+            let amp = self.params.pendulums[paper].amp;
+            self.adjust_amp(paper, amp);
+        }
     }
 
-    fn color(&self, t: f32) -> Color {
+
+    fn color(&self, t: f32) -> Rgba {
         let t = t % (2.0 * PI);
         let interpolation = if t <= PI {
             t / PI
@@ -111,35 +308,34 @@ impl Harmonograph {
         utils::interpolate_color(&self.start_color_picker.color(), &self.end_color_picker.color(), interpolation)
     }
 
-    fn adjust_amp_for_axis(&mut self, axis: Axis, value: f32) {
+    #[cfg(feature = "gui")]
+    fn adjust_amp_for_axis(&mut self, pendulum: usize, value: f32) {
         let new_value = utils::normalize(value, 1.0);
 
-        match axis {
-            Axis::LeftStickY => {
-                self.pendulums[PAPERY].amp = new_value;
-                self.pendulums[PENY].amp = 1.0 - new_value;
+        match pendulum {
+            PAPERY => {
+                self.params.pendulums[PAPERY].amp = new_value;
+                self.params.pendulums[PENY].amp = 1.0 - new_value;
             },
-            Axis::RightStickX => {
-                self.pendulums[PAPERX].amp = new_value;
-                self.pendulums[PENX].amp = 1.0 - new_value;
+            PENX => {
+                self.params.pendulums[PAPERX].amp = new_value;
+                self.params.pendulums[PENX].amp = 1.0 - new_value;
             },
             _ => ()
         }
     }
 
-    fn adjust_freq_for_axis(&mut self, axis: Axis, value: f32) {
-        let new_value = utils::normalize(value, 20.0).round() / 2.0;
-        self.pendulums[*self.axis_to_pendulum.get(&axis).unwrap()].freq = new_value;
-    }
-
-    fn adjust_phase_for_axis(&mut self, axis: Axis, value: f32) {
-        let new_value = utils::normalize(value, PI / 2.0);
-        self.pendulums[*self.axis_to_pendulum.get(&axis).unwrap()].phase = new_value;
-    }
-
-    fn adjust_decay_for_axis(&mut self, axis: Axis, value: f32) {
-        let new_value = utils::normalize(value, 0.002);
-        self.pendulums[*self.axis_to_pendulum.get(&axis).unwrap()].decay = new_value;
+    #[cfg(feature = "gui")]
+    fn adjust_amp_relative(&mut self, pendulum: usize, delta: f32) {
+        // Same pendulums as in absolute mode
+        let paper = match pendulum {
+            PAPERY => PAPERY,
+            PENX => PAPERX,
+            _ => return
+        };
+        let amp = (self.params.pendulums[paper].amp + delta).clamp(0.0, 1.0);
+        self.params.pendulums[paper].amp = amp;
+        self.params.pendulums[Harmonograph::partner_of(paper)].amp = 1.0 - amp;
     }
 
     fn mut_displayed_color_picker(&mut self) -> Option<&mut ColorPicker> {
@@ -159,114 +355,273 @@ impl Harmonograph {
     }
 }
 
+impl Default for Harmonograph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Display for Harmonograph {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if self.displayed_param < START_COLOR {
+            let [paper_x, paper_y, pen_x, pen_y] = [PAPERX, PAPERY, PENX, PENY]
+                .map(|pendulum| format!("{:<6.4}", self.params.pendulums[pendulum].param_value(self.displayed_param)));
             write!(
                 f,
-                "HARMONOGRAPH {} [Paper] x {:<6.4} y {:<6.4} [Pen] x {:<6.4} y {:<6.4}",
-                PARAM_NAMES[self.displayed_param],
-                self.pendulums[PAPERX].param_value(self.displayed_param),
-                self.pendulums[PAPERY].param_value(self.displayed_param),
-                self.pendulums[PENX].param_value(self.displayed_param),
-                self.pendulums[PENY].param_value(self.displayed_param),
+                "{}",
+                tr_args("harmonograph.status", &[&PARAM_NAMES[self.displayed_param], &paper_x, &paper_y, &pen_x, &pen_y]),
             )
         }
         else {
-            write!(
-                f,
-                "HARMONOGRAPH {}",
-                PARAM_NAMES[self.displayed_param],
-            )
+            write!(f, "{}", tr_args("harmonograph.status_color", &[&PARAM_NAMES[self.displayed_param]]))
 
         }
     }
 }
 
 impl InteractiveCurve for Harmonograph {
-    fn compute_drawables(&mut self, _ctx: &mut Context, dest: Vec2, size: Vec2) -> GameResult<Vec<DrawData>> {
+    #[cfg(feature = "gui")]
+    fn update_ui(&mut self, ui: &mut Ui) {
+        for (pendulum, pendulum_name) in PENDULUM_NAMES.iter().enumerate() {
+            egui::CollapsingHeader::new(tr(pendulum_name))
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::Grid::new(format!("harmonograph_pendulum_{}", pendulum))
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            let mut amp = self.params.pendulums[pendulum].amp;
+                            ui.label(tr("harmonograph.amp"));
+                            if ui.add(Harmonograph::param_spec(pendulum, AMP).slider(&mut amp).fixed_decimals(3)).changed() {
+                                self.adjust_amp(pendulum, amp);
+                            }
+                            ui.end_row();
+
+                            let [freq_spec, phase_spec, decay_spec] = [FREQ, PHASE, DECAY].map(|param| Harmonograph::param_spec(pendulum, param));
+                            let pendulum = &mut self.params.pendulums[pendulum];
+                            ui.label(tr("harmonograph.freq"));
+                            ui.add(freq_spec.slider(&mut pendulum.freq));
+                            ui.end_row();
+                            ui.label(tr("harmonograph.phase"));
+                            ui.add(phase_spec.slider(&mut pendulum.phase).fixed_decimals(3));
+                            ui.end_row();
+                            ui.label(tr("harmonograph.decay"));
+                            ui.add(decay_spec.slider(&mut pendulum.decay).fixed_decimals(5));
+                            ui.end_row();
+                        });
+                });
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr("harmonograph.lock_amp"));
+            for (axis, axis_name) in AXIS_NAMES.iter().enumerate() {
+                if ui.checkbox(&mut self.amp_locked[axis], *axis_name).changed() && self.amp_locked[axis] {
+                    let paper = if axis == 0 { PAPERX } else { PAPERY };
+                    let amp = self.params.pendulums[paper].amp;
+                    self.adjust_amp(paper, amp);
+                }
+            }
+        });
+        if ui.button(tr("common.random")).clicked() {
+            self.randomize();
+        }
+        ui.separator();
+
+        egui::Grid::new("harmonograph_time")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for spec in [PARAM_NB_ITER, PARAM_T_STEP].map(Harmonograph::time_spec) {
+                    let Some(mut value) = self.param(spec.id) else { continue };
+                    ui.label(tr(spec.id));
+                    if ui.add(spec.slider(&mut value)).changed() {
+                        self.set_param(spec.id, value);
+                    }
+                    ui.end_row();
+                }
+            });
+        if ui.button("r").clicked() {
+            self.params.nb_iter = DEFAULT_NB_ITER;
+            self.params.t_step = DEFAULT_T_STEP;
+        }
+        ui.separator();
+
+        self.animation.update_ui(ui, MAX_ANIMATION_SPEED);
+    }
+
+    fn tick(&mut self, dt: f32) {
+        // The phase drift rotates the pen pendulum in X against the paper
+        let phase_drift = self.animation.tick(dt, self.end_t());
+        if phase_drift != 0.0 {
+            self.params.pendulums[PENX].phase = (self.params.pendulums[PENX].phase + phase_drift).rem_euclid(2.0 * PI);
+        }
+    }
+
+    fn compute_drawables(&mut self, dest: Vec2, size: Vec2) -> Vec<DrawData<'_>> {
         let radius = SIZE_RATIO * size / 2.0;
-        let mut builder = MeshBuilder::new();
-        let mut previous_pt = self.point(radius.x, radius.y, 0.0);
-        for i in 0..NB_ITER {
-            let t = (i as f32) * T_STEP;
-            let pt = self.point(radius.x, radius.y, t);
-            builder.line(&[previous_pt, pt], 1.0, self.color(t))?;
-            previous_pt = pt;
+        let mut points = vec![self.params.point(radius.x, radius.y, 0.0)];
+        let mut colors = vec![];
+        for i in 0..self.drawn_iterations() {
+            let t = (i as f32) * self.params.t_step;
+            points.push(self.params.point(radius.x, radius.y, t));
+            colors.push(self.color(t));
+        }
+        let pen = *points.last().unwrap();
+        let mut meshes = DrawData::new().dest(dest);
+        if !colors.is_empty() {
+            meshes.colored_line(points, 1.0, colors);
+        }
+        if self.animation.is_enabled() {
+            meshes.circle(pen, PEN_SIZE, None, Rgba::RED);
         }
-        let meshes = Meshes(builder, DrawParam::new().dest(dest));
 
-        match self.mut_displayed_color_picker() {
-            Some(picker) => picker.set_view(size, dest),
-            None => ()
+        if let Some(picker) = self.mut_displayed_color_picker() {
+            picker.set_view(size, dest)
         }
         match self.displayed_color_picker() {
-            Some(picker) => Ok(vec!(meshes, picker.meshes()?)),
-            None => Ok(vec!(meshes))
+            Some(picker) => vec!(meshes, picker.meshes()),
+            None => vec!(meshes)
+        }
+    }
+
+    fn actions(&self) -> Vec<ActionSpec> {
+        [ACTIONS.as_slice(), &color_picker::ACTIONS].concat()
+    }
+
+    fn gamepad_roles(&self) -> Vec<GamepadRole> {
+        ROLES.to_vec()
+    }
+
+    // Whole drawing, even during an animation
+    fn pen_path(&self) -> Vec<Vec2> {
+        (0..=self.params.nb_iter)
+            .map(|i| self.params.point(1.0, 1.0, i as f32 * self.params.t_step))
+            .collect()
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        (0..PENDULUM_NAMES.len())
+            .flat_map(|pendulum| [AMP, FREQ, PHASE, DECAY].map(|param| Harmonograph::param_spec(pendulum, param)))
+            .chain([PARAM_NB_ITER, PARAM_T_STEP].map(Harmonograph::time_spec))
+            .collect()
+    }
+
+    fn param(&self, id: &str) -> Option<f32> {
+        match id {
+            PARAM_NB_ITER => Some(self.params.nb_iter as f32),
+            PARAM_T_STEP => Some(self.params.t_step),
+            _ => {
+                let (pendulum, param) = Harmonograph::param_index(id)?;
+                Some(self.params.pendulums[pendulum].param_value(param))
+            },
         }
     }
 
-    fn adjust_for_button(self: &mut Self, btn: Button) {
-        match btn {
-            Button::DPadLeft  => if self.displayed_param > 0 { self.displayed_param = self.displayed_param - 1 },
-            Button::DPadRight => if self.displayed_param < 5 { self.displayed_param = self.displayed_param + 1 },
-            Button::LeftTrigger | Button::RightTrigger => self.pinning_values = true,
+    fn set_param(&mut self, id: &str, value: f32) {
+        match id {
+            PARAM_NB_ITER => self.params.nb_iter = value.round().clamp(NB_ITER_RANGE.0, NB_ITER_RANGE.1) as u32,
+            PARAM_T_STEP => self.params.t_step = value.clamp(T_STEP_RANGE.0, T_STEP_RANGE.1),
+            _ => {
+                let Some((pendulum, param)) = Harmonograph::param_index(id) else { return };
+                match param {
+                    AMP => self.adjust_amp(pendulum, value),
+                    FREQ => self.params.pendulums[pendulum].freq = value,
+                    PHASE => self.params.pendulums[pendulum].phase = value,
+                    _ => self.params.pendulums[pendulum].decay = value,
+                }
+            },
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    fn on_trigger(&mut self, action: &str, _pointer: Vec2) {
+        match action {
+            ACTION_PREVIOUS_PARAM if self.displayed_param > 0 => self.displayed_param -= 1,
+            ACTION_NEXT_PARAM if self.displayed_param < 5 => self.displayed_param += 1,
+            ACTION_PIN => self.pinning_values = true,
             _ => ()
         }
 
-        match self.mut_displayed_color_picker() {
-            Some(picker) => picker.adjust_for_button(btn),
-            None => (),
+        if let Some(picker) = self.mut_displayed_color_picker() {
+            picker.on_trigger(action)
         }
     }
 
-    fn adjust_for_axis(self: &mut Self, axis: Axis, value: f32) {
-        self.values.insert(axis, value);
+    #[cfg(feature = "gui")]
+    fn on_axis(&mut self, action: &str, value: f32) {
+        self.values.insert(action.to_string(), value);
 
         if self.pinning_values {
             let all_zeroes = self.values.values().all(|v| v.abs() < EPSILON);
             if all_zeroes {
                 self.pinning_values = false;
             }
-            else {
-                return
-            }
         }
         else {
-            match self.displayed_param {
-                AMP => self.adjust_amp_for_axis(axis, value),
-                FREQ => self.adjust_freq_for_axis(axis, value),
-                PHASE => self.adjust_phase_for_axis(axis, value),
-                DECAY => self.adjust_decay_for_axis(axis, value),
-                unknown => panic!("Tried to adjust unknown axis {} in Harmonograph", unknown),
+            if let Some(pendulum) = PENDULUM_ACTIONS.iter().position(|pendulum_action| *pendulum_action == action) {
+                match self.displayed_param {
+                    AMP => self.adjust_amp_for_axis(pendulum, value),
+                    FREQ | PHASE | DECAY => self.set_param_position(PARAM_IDS[pendulum][self.displayed_param], (value + 1.0) / 2.0),
+                    _ => () // Colors, adjusted by the picker
+                }
             }
 
-            match self.mut_displayed_color_picker() {
-                Some(picker) => picker.adjust_for_axis(axis, value),
-                None => (),
+            if let Some(picker) = self.mut_displayed_color_picker() {
+                picker.on_axis(action, value)
             }
         }
     }
 
-    fn adjust_for_mouse_button_up(self: &mut Self, button: MouseButton, x: f32, y: f32, _drag_start: Vec2) {
-        match self.mut_displayed_color_picker() {
-            Some(picker) => picker.adjust_for_click(button, x, y),
-            None => (),
+    #[cfg(feature = "gui")]
+    fn on_axis_relative(&mut self, action: &str, delta: f32) {
+        if let Some(pendulum) = PENDULUM_ACTIONS.iter().position(|pendulum_action| *pendulum_action == action) {
+            match self.displayed_param {
+                AMP => self.adjust_amp_relative(pendulum, delta),
+                FREQ | PHASE | DECAY => params::nudge(self, PARAM_IDS[pendulum][self.displayed_param], delta),
+                _ => ()
+            }
+        }
+
+        if let Some(picker) = self.mut_displayed_color_picker() {
+            picker.on_axis_relative(action, delta)
         }
     }
 
+    #[cfg(feature = "gui")]
+    fn adjust_for_mouse_button_up(&mut self, button: MouseButton, x: f32, y: f32, _drag_start: Vec2) {
+        if let Some(picker) = self.mut_displayed_color_picker() {
+            picker.adjust_for_click(button, x, y)
+        }
+    }
+
+    fn save_state(&self) -> Value {
+        serde_json::to_value(HarmonographState {
+            params: self.params,
+            start_color: self.start_color_picker.hsv(),
+            end_color: self.end_color_picker.hsv(),
+            amp_locked: self.amp_locked,
+        }).unwrap()
+    }
+
+    fn restore_state(&mut self, state: &Value) -> Result<(), String> {
+        let state: HarmonographState = serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.params = state.params;
+        self.start_color_picker.set_hsv(state.start_color);
+        self.end_color_picker.set_hsv(state.end_color);
+        self.amp_locked = state.amp_locked;
+        Ok(())
+    }
+
     fn screenshot_file_name(&self) -> String {
         format!(
             "armono_paperx_amp{}_freq{}_ph{}_dec{}_papery_amp{}_freq{}_ph{}_dec{}_penx_amp{}_freq{}_ph{}_dec{}_peny_amp{}_freq{}_ph{}_dec{}",
-            self.pendulums[PAPERX].amp, self.pendulums[PAPERX].freq, self.pendulums[PAPERX].phase, self.pendulums[PAPERX].decay,
-            self.pendulums[PAPERY].amp, self.pendulums[PAPERY].freq, self.pendulums[PAPERY].phase, self.pendulums[PAPERY].decay,
-            self.pendulums[PENX].amp, self.pendulums[PENX].freq, self.pendulums[PENX].phase, self.pendulums[PENX].decay,
-            self.pendulums[PENY].amp, self.pendulums[PENY].freq, self.pendulums[PENY].phase, self.pendulums[PENY].decay
+            self.params.pendulums[PAPERX].amp, self.params.pendulums[PAPERX].freq, self.params.pendulums[PAPERX].phase, self.params.pendulums[PAPERX].decay,
+            self.params.pendulums[PAPERY].amp, self.params.pendulums[PAPERY].freq, self.params.pendulums[PAPERY].phase, self.params.pendulums[PAPERY].decay,
+            self.params.pendulums[PENX].amp, self.params.pendulums[PENX].freq, self.params.pendulums[PENX].phase, self.params.pendulums[PENX].decay,
+            self.params.pendulums[PENY].amp, self.params.pendulums[PENY].freq, self.params.pendulums[PENY].phase, self.params.pendulums[PENY].decay
         )
     }
 
     fn name(&self) -> &str {
-        "Harmonographe"
+        NAME
     }
 
     fn inspiration_url(&self) -> &str {
@@ -0,0 +1,174 @@
+use std::time::{Duration, Instant};
+use serde_json::Value;
+
+// Changes closer than this make a single undo step, e.g. the stream of gamepad axis events of one stick movement
+const COALESCE_DELAY: Duration = Duration::from_millis(500);
+const MAX_UNDO_STEPS: usize = 200;
+
+// Undo/redo stack of the saved states of a curve
+pub struct History {
+    undo: Vec<Value>,
+    redo: Vec<Value>,
+    current: Value,
+    last_change: Option<Instant>,
+}
+
+impl History {
+    pub fn new(state: Value) -> Self {
+        Self {
+            undo: vec![],
+            redo: vec![],
+            current: state,
+            last_change: None,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // To call with the state of the curve after each frame.
+    // continuous is true during a gesture, like a mouse drag, that should make a single step whatever its duration.
+    pub fn record(&mut self, state: Value, now: Instant, continuous: bool) {
+        if let Some(last_change) = self.last_change {
+            if !continuous && now.duration_since(last_change) >= COALESCE_DELAY {
+                self.last_change = None;
+            }
+        }
+        if state == self.current {
+            return;
+        }
+
+        if self.last_change.is_none() {
+            let previous = std::mem::replace(&mut self.current, Value::Null);
+            self.undo.push(previous);
+            if self.undo.len() > MAX_UNDO_STEPS {
+                self.undo.remove(0);
+            }
+            self.redo.clear();
+        }
+        self.current = state;
+        self.last_change = Some(now);
+    }
+
+    // Replaces the current state without making an undo step, e.g. after an animation frame
+    pub fn rebase(&mut self, state: Value) {
+        self.current = state;
+    }
+
+    // Restores the previous state, if any. A state that cannot be restored is dropped, so that the next undo goes
+    // further back, and the history stays on the current state.
+    pub fn undo(&mut self, restore: impl FnOnce(&Value) -> Result<(), String>) -> Result<(), String> {
+        let Some(previous) = self.undo.pop() else { return Ok(()) };
+        restore(&previous)?;
+        self.redo.push(std::mem::replace(&mut self.current, previous));
+        self.last_change = None;
+        Ok(())
+    }
+
+    // Restores the next state, if any, like undo
+    pub fn redo(&mut self, restore: impl FnOnce(&Value) -> Result<(), String>) -> Result<(), String> {
+        let Some(next) = self.redo.pop() else { return Ok(()) };
+        restore(&next)?;
+        self.undo.push(std::mem::replace(&mut self.current, next));
+        self.last_change = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // The state restored by the step, if any
+    fn undo(history: &mut History) -> Option<Value> {
+        let mut restored = None;
+        history.undo(|state| { restored = Some(state.clone()); Ok(()) }).unwrap();
+        restored
+    }
+
+    fn redo(history: &mut History) -> Option<Value> {
+        let mut restored = None;
+        history.redo(|state| { restored = Some(state.clone()); Ok(()) }).unwrap();
+        restored
+    }
+
+    #[test]
+    fn undo_and_redo_discrete_changes() {
+        let start = Instant::now();
+        let mut history = History::new(json!(0));
+        history.record(json!(1), start, false);
+        history.record(json!(2), start + COALESCE_DELAY, false);
+
+        assert_eq!(undo(&mut history), Some(json!(1)));
+        assert_eq!(undo(&mut history), Some(json!(0)));
+        assert_eq!(undo(&mut history), None);
+        assert_eq!(redo(&mut history), Some(json!(1)));
+        assert_eq!(redo(&mut history), Some(json!(2)));
+        assert_eq!(redo(&mut history), None);
+    }
+
+    #[test]
+    fn close_changes_are_coalesced() {
+        let start = Instant::now();
+        let mut history = History::new(json!(0));
+        for i in 1..10 {
+            history.record(json!(i), start + Duration::from_millis(20 * i), false);
+        }
+
+        assert_eq!(undo(&mut history), Some(json!(0)));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn continuous_gestures_are_coalesced() {
+        let start = Instant::now();
+        let mut history = History::new(json!(0));
+        history.record(json!(1), start, true);
+        history.record(json!(1), start + 2 * COALESCE_DELAY, true);
+        history.record(json!(2), start + 3 * COALESCE_DELAY, true);
+        history.record(json!(3), start + 5 * COALESCE_DELAY, false);
+
+        assert_eq!(undo(&mut history), Some(json!(2)));
+        assert_eq!(undo(&mut history), Some(json!(0)));
+    }
+
+    #[test]
+    fn new_change_clears_redo() {
+        let start = Instant::now();
+        let mut history = History::new(json!(0));
+        history.record(json!(1), start, false);
+        undo(&mut history);
+        history.record(json!(5), start + COALESCE_DELAY, false);
+
+        assert!(!history.can_redo());
+        assert_eq!(undo(&mut history), Some(json!(0)));
+    }
+
+    #[test]
+    fn rebase_does_not_make_a_step() {
+        let mut history = History::new(json!(0));
+        history.rebase(json!(1));
+
+        assert!(!history.can_undo());
+        assert_eq!(history.current, json!(1));
+    }
+
+    #[test]
+    fn states_that_cannot_be_restored_are_dropped() {
+        let start = Instant::now();
+        let mut history = History::new(json!(0));
+        history.record(json!(1), start, false);
+        history.record(json!(2), start + COALESCE_DELAY, false);
+
+        assert!(history.undo(|_| Err("Invalid state".to_string())).is_err());
+        assert_eq!(history.current, json!(2));
+        assert!(!history.can_redo());
+        assert_eq!(undo(&mut history), Some(json!(0)));
+    }
+}
@@ -0,0 +1,357 @@
+use std::net::ToSocketAddrs;
+use serde_json::Value;
+#[cfg(feature = "http")]
+use serde_json::{json, Map};
+use crate::curve_registry::CurveRegistry;
+#[cfg(feature = "http")]
+use crate::i18n::tr;
+use crate::interactive_curve::InteractiveCurve;
+#[cfg(feature = "http")]
+use crate::params::{self, Scale};
+
+// Requests that need the window, answered by the application
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowRequest {
+    Screenshot, // Saved as with the screenshot action, answered with the file path
+    Frame,      // The last rendered frame, as PNG
+}
+
+#[cfg(feature = "http")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Route {
+    Curves,
+    Curve,
+    SelectCurve,
+    State,
+    ReplaceState,
+    MergeState,
+    Params,
+    SetParams,
+    Window(WindowRequest),
+}
+
+#[cfg(feature = "http")]
+fn route(method: &str, path: &str) -> Option<Route> {
+    let path = path.split('?').next().unwrap_or_default().trim_end_matches('/');
+    Some(match (method, path) {
+        ("GET", "/curves") => Route::Curves,
+        ("GET", "/curve") => Route::Curve,
+        ("PUT", "/curve") => Route::SelectCurve,
+        ("GET", "/curve/state") => Route::State,
+        ("PUT", "/curve/state") => Route::ReplaceState,
+        ("PATCH", "/curve/state") => Route::MergeState,
+        ("GET", "/curve/params") => Route::Params,
+        ("PUT", "/curve/params") => Route::SetParams,
+        ("POST", "/screenshot") => Route::Window(WindowRequest::Screenshot),
+        ("GET", "/frame.png") => Route::Window(WindowRequest::Frame),
+        _ => return None,
+    })
+}
+
+// The HTTP status and the message of a failed request
+#[cfg(feature = "http")]
+type ApiError = (u16, String);
+
+#[cfg(feature = "http")]
+fn bad_request(message: impl Into<String>) -> ApiError {
+    (400, message.into())
+}
+
+#[cfg(feature = "http")]
+fn curve_json(registry: &CurveRegistry, curves: &[Box<dyn InteractiveCurve>], index: usize) -> Value {
+    let registration = &registry.registrations()[index];
+    json!({
+        "number": index + 1,
+        "name": registration.name,
+        "label": tr(&registration.name),
+        "category": registration.category,
+        "state": curves[index].save_state(),
+    })
+}
+
+// Answers the requests about the curves. Selecting a curve or changing its state goes through the same paths as the
+// bookmarks, so the changes are undoable in the application.
+#[cfg(feature = "http")]
+fn answer(
+    route: Route,
+    body: &str,
+    registry: &CurveRegistry,
+    curves: &mut [Box<dyn InteractiveCurve>],
+    curve_index: &mut usize,
+) -> Result<Value, ApiError> {
+    let parse_body = || serde_json::from_str::<Value>(body).map_err(|err| bad_request(format!("Invalid JSON: {}", err)));
+    match route {
+        Route::Curves => Ok(registry
+            .registrations()
+            .iter()
+            .enumerate()
+            .map(|(index, registration)| json!({
+                "number": index + 1,
+                "name": registration.name,
+                "label": tr(&registration.name),
+                "category": registration.category,
+                "current": index == *curve_index,
+            }))
+            .collect()),
+        Route::Curve => Ok(curve_json(registry, curves, *curve_index)),
+        Route::SelectCurve => {
+            let curve = match parse_body()?.get("curve") {
+                Some(Value::String(name)) => name.clone(),
+                Some(Value::Number(number)) => number.to_string(),
+                _ => return Err(bad_request(r#"Expected {"curve": <name or number>}"#)),
+            };
+            *curve_index = registry.find(&curve).ok_or((404, format!("Unknown curve '{}'", curve)))?;
+            Ok(curve_json(registry, curves, *curve_index))
+        },
+        Route::State => Ok(curves[*curve_index].save_state()),
+        Route::ReplaceState | Route::MergeState => {
+            let mut state = parse_body()?;
+            if route == Route::MergeState {
+                let Value::Object(changes) = state else {
+                    return Err(bad_request("Expected an object of the state fields to change"));
+                };
+                let mut merged = match curves[*curve_index].save_state() {
+                    Value::Object(fields) => fields,
+                    _ => Map::new(),
+                };
+                merged.extend(changes);
+                state = Value::Object(merged);
+            }
+            let curve = &mut curves[*curve_index];
+            let previous = curve.save_state();
+            if let Err(err) = curve.restore_state(&state) {
+                return Err(match curve.restore_state(&previous) {
+                    Ok(()) => bad_request(err),
+                    Err(rollback) => (500, format!("{}, and the previous state cannot be restored: {}", err, rollback)),
+                });
+            }
+            // The state is not limited to the ranges of the controls
+            params::snap_values(curve.as_mut());
+            Ok(curve.save_state())
+        },
+        Route::Params => {
+            let curve = &curves[*curve_index];
+            Ok(curve
+                .param_specs()
+                .into_iter()
+                .map(|spec| json!({
+                    "id": spec.id,
+                    "label": spec.label,
+                    "value": curve.param(spec.id),
+                    "min": spec.min,
+                    "max": spec.max,
+                    "step": spec.step,
+                    "scale": if spec.scale == Scale::Log { "log" } else { "linear" },
+                    "unit": spec.unit,
+                }))
+                .collect())
+        },
+        // Some of the parameters, by id, clamped to their range
+        Route::SetParams => {
+            let values = serde_json::from_value(parse_body()?)
+                .map_err(|_| bad_request(r#"Expected {"<parameter id>": <value>, ...}"#))?;
+            let curve = curves[*curve_index].as_mut();
+            params::set_values(curve, &values).map_err(bad_request)?;
+            Ok(json!(params::values(curve)))
+        },
+        Route::Window(_) => unreachable!("Answered by the application"),
+    }
+}
+
+// JSON API on an HTTP server, to script the application e.g. from Python notebooks. The requests are polled by the
+// application between frames, so they are answered in its thread.
+pub struct HttpApi {
+    #[cfg(feature = "http")]
+    server: tiny_http::Server,
+}
+
+// A request that waits for the application to render or save the frame
+pub struct PendingRequest {
+    pub kind: WindowRequest,
+    #[cfg(feature = "http")]
+    request: tiny_http::Request,
+}
+
+#[cfg(feature = "http")]
+impl HttpApi {
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, String> {
+        let server = tiny_http::Server::http(address).map_err(|err| err.to_string())?;
+        Ok(Self { server })
+    }
+
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    // Answers the requests received since the last call, and returns the ones that need the window
+    pub fn handle(
+        &mut self,
+        registry: &CurveRegistry,
+        curves: &mut [Box<dyn InteractiveCurve>],
+        curve_index: &mut usize,
+    ) -> Vec<PendingRequest> {
+        let mut pending = vec![];
+        while let Ok(Some(mut request)) = self.server.try_recv() {
+            let route = route(request.method().as_str(), request.url());
+            let mut body = String::new();
+            let result = match route {
+                Some(Route::Window(kind)) => {
+                    pending.push(PendingRequest { kind, request });
+                    continue;
+                },
+                None => Err((404, format!("No endpoint {} {}", request.method(), request.url()))),
+                Some(route) => match request.as_reader().read_to_string(&mut body) {
+                    Ok(_) => answer(route, &body, registry, curves, curve_index),
+                    Err(err) => Err(bad_request(err.to_string())),
+                },
+            };
+            respond(request, result);
+        }
+        pending
+    }
+}
+
+#[cfg(feature = "http")]
+fn respond(request: tiny_http::Request, result: Result<Value, ApiError>) {
+    let (status, value) = match result {
+        Ok(value) => (200, value),
+        Err((status, message)) => (status, json!({ "error": message })),
+    };
+    send(request, status, "application/json", value.to_string().into_bytes());
+}
+
+#[cfg(feature = "http")]
+fn send(request: tiny_http::Request, status: u16, content_type: &str, data: Vec<u8>) {
+    let header = tiny_http::Header::from_bytes("Content-Type", content_type).expect("Valid header");
+    let response = tiny_http::Response::from_data(data).with_status_code(status).with_header(header);
+    // The client may be gone
+    let _ = request.respond(response);
+}
+
+#[cfg(feature = "http")]
+impl PendingRequest {
+    pub fn respond_png(self, png: Vec<u8>) {
+        send(self.request, 200, "image/png", png);
+    }
+
+    pub fn respond_json(self, value: Value) {
+        respond(self.request, Ok(value));
+    }
+
+    pub fn respond_error(self, message: &str) {
+        respond(self.request, Err((500, message.to_string())));
+    }
+}
+
+#[cfg(not(feature = "http"))]
+impl HttpApi {
+    pub fn bind(_address: impl ToSocketAddrs) -> Result<Self, String> {
+        Err("Built without the http feature".to_string())
+    }
+
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        None
+    }
+
+    pub fn handle(
+        &mut self,
+        _registry: &CurveRegistry,
+        _curves: &mut [Box<dyn InteractiveCurve>],
+        _curve_index: &mut usize,
+    ) -> Vec<PendingRequest> {
+        vec![]
+    }
+}
+
+#[cfg(not(feature = "http"))]
+impl PendingRequest {
+    pub fn respond_png(self, _png: Vec<u8>) {}
+
+    pub fn respond_json(self, _value: Value) {}
+
+    pub fn respond_error(self, _message: &str) {}
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes() {
+        assert_eq!(route("GET", "/curves/"), Some(Route::Curves));
+        assert_eq!(route("PATCH", "/curve/state?pretty"), Some(Route::MergeState));
+        assert_eq!(route("GET", "/frame.png"), Some(Route::Window(WindowRequest::Frame)));
+        assert_eq!(route("DELETE", "/curve"), None);
+    }
+
+    #[test]
+    fn invalid_states_are_rejected_and_not_applied() {
+        let registry = CurveRegistry::default();
+        let mut curves = registry.instantiate();
+        let mut index = 0;
+        let before = curves[0].save_state();
+        let result = answer(Route::MergeState, r#"{"a": "not a number"}"#, &registry, &mut curves, &mut index);
+        assert_eq!(result.unwrap_err().0, 400);
+        assert_eq!(curves[0].save_state(), before);
+
+        let result = answer(Route::SelectCurve, r#"{"curve": 9}"#, &registry, &mut curves, &mut index);
+        assert_eq!(result.unwrap_err().0, 404);
+        assert_eq!(index, 0);
+    }
+
+    // Without save_state and restore_state
+    struct Stateless;
+
+    impl std::fmt::Display for Stateless {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "stateless")
+        }
+    }
+
+    impl InteractiveCurve for Stateless {
+        fn compute_drawables(&mut self, _dest: glam::Vec2, _size: glam::Vec2) -> Vec<crate::render::DrawData<'_>> {
+            vec![]
+        }
+
+        fn screenshot_file_name(&self) -> String {
+            "stateless".to_string()
+        }
+
+        fn name(&self) -> &str {
+            "stateless"
+        }
+
+        fn inspiration_url(&self) -> &str {
+            ""
+        }
+    }
+
+    #[test]
+    fn restored_states_are_brought_back_in_range() {
+        let registry = CurveRegistry::default();
+        let mut curves = registry.instantiate();
+        let mut index = registry.find("curve.lissajou").unwrap();
+        let mut state = curves[index].save_state();
+        state["params"]["nb_points"] = json!(4_000_000_000u64);
+        state["params"]["jitter_factor"] = json!(-1.0);
+        answer(Route::ReplaceState, &state.to_string(), &registry, &mut curves, &mut index).unwrap();
+        let specs = curves[index].param_specs();
+        for spec in specs {
+            let value = curves[index].param(spec.id).unwrap();
+            assert!((spec.min..=spec.max).contains(&value), "{} is {}", spec.id, value);
+        }
+
+        index = registry.find("curve.mandelbrot").unwrap();
+        let result = answer(Route::MergeState, r#"{"params": {"max_iterations": 0, "box_center": [0, 0], "box_size": [3, 3]}}"#, &registry, &mut curves, &mut index);
+        assert_eq!(result.unwrap()["params"]["max_iterations"], 16);
+    }
+
+    #[test]
+    fn failed_rollbacks_are_server_errors() {
+        let registry = CurveRegistry::default();
+        let mut curves: Vec<Box<dyn InteractiveCurve>> = vec![Box::new(Stateless)];
+        let mut index = 0;
+        let result = answer(Route::ReplaceState, "{}", &registry, &mut curves, &mut index);
+        assert_eq!(result.unwrap_err().0, 500);
+    }
+}
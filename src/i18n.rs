@@ -0,0 +1,504 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Language {
+    French,
+    English,
+}
+
+pub const LANGUAGES: [Language; 2] = [Language::French, Language::English];
+
+// The language is global, so that the Display impls of the curves, used in the window title, are translated too
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+impl Language {
+    // Always in its own language, to find it back whatever the current one
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::French => "Français",
+            Language::English => "English",
+        }
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::French => FRENCH,
+            Language::English => ENGLISH,
+        }
+    }
+}
+
+pub fn language() -> Language {
+    LANGUAGES[LANGUAGE.load(Ordering::Relaxed) as usize]
+}
+
+#[cfg(feature = "gui")]
+pub fn set_language(language: Language) {
+    let index = LANGUAGES.iter().position(|l| *l == language).unwrap();
+    LANGUAGE.store(index as u8, Ordering::Relaxed);
+}
+
+// Translation of a message key in the current language.
+// Unknown keys are returned as is, so that the curves registered by other crates can use plain names.
+pub fn tr(key: &str) -> &str {
+    tr_in(language(), key)
+}
+
+pub fn tr_in(language: Language, key: &str) -> &str {
+    language
+        .catalog()
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map_or(key, |(_, message)| message)
+}
+
+// Translation where each {} is replaced by the next argument
+pub fn tr_args(key: &str, args: &[&dyn Display]) -> String {
+    let mut args = args.iter();
+    let mut parts = tr(key).split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+const FRENCH: &[(&str, &str)] = &[
+    ("app.language", "Langue:"),
+    ("app.search_curve", "Rechercher une courbe"),
+    ("app.undo", "Annuler"),
+    ("app.redo", "Rétablir"),
+    ("app.bookmarks", "Favoris"),
+    ("app.bookmark_name", "Nom du favori"),
+    ("app.add", "Ajouter"),
+    ("app.remove", "Supprimer"),
+    ("app.bindings", "Raccourcis"),
+    ("app.bindings_app", "Application"),
+    ("app.help", "Aide"),
+    ("app.press_input", "Appuyer sur une touche, un bouton ou bouger un axe pour: {}"),
+    ("app.cancel", "Annuler"),
+    ("app.bind", "Ajouter une entrée"),
+    ("app.unbind", "Retirer cette entrée"),
+    ("app.reset_binding", "Revenir aux entrées par défaut"),
+    ("app.reset_all_bindings", "Tout réinitialiser"),
+    ("gamepad.title", "Manette"),
+    ("gamepad.mode", "Mode:"),
+    ("gamepad.absolute", "Absolu"),
+    ("gamepad.relative", "Relatif"),
+    ("gamepad.dead_zone", "Zone morte:"),
+    ("gamepad.response", "Réponse:"),
+    ("gamepad.linear", "Linéaire"),
+    ("gamepad.quadratic", "Quadratique"),
+    ("gamepad.cubic", "Cubique"),
+    ("gamepad.sensitivity", "Sensibilité:"),
+    ("gamepad.sensitivity_hint", "Fraction de la plage du paramètre par seconde, stick à fond"),
+    ("gamepad.pad", "Manette {}"),
+    ("gamepad.all", "Tout"),
+    ("gamepad.no_pad", "Aucune manette détectée"),
+    ("midi.title", "MIDI"),
+    ("midi.ports", "Entrées : {}"),
+    ("midi.no_port", "Aucune entrée MIDI"),
+    ("midi.unavailable", "MIDI indisponible : {}"),
+    ("midi.learn", "Cliquer, puis tourner un bouton du contrôleur pour l'associer"),
+    ("midi.move_control", "Tournez un bouton..."),
+    ("midi.forget", "Oublier les contrôles de ce paramètre"),
+    ("audio.title", "Audio"),
+    ("audio.wav_file", "Fichier WAV"),
+    ("audio.open", "Ouvrir"),
+    ("audio.live", "Entrée audio"),
+    ("audio.stop", "Arrêter"),
+    ("audio.no_source", "Aucune source audio"),
+    ("audio.source", "Source : {}"),
+    ("audio.level", "Niveau"),
+    ("audio.bass", "Graves"),
+    ("audio.mid", "Médiums"),
+    ("audio.treble", "Aigus"),
+    ("audio.beat", "Temps"),
+    ("audio.gain", "Gain:"),
+    ("audio.smoothing", "Lissage:"),
+    ("audio.add_route", "Relier un signal à un paramètre"),
+    ("audio.remove_route", "Supprimer ce lien"),
+    ("params.title", "Paramètres"),
+    ("params.sweep", "Balayer ce paramètre d'un bout à l'autre de sa plage"),
+    ("params.sweep_period", "Période balayage:"),
+    ("params.randomize", "Hasard"),
+    ("explore.title", "Exploration"),
+    ("explore.variants", "Variantes:"),
+    ("explore.strength", "Mutation:"),
+    ("explore.restart", "Repartir de la courbe affichée"),
+    ("explore.generation", "Génération {}"),
+    ("explore.hint", "Cliquez une variante pour en faire le parent de la génération suivante, ou croisez deux favoris."),
+    ("explore.parent", "Parent"),
+    ("explore.variant", "Variante"),
+    ("explore.favorite", "Favori, à croiser"),
+    ("explore.cross", "Croiser les favoris"),
+    ("layout.title", "Écran partagé"),
+    ("layout.columns", "Colonnes:"),
+    ("layout.rows", "Lignes:"),
+    ("layout.linked", "Paramètres liés"),
+    ("layout.linked_hint", "Les changements de paramètres s'appliquent aux autres cases de la même courbe"),
+    ("layout.same_curve", "Courbe affichée dans toutes les cases"),
+    ("layout.hint", "Cliquez une case pour la contrôler."),
+    ("role.dejong.ab", "Paramètres a et b"),
+    ("role.dejong.cd", "Paramètres c et d"),
+    ("role.harmonograph.paper", "Papier"),
+    ("role.harmonograph.pen", "Stylo"),
+    ("role.lissajou.frequencies", "Fréquences et phase"),
+    ("role.lissajou.points", "Points"),
+    ("common.iterations", "Itérations:"),
+    ("common.random", "Aléatoire"),
+    ("category.attractors", "Attracteurs"),
+    ("category.oscillations", "Oscillations"),
+    ("category.fractals", "Fractales"),
+    ("curve.dejong", "Attracteur de DeJong"),
+    ("curve.lissajou", "Courbe de Lissajou"),
+    ("curve.harmonograph", "Harmonographe"),
+    ("curve.mandelbrot", "Ensemble de Mandelbrot"),
+    ("animation.enabled", "Animation"),
+    ("animation.play", "Lecture"),
+    ("animation.pause", "Pause"),
+    ("animation.speed", "Vitesse:"),
+    ("animation.phase_drift", "Dérive phase:"),
+    ("dejong.status", "DE JONG   a {} b {} c {} d {}   itér. {} (A / B / Y)"),
+    ("harmonograph.status", "HARMONOGRAPHE {} [Papier] x {} y {} [Stylo] x {} y {}"),
+    ("harmonograph.status_color", "HARMONOGRAPHE {}"),
+    ("harmonograph.paper_x", "Papier X"),
+    ("harmonograph.paper_y", "Papier Y"),
+    ("harmonograph.pen_x", "Stylo X"),
+    ("harmonograph.pen_y", "Stylo Y"),
+    ("harmonograph.amp", "amp:"),
+    ("harmonograph.freq", "fréq:"),
+    ("harmonograph.phase", "phase:"),
+    ("harmonograph.decay", "amort:"),
+    ("harmonograph.lock_amp", "Verrouiller amp:"),
+    ("harmonograph.iterations", "Itérations:"),
+    ("harmonograph.time_step", "Pas de temps:"),
+    ("lissajou.status", "LISSAJOU fréq-X (U / D): {} fréq-Y (L / R): {} phase (LT / RT): {} points (BLT / BRT): {} gigue (X / Y): {} dist_max (A / B): {}"),
+    ("lissajou.status_z", " fréq-Z: {}"),
+    ("lissajou.freq_x", "Fréquence X:"),
+    ("lissajou.freq_y", "Fréquence Y:"),
+    ("lissajou.freq_z", "Fréquence Z:"),
+    ("lissajou.phase_x", "Phase X:"),
+    ("lissajou.phase_y", "Phase Y:"),
+    ("lissajou.phase_z", "Phase Z:"),
+    ("lissajou.three_d", "3D:"),
+    ("lissajou.yaw", "Lacet:"),
+    ("lissajou.pitch", "Tangage:"),
+    ("lissajou.points", "Points:"),
+    ("lissajou.jitter", "Gigue:"),
+    ("lissajou.seed", "Graine:"),
+    ("lissajou.max_distance", "Distance max:"),
+    ("lissajou.graph", "Graphe:"),
+    ("lissajou.max_edges", "Lignes max:"),
+    ("lissajou.edge_width", "Épaisseur:"),
+    ("lissajou.near_color", "Couleur proche:"),
+    ("lissajou.far_color", "Couleur loin:"),
+    ("graph.all_within", "Tous dans le rayon"),
+    ("graph.k_nearest", "k plus proches"),
+    ("graph.delaunay", "Delaunay"),
+    ("graph.spanning_tree", "Arbre couvrant minimal"),
+    ("mandelbrot.dwell", "Profondeur:"),
+    ("mandelbrot.histogram", "Histogramme"),
+    ("mandelbrot.color_1", "Couleur 1"),
+    ("mandelbrot.color_2", "Couleur 2"),
+    ("mandelbrot.start", "Départ:"),
+    ("mandelbrot.go", "Aller"),
+    ("mandelbrot.position", "Position:"),
+    ("mandelbrot.zoom", "Zoom:"),
+    ("mandelbrot.compute_time", "Temps calcul:"),
+    ("mandelbrot.computed_points", "Points calculés:"),
+    ("mandelbrot.point_iterations", "Itérations au point:"),
+    ("mandelbrot.point.default", "Défaut"),
+    ("mandelbrot.point.feigenbaum", "Feigenbaum"),
+    ("mandelbrot.point.seahorse_valley", "Vallée hippocampes"),
+    ("mandelbrot.point.left_mini", "Mini mandelbrot à gauche"),
+    ("mandelbrot.point.top_julia", "En haut, mini Julia"),
+    ("mandelbrot.point.left_line", "Ligne à gauche"),
+    ("mandelbrot.point.curly_hair", "Cheveux frisés en haut à droite"),
+    ("action.app.undo", "Annuler"),
+    ("action.app.redo", "Rétablir"),
+    ("action.app.next_curve", "Courbe suivante"),
+    ("action.app.screenshot", "Capture d'écran"),
+    ("action.app.oscilloscope", "Son pour oscilloscope XY"),
+    ("action.app.help", "Aide"),
+    ("action.app.explore", "Exploration génétique"),
+    ("action.app.curve_1", "Courbe 1"),
+    ("action.app.curve_2", "Courbe 2"),
+    ("action.app.curve_3", "Courbe 3"),
+    ("action.app.curve_4", "Courbe 4"),
+    ("action.app.curve_5", "Courbe 5"),
+    ("action.app.curve_6", "Courbe 6"),
+    ("action.app.curve_7", "Courbe 7"),
+    ("action.app.curve_8", "Courbe 8"),
+    ("action.app.curve_9", "Courbe 9"),
+    ("action.color.hue", "Teinte"),
+    ("action.color.saturation", "Saturation"),
+    ("action.color.value_down", "Assombrir"),
+    ("action.color.value_up", "Éclaircir"),
+    ("action.dejong.a", "Paramètre a"),
+    ("action.dejong.b", "Paramètre b"),
+    ("action.dejong.c", "Paramètre c"),
+    ("action.dejong.d", "Paramètre d"),
+    ("action.dejong.pin", "Figer les valeurs"),
+    ("action.dejong.reset_iterations", "Itérations par défaut"),
+    ("action.dejong.fewer_iterations", "Moins d'itérations"),
+    ("action.dejong.more_iterations", "Plus d'itérations"),
+    ("action.harmonograph.paper_x", "Pendule papier X"),
+    ("action.harmonograph.paper_y", "Pendule papier Y"),
+    ("action.harmonograph.pen_x", "Pendule stylo X"),
+    ("action.harmonograph.pen_y", "Pendule stylo Y"),
+    ("action.harmonograph.previous_param", "Paramètre précédent"),
+    ("action.harmonograph.next_param", "Paramètre suivant"),
+    ("action.harmonograph.pin", "Figer les valeurs"),
+    ("action.lissajou.freq_x_down", "Fréquence X -"),
+    ("action.lissajou.freq_x_up", "Fréquence X +"),
+    ("action.lissajou.freq_y_down", "Fréquence Y -"),
+    ("action.lissajou.freq_y_up", "Fréquence Y +"),
+    ("action.lissajou.phase_down", "Phase -"),
+    ("action.lissajou.phase_up", "Phase +"),
+    ("action.lissajou.points_down", "Points -"),
+    ("action.lissajou.points_up", "Points +"),
+    ("action.lissajou.jitter_down", "Gigue -"),
+    ("action.lissajou.jitter_up", "Gigue +"),
+    ("action.lissajou.max_distance_down", "Distance max -"),
+    ("action.lissajou.max_distance_up", "Distance max +"),
+    ("action.mandelbrot.point_details", "Calcul au point"),
+    ("action.mandelbrot.zoom_in", "Zoomer"),
+    ("action.mandelbrot.zoom_out", "Dézoomer"),
+    ("action.mandelbrot.reset", "Zoom à 0"),
+    ("action.mandelbrot.histogram", "Histogramme"),
+    ("action.mandelbrot.close_picker", "Fermer le sélecteur de couleur"),
+];
+
+const ENGLISH: &[(&str, &str)] = &[
+    ("app.language", "Language:"),
+    ("app.search_curve", "Search a curve"),
+    ("app.undo", "Undo"),
+    ("app.redo", "Redo"),
+    ("app.bookmarks", "Bookmarks"),
+    ("app.bookmark_name", "Bookmark name"),
+    ("app.add", "Add"),
+    ("app.remove", "Remove"),
+    ("app.bindings", "Shortcuts"),
+    ("app.bindings_app", "Application"),
+    ("app.help", "Help"),
+    ("app.press_input", "Press a key, a button or move an axis for: {}"),
+    ("app.cancel", "Cancel"),
+    ("app.bind", "Add an input"),
+    ("app.unbind", "Remove this input"),
+    ("app.reset_binding", "Restore the default inputs"),
+    ("app.reset_all_bindings", "Reset all"),
+    ("gamepad.title", "Gamepad"),
+    ("gamepad.mode", "Mode:"),
+    ("gamepad.absolute", "Absolute"),
+    ("gamepad.relative", "Relative"),
+    ("gamepad.dead_zone", "Dead zone:"),
+    ("gamepad.response", "Response:"),
+    ("gamepad.linear", "Linear"),
+    ("gamepad.quadratic", "Quadratic"),
+    ("gamepad.cubic", "Cubic"),
+    ("gamepad.sensitivity", "Sensitivity:"),
+    ("gamepad.sensitivity_hint", "Fraction of the parameter range per second, with the stick fully deflected"),
+    ("gamepad.pad", "Gamepad {}"),
+    ("gamepad.all", "Everything"),
+    ("gamepad.no_pad", "No gamepad detected"),
+    ("midi.title", "MIDI"),
+    ("midi.ports", "Inputs: {}"),
+    ("midi.no_port", "No MIDI input"),
+    ("midi.unavailable", "MIDI unavailable: {}"),
+    ("midi.learn", "Click, then move a knob of the controller to bind it"),
+    ("midi.move_control", "Move a knob..."),
+    ("midi.forget", "Forget the controls of this parameter"),
+    ("audio.title", "Audio"),
+    ("audio.wav_file", "WAV file"),
+    ("audio.open", "Open"),
+    ("audio.live", "Audio input"),
+    ("audio.stop", "Stop"),
+    ("audio.no_source", "No audio source"),
+    ("audio.source", "Source: {}"),
+    ("audio.level", "Level"),
+    ("audio.bass", "Bass"),
+    ("audio.mid", "Mid"),
+    ("audio.treble", "Treble"),
+    ("audio.beat", "Beat"),
+    ("audio.gain", "Gain:"),
+    ("audio.smoothing", "Smoothing:"),
+    ("audio.add_route", "Route a signal to a parameter"),
+    ("audio.remove_route", "Remove this route"),
+    ("params.title", "Parameters"),
+    ("params.sweep", "Sweep this parameter back and forth over its range"),
+    ("params.sweep_period", "Sweep period:"),
+    ("params.randomize", "Randomize"),
+    ("explore.title", "Exploration"),
+    ("explore.variants", "Variants:"),
+    ("explore.strength", "Mutation:"),
+    ("explore.restart", "Start again from the displayed curve"),
+    ("explore.generation", "Generation {}"),
+    ("explore.hint", "Click a variant to make it the parent of the next generation, or cross over two favorites."),
+    ("explore.parent", "Parent"),
+    ("explore.variant", "Variant"),
+    ("explore.favorite", "Favorite, to cross over"),
+    ("explore.cross", "Cross over the favorites"),
+    ("layout.title", "Split screen"),
+    ("layout.columns", "Columns:"),
+    ("layout.rows", "Rows:"),
+    ("layout.linked", "Linked parameters"),
+    ("layout.linked_hint", "Parameter changes apply to the other tiles of the same curve"),
+    ("layout.same_curve", "Displayed curve in every tile"),
+    ("layout.hint", "Click a tile to control it."),
+    ("role.dejong.ab", "Parameters a and b"),
+    ("role.dejong.cd", "Parameters c and d"),
+    ("role.harmonograph.paper", "Paper"),
+    ("role.harmonograph.pen", "Pen"),
+    ("role.lissajou.frequencies", "Frequencies and phase"),
+    ("role.lissajou.points", "Points"),
+    ("common.iterations", "Iterations:"),
+    ("common.random", "Random"),
+    ("category.attractors", "Attractors"),
+    ("category.oscillations", "Oscillations"),
+    ("category.fractals", "Fractals"),
+    ("curve.dejong", "De Jong attractor"),
+    ("curve.lissajou", "Lissajous curve"),
+    ("curve.harmonograph", "Harmonograph"),
+    ("curve.mandelbrot", "Mandelbrot set"),
+    ("animation.enabled", "Animation"),
+    ("animation.play", "Play"),
+    ("animation.pause", "Pause"),
+    ("animation.speed", "Speed:"),
+    ("animation.phase_drift", "Phase drift:"),
+    ("dejong.status", "DE JONG   a {} b {} c {} d {}   iter {} (A / B / Y)"),
+    ("harmonograph.status", "HARMONOGRAPH {} [Paper] x {} y {} [Pen] x {} y {}"),
+    ("harmonograph.status_color", "HARMONOGRAPH {}"),
+    ("harmonograph.paper_x", "Paper X"),
+    ("harmonograph.paper_y", "Paper Y"),
+    ("harmonograph.pen_x", "Pen X"),
+    ("harmonograph.pen_y", "Pen Y"),
+    ("harmonograph.amp", "amp:"),
+    ("harmonograph.freq", "freq:"),
+    ("harmonograph.phase", "phase:"),
+    ("harmonograph.decay", "decay:"),
+    ("harmonograph.lock_amp", "Lock amp:"),
+    ("harmonograph.iterations", "Iterations:"),
+    ("harmonograph.time_step", "Time step:"),
+    ("lissajou.status", "LISSAJOU freq-X (U / D): {} freq-Y (L / R): {} phase (LT / RT): {} points (BLT / BRT): {} jitter (X / Y): {} max_dist (A / B): {}"),
+    ("lissajou.status_z", " freq-Z: {}"),
+    ("lissajou.freq_x", "Frequency X:"),
+    ("lissajou.freq_y", "Frequency Y:"),
+    ("lissajou.freq_z", "Frequency Z:"),
+    ("lissajou.phase_x", "Phase X:"),
+    ("lissajou.phase_y", "Phase Y:"),
+    ("lissajou.phase_z", "Phase Z:"),
+    ("lissajou.three_d", "3D:"),
+    ("lissajou.yaw", "Yaw:"),
+    ("lissajou.pitch", "Pitch:"),
+    ("lissajou.points", "Points:"),
+    ("lissajou.jitter", "Jitter:"),
+    ("lissajou.seed", "Seed:"),
+    ("lissajou.max_distance", "Max distance:"),
+    ("lissajou.graph", "Graph:"),
+    ("lissajou.max_edges", "Max lines:"),
+    ("lissajou.edge_width", "Width:"),
+    ("lissajou.near_color", "Near color:"),
+    ("lissajou.far_color", "Far color:"),
+    ("graph.all_within", "All within radius"),
+    ("graph.k_nearest", "k nearest"),
+    ("graph.delaunay", "Delaunay"),
+    ("graph.spanning_tree", "Minimum spanning tree"),
+    ("mandelbrot.dwell", "Dwell:"),
+    ("mandelbrot.histogram", "Histogram"),
+    ("mandelbrot.color_1", "Color 1"),
+    ("mandelbrot.color_2", "Color 2"),
+    ("mandelbrot.start", "Start:"),
+    ("mandelbrot.go", "Go"),
+    ("mandelbrot.position", "Position:"),
+    ("mandelbrot.zoom", "Zoom:"),
+    ("mandelbrot.compute_time", "Compute time:"),
+    ("mandelbrot.computed_points", "Computed points:"),
+    ("mandelbrot.point_iterations", "Iterations at point:"),
+    ("mandelbrot.point.default", "Default"),
+    ("mandelbrot.point.feigenbaum", "Feigenbaum"),
+    ("mandelbrot.point.seahorse_valley", "Seahorse valley"),
+    ("mandelbrot.point.left_mini", "Mini Mandelbrot on the left"),
+    ("mandelbrot.point.top_julia", "Mini Julia at the top"),
+    ("mandelbrot.point.left_line", "Line on the left"),
+    ("mandelbrot.point.curly_hair", "Curly hair at the top right"),
+    ("action.app.undo", "Undo"),
+    ("action.app.redo", "Redo"),
+    ("action.app.next_curve", "Next curve"),
+    ("action.app.screenshot", "Screenshot"),
+    ("action.app.oscilloscope", "XY oscilloscope sound"),
+    ("action.app.help", "Help"),
+    ("action.app.explore", "Genetic exploration"),
+    ("action.app.curve_1", "Curve 1"),
+    ("action.app.curve_2", "Curve 2"),
+    ("action.app.curve_3", "Curve 3"),
+    ("action.app.curve_4", "Curve 4"),
+    ("action.app.curve_5", "Curve 5"),
+    ("action.app.curve_6", "Curve 6"),
+    ("action.app.curve_7", "Curve 7"),
+    ("action.app.curve_8", "Curve 8"),
+    ("action.app.curve_9", "Curve 9"),
+    ("action.color.hue", "Hue"),
+    ("action.color.saturation", "Saturation"),
+    ("action.color.value_down", "Darker"),
+    ("action.color.value_up", "Lighter"),
+    ("action.dejong.a", "Parameter a"),
+    ("action.dejong.b", "Parameter b"),
+    ("action.dejong.c", "Parameter c"),
+    ("action.dejong.d", "Parameter d"),
+    ("action.dejong.pin", "Pin the values"),
+    ("action.dejong.reset_iterations", "Default iterations"),
+    ("action.dejong.fewer_iterations", "Fewer iterations"),
+    ("action.dejong.more_iterations", "More iterations"),
+    ("action.harmonograph.paper_x", "Paper X pendulum"),
+    ("action.harmonograph.paper_y", "Paper Y pendulum"),
+    ("action.harmonograph.pen_x", "Pen X pendulum"),
+    ("action.harmonograph.pen_y", "Pen Y pendulum"),
+    ("action.harmonograph.previous_param", "Previous parameter"),
+    ("action.harmonograph.next_param", "Next parameter"),
+    ("action.harmonograph.pin", "Pin the values"),
+    ("action.lissajou.freq_x_down", "Frequency X -"),
+    ("action.lissajou.freq_x_up", "Frequency X +"),
+    ("action.lissajou.freq_y_down", "Frequency Y -"),
+    ("action.lissajou.freq_y_up", "Frequency Y +"),
+    ("action.lissajou.phase_down", "Phase -"),
+    ("action.lissajou.phase_up", "Phase +"),
+    ("action.lissajou.points_down", "Points -"),
+    ("action.lissajou.points_up", "Points +"),
+    ("action.lissajou.jitter_down", "Jitter -"),
+    ("action.lissajou.jitter_up", "Jitter +"),
+    ("action.lissajou.max_distance_down", "Max distance -"),
+    ("action.lissajou.max_distance_up", "Max distance +"),
+    ("action.mandelbrot.point_details", "Compute at point"),
+    ("action.mandelbrot.zoom_in", "Zoom in"),
+    ("action.mandelbrot.zoom_out", "Zoom out"),
+    ("action.mandelbrot.reset", "Reset zoom"),
+    ("action.mandelbrot.histogram", "Histogram"),
+    ("action.mandelbrot.close_picker", "Close the color picker"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogs_have_the_same_keys() {
+        for (catalog, other) in [(FRENCH, ENGLISH), (ENGLISH, FRENCH)] {
+            for (key, _) in catalog {
+                assert!(other.iter().any(|(k, _)| k == key), "{} is missing a translation", key);
+            }
+        }
+    }
+
+    #[test]
+    fn translations() {
+        assert_eq!(tr_in(Language::French, "curve.mandelbrot"), "Ensemble de Mandelbrot");
+        assert_eq!(tr_in(Language::English, "curve.mandelbrot"), "Mandelbrot set");
+        assert_eq!(tr_in(Language::English, "My own curve"), "My own curve");
+    }
+}
@@ -1,50 +1,125 @@
 use std::fmt::Display;
+#[cfg(feature = "gui")]
 use ggegui::egui::Ui;
-use ggez::event::{Button, Axis, MouseButton};
-use ggez::{Context, GameResult};
-use ggez::glam::Vec2;
-use ggez::graphics::{DrawParam, Image as GImage, MeshBuilder};
-use ggez::input::keyboard::KeyInput;
+#[cfg(feature = "gui")]
+use ggez::event::MouseButton;
+use glam::Vec2;
+use serde_json::Value;
+use crate::bindings::{ActionSpec, GamepadRole};
+use crate::i18n::tr;
+use crate::params::ParamSpec;
+#[cfg(feature = "gui")]
+use crate::params;
+pub use crate::render::DrawData;
 
 pub trait InteractiveCurve: Display {
+    #[cfg(feature = "gui")]
     fn update_ui(&mut self, _ui: &mut Ui) {
         // Default do nothing
     }
-    fn compute_drawables(&mut self, _ctx: &mut Context, _dest: Vec2, _size: Vec2) -> GameResult<Vec<DrawData>>;
+    // Advances animations by dt seconds
+    fn tick(&mut self, _dt: f32) {
+        // Default do nothing
+    }
+
+    fn compute_drawables(&mut self, dest: Vec2, size: Vec2) -> Vec<DrawData<'_>>;
+
+    // Actions the user can bind to keys, mouse or gamepad buttons and axes
+    fn actions(&self) -> Vec<ActionSpec> {
+        vec![]
+    }
+
+    // Roles that can be given to the gamepads, so that each one drives its own actions
+    fn gamepad_roles(&self) -> Vec<GamepadRole> {
+        vec![]
+    }
 
-    fn adjust_for_button(&mut self, _btn: Button) {
+    // A key or button bound to a trigger action was pressed. pointer is the position of the mouse.
+    #[cfg(feature = "gui")]
+    fn on_trigger(&mut self, _action: &str, _pointer: Vec2) {
         // Default do nothing
     }
 
-    fn adjust_for_axis(&mut self, _axis: Axis, _value: f32) {
+    // An axis bound to an axis action moved, value is in [-1, 1]. By default, the axis actions that are
+    // parameters follow the axis over their range.
+    #[cfg(feature = "gui")]
+    fn on_axis(&mut self, action: &str, value: f32) {
+        self.set_param_position(action, (value + 1.0) / 2.0);
+    }
+
+    // Relative mode of the axes: the parameter of the axis action should change by delta times its range
+    #[cfg(feature = "gui")]
+    fn on_axis_relative(&mut self, action: &str, delta: f32) {
+        params::nudge(self, action, delta);
+    }
+
+    // Points of the whole drawing of the curves drawn by a pen, in the order of the pen, in [-1, 1] with y downwards
+    // like on screen. Empty for the other curves.
+    fn pen_path(&self) -> Vec<Vec2> {
+        vec![]
+    }
+
+    // Description of the numeric parameters, from which the generic controls are generated
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![]
+    }
+
+    // Value of a parameter of param_specs
+    fn param(&self, _id: &str) -> Option<f32> {
+        None
+    }
+
+    fn set_param(&mut self, _id: &str, _value: f32) {
         // Default do nothing
     }
 
+    // Position of the parameter in its range, in [0, 1]
+    fn param_position(&self, id: &str) -> Option<f32> {
+        let spec = self.param_specs().into_iter().find(|spec| spec.id == id)?;
+        Some(spec.position(self.param(id)?))
+    }
+
+    // Sets the parameter at this position of its range, on a step
+    fn set_param_position(&mut self, id: &str, position: f32) {
+        if let Some(spec) = self.param_specs().into_iter().find(|spec| spec.id == id) {
+            self.set_param(id, spec.snap(spec.value_at(position)));
+        }
+    }
+
+    #[cfg(feature = "gui")]
     fn adjust_for_mouse_button_up(&mut self, _button: MouseButton, _x: f32, _y: f32, _drag_start: Vec2) {
         // Default do nothing
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_for_mouse_drag(&mut self, _x: f32, _y: f32, _drag_start: Vec2) {
         // Default do nothing
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_for_mouse_wheel(&mut self, _x: f32, _y: f32, _wheel_y_dir: f32) {
         // y is either 1 (one click away) or -1 (one click towards the user)
         // Default do nothing
     }
 
-    fn adjust_for_key_up(&mut self, _input: KeyInput) {
-        // Default do nothing
+    // Snapshot of the parameters, for undo/redo and bookmarks. Null when the curve cannot be saved.
+    fn save_state(&self) -> Value {
+        Value::Null
+    }
+
+    fn restore_state(&mut self, _state: &Value) -> Result<(), String> {
+        Err(format!("{} cannot restore a saved state", tr(self.name())))
+    }
+
+    // Named states offered as bookmarks until the user has saved their own
+    fn default_bookmarks(&self) -> Vec<(String, Value)> {
+        vec![]
     }
 
     fn screenshot_file_name(&self) -> String;
 
+    // Message key of the name in the i18n catalogs, or a plain name that is displayed as is
     fn name(&self) -> &str;
 
     fn inspiration_url(&self) -> &str;
 }
-
-pub enum DrawData<'a> {
-    Meshes(MeshBuilder, DrawParam),
-    Image(&'a GImage, DrawParam)
-}
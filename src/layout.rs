@@ -0,0 +1,78 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_TILES_PER_SIDE: usize = 4;
+
+// Split screen: the view is divided in columns x rows tiles, each with its own curve. With linked controls, the
+// parameter changes of the focused tile also apply to the other tiles of the same curve.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Layout {
+    pub columns: usize,
+    pub rows: usize,
+    pub linked: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self { columns: 1, rows: 1, linked: false }
+    }
+}
+
+impl Layout {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns: columns.clamp(1, MAX_TILES_PER_SIDE),
+            rows: rows.clamp(1, MAX_TILES_PER_SIDE),
+            linked: false,
+        }
+    }
+
+    pub fn tiles(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn is_split(&self) -> bool {
+        self.tiles() > 1
+    }
+
+    // Center and size of each tile of an area, row by row
+    pub fn tile_rects(&self, origin: Vec2, size: Vec2) -> Vec<(Vec2, Vec2)> {
+        let tile_size = size / Vec2::new(self.columns as f32, self.rows as f32);
+        (0..self.tiles())
+            .map(|tile| {
+                let cell = Vec2::new((tile % self.columns) as f32, (tile / self.columns) as f32);
+                (origin + (cell + 0.5) * tile_size, tile_size)
+            })
+            .collect()
+    }
+
+    pub fn tile_at(&self, origin: Vec2, size: Vec2, point: Vec2) -> Option<usize> {
+        let cell = (point - origin) / size * Vec2::new(self.columns as f32, self.rows as f32);
+        if cell.x < 0.0 || cell.y < 0.0 || cell.x >= self.columns as f32 || cell.y >= self.rows as f32 {
+            return None;
+        }
+        Some(cell.y as usize * self.columns + cell.x as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_split_the_area_row_by_row() {
+        let layout = Layout::new(2, 3);
+        let origin = Vec2::new(100.0, 0.0);
+        let size = Vec2::new(200.0, 300.0);
+        let rects = layout.tile_rects(origin, size);
+        assert_eq!(rects.len(), 6);
+        assert_eq!(rects[0], (Vec2::new(150.0, 50.0), Vec2::new(100.0, 100.0)));
+        assert_eq!(rects[3], (Vec2::new(250.0, 150.0), Vec2::new(100.0, 100.0)));
+        for (tile, (center, _)) in rects.iter().enumerate() {
+            assert_eq!(layout.tile_at(origin, size, *center), Some(tile));
+        }
+        assert_eq!(layout.tile_at(origin, size, Vec2::new(50.0, 10.0)), None);
+        assert_eq!(Layout::new(9, 0), Layout::new(MAX_TILES_PER_SIDE, 1));
+    }
+}
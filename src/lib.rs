@@ -1,32 +1,78 @@
+
 extern crate core;
 
 mod interactive_curve;
+mod params;
+mod exploration;
+#[cfg(feature = "gui")]
+mod layout;
+mod curve_registry;
 mod harmonograph_curve;
 mod lissajou_curve;
 mod dejong_curve;
 mod mandelbrot_curve;
+#[cfg(feature = "gui")]
 mod lissajou_app;
 mod color_picker;
+mod animation;
+mod neighbor_graph;
+#[cfg(feature = "gui")]
+mod history;
+mod i18n;
+#[cfg(feature = "gui")]
+mod bookmarks;
+mod bindings;
+#[cfg(feature = "gui")]
+mod config;
+#[cfg(feature = "gui")]
+mod gamepad;
+mod midi;
+#[cfg(feature = "gui")]
+mod audio;
+mod osc;
+mod http_api;
+#[cfg(feature = "gui")]
+mod app_builder;
+mod render;
+#[cfg(feature = "gui")]
+mod render_ggez;
+mod render_raster;
+mod render_svg;
+mod export;
+mod cli;
 mod utils;
 
-use ggez::conf;
-use ggez::event;
+#[cfg(feature = "gui")]
 use ggez::GameResult;
-use lissajou_app::LissajouApp;
 
-const WINDOW_SIZE: f32 = 1024.0;
+pub use interactive_curve::InteractiveCurve;
+pub use render::{DrawData, Rgba, Shape};
+#[cfg(feature = "gui")]
+pub use render_ggez::GgezRenderer;
+pub use render_raster::RasterImage;
+pub use render_svg::to_svg;
+pub use export::{export_xy_wav, render_raster};
+pub use curve_registry::{CurveFactory, CurveRegistration, CurveRegistry};
+pub use harmonograph_curve::{Harmonograph, HarmonographParams, Pendulum};
+pub use lissajou_curve::{Lissajou, LissajouParams};
+pub use neighbor_graph::{Edge, GraphMode, GraphParams, GRAPH_MODES};
+pub use dejong_curve::{DeJongAttractor, DeJongParams};
+pub use mandelbrot_curve::{IterationResult, MandelbrotParams, MandelbrotSet, MandelIterator, REMARKABLE_POINTS};
+#[cfg(feature = "gui")]
+pub use lissajou_app::LissajouApp;
+pub use color_picker::{ColorPicker, HSV};
+#[cfg(feature = "gui")]
+pub use app_builder::AppBuilder;
+pub use i18n::{tr, tr_in, Language, LANGUAGES};
+pub use bindings::{ActionKind, ActionSpec, GamepadRole, Input};
+pub use params::{ParamSpec, Scale};
+pub use midi::{MidiControl, MidiInputs};
+pub use osc::{OscArg, OscMessage, OscRemote};
+pub use http_api::{HttpApi, WindowRequest};
+pub use exploration::Exploration;
+pub use cli::run as run_cli;
 
+#[cfg(feature = "gui")]
 pub fn run() -> GameResult {
-    let (mut ctx, event_loop) = ggez::ContextBuilder::new("input_test", "ggez")
-        .window_mode(
-        conf::WindowMode::default()
-                .fullscreen_type(conf::FullscreenType::Windowed)
-                .dimensions(WINDOW_SIZE, WINDOW_SIZE)
-                .resizable(true),
-        )
-         .build()?;
-
-    let state = LissajouApp::new(&mut ctx);
-
-    event::run(ctx, event_loop, state)
+    AppBuilder::new().run()
 }
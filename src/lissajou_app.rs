@@ -1,27 +1,155 @@
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use ggez::{Context, GameError, GameResult};
 use ggez::event::{self, Button, Axis, GamepadId, MouseButton};
-use ggez::glam::Vec2;
-use ggez::graphics::{self, Canvas, Color, DrawParam, Mesh};
-use ggez::input::keyboard::{KeyCode, KeyInput};
+use glam::Vec2;
+use ggez::graphics::{self, Canvas, Color, DrawMode, DrawParam, Mesh, Rect};
+use ggez::input::keyboard::{KeyInput, KeyMods};
 use image::codecs::png::PngEncoder;
 use image::{ImageEncoder};
 use ggegui::{egui, Gui};
 use ggegui::egui::{Style, Visuals};
 use ggez::winit::event::VirtualKeyCode;
-use crate::mandelbrot_curve::MandelbrotSet;
-use crate::dejong_curve::DeJongAttractor;
-use crate::harmonograph_curve::Harmonograph;
-use crate::interactive_curve::DrawData::{Image, Meshes};
-use crate::lissajou_curve::Lissajou;
+use serde_json::Value;
+use crate::audio::{AudioAnalyzer, AudioFrame, AudioRoute, AudioSignal, AudioSource, LiveInput, WavPlayer, AUDIO_SIGNALS};
+use crate::bindings::{ActionKind, ActionSpec, GamepadRole, Input};
+use crate::bookmarks::{self, Bookmark, Bookmarks};
+use crate::config::Config;
+use crate::export;
+use crate::exploration::Exploration;
+use crate::gamepad::{AxisMode, AXIS_MODES, RESPONSE_CURVES};
+use crate::curve_registry::{CurveRegistration, CurveRegistry};
+use crate::history::History;
+use crate::i18n::{self, tr, tr_args, LANGUAGES};
 use crate::interactive_curve::InteractiveCurve;
+use crate::layout::{Layout, MAX_TILES_PER_SIDE};
+use crate::midi::{MidiControl, MidiInputs};
+use crate::osc::OscRemote;
+use crate::params::{self, ParamSweep};
+use crate::http_api::{HttpApi, WindowRequest};
+use crate::render_ggez::GgezRenderer;
 
 const SIDE_PANEL_WIDTH_PX: f32 = 256.;
+const ACTION_UNDO: &str = "action.app.undo";
+const ACTION_REDO: &str = "action.app.redo";
+const ACTION_NEXT_CURVE: &str = "action.app.next_curve";
+const ACTION_SCREENSHOT: &str = "action.app.screenshot";
+const ACTION_OSCILLOSCOPE: &str = "action.app.oscilloscope";
+const ACTION_HELP: &str = "action.app.help";
+const ACTION_EXPLORE: &str = "action.app.explore";
+// Per curve, in registration order
+const CURVE_ACTIONS: [&str; 9] = [
+    "action.app.curve_1",
+    "action.app.curve_2",
+    "action.app.curve_3",
+    "action.app.curve_4",
+    "action.app.curve_5",
+    "action.app.curve_6",
+    "action.app.curve_7",
+    "action.app.curve_8",
+    "action.app.curve_9",
+];
+// Before the ones of the curve, that they take precedence over
+const APP_ACTIONS: [ActionSpec; 16] = [
+    ActionSpec::trigger(ACTION_UNDO, &["Ctrl+Z", "Pad LeftThumb"]),
+    ActionSpec::trigger(ACTION_REDO, &["Ctrl+Shift+Z", "Ctrl+Y", "Pad RightThumb"]),
+    ActionSpec::trigger(ACTION_NEXT_CURVE, &["Pad Select"]),
+    ActionSpec::trigger(ACTION_SCREENSHOT, &["Pad Start"]),
+    ActionSpec::trigger(ACTION_OSCILLOSCOPE, &["O"]),
+    ActionSpec::trigger(ACTION_HELP, &["F1"]),
+    ActionSpec::trigger(ACTION_EXPLORE, &["E"]),
+    ActionSpec::trigger(CURVE_ACTIONS[0], &["Key1", "Numpad1"]),
+    ActionSpec::trigger(CURVE_ACTIONS[1], &["Key2", "Numpad2"]),
+    ActionSpec::trigger(CURVE_ACTIONS[2], &["Key3", "Numpad3"]),
+    ActionSpec::trigger(CURVE_ACTIONS[3], &["Key4", "Numpad4"]),
+    ActionSpec::trigger(CURVE_ACTIONS[4], &["Key5", "Numpad5"]),
+    ActionSpec::trigger(CURVE_ACTIONS[5], &["Key6", "Numpad6"]),
+    ActionSpec::trigger(CURVE_ACTIONS[6], &["Key7", "Numpad7"]),
+    ActionSpec::trigger(CURVE_ACTIONS[7], &["Key8", "Numpad8"]),
+    ActionSpec::trigger(CURVE_ACTIONS[8], &["Key9", "Numpad9"]),
+];
+// Not bindable alone
+const MODIFIER_KEYS: [&str; 8] = ["LControl", "RControl", "LShift", "RShift", "LAlt", "RAlt", "LWin", "RWin"];
+// Under this, gamepad axes are not captured when rebinding
+const CAPTURE_AXIS_THRESHOLD: f32 = 0.5;
+// Colors of the role indicators, in the order of the roles of the curve
+const ROLE_COLORS: [egui::Color32; 4] = [
+    egui::Color32::from_rgb(230, 120, 30),
+    egui::Color32::from_rgb(40, 140, 220),
+    egui::Color32::from_rgb(60, 170, 80),
+    egui::Color32::from_rgb(190, 70, 180),
+];
+// Name of the MIDI client, and of its virtual port
+const MIDI_CLIENT_NAME: &str = "lissajous";
+const OSCILLOSCOPE_SAMPLE_RATE: u32 = 48000;
+const OSCILLOSCOPE_DURATION: f32 = 10.0;
+const OSCILLOSCOPE_DRAWINGS_PER_SECOND: f32 = 50.0;
+// Until an audio source is opened
+const DEFAULT_SAMPLE_RATE: u32 = 44100;
+// Variants per row of the exploration window
+const EXPLORATION_COLUMNS: usize = 3;
+const DEFAULT_EXPLORATION_VARIANTS: usize = 8;
+const DEFAULT_EXPLORATION_STRENGTH: f32 = 0.1;
+const EXPLORATION_THUMBNAIL_SIZE: u32 = 128;
+// Seconds to sweep a parameter over its range and back
+const DEFAULT_SWEEP_PERIOD: f32 = 8.0;
+// An indicator lights up for this long after an input of its gamepad
+const PAD_ACTIVITY_SECONDS: f32 = 0.3;
+
+const FOCUSED_TILE_COLOR: Color = Color::new(0.2, 0.4, 0.9, 1.0);
+const FOCUSED_TILE_BORDER_PX: f32 = 2.0;
+
+// A tile of the split screen. The focused one displays the current curve of the application, so that every control
+// drives it, the other ones their own instance.
+struct Tile {
+    registration: usize,
+    curve: Option<Box<dyn InteractiveCurve>>,
+}
 
 pub struct LissajouApp {
-    curves: [Box<dyn InteractiveCurve>; 4],
+    title: String,
+    registry: CurveRegistry,
+    curves: Vec<Box<dyn InteractiveCurve>>,
+    histories: Vec<History>, // One per curve
     curve_index: usize,
+    curve_search: String,
+    bookmarks: Bookmarks,
+    bookmark_name: String,
+    thumbnails: HashMap<usize, egui::TextureHandle>, // Per bookmark index
+    config: Config,
+    config_path: Option<PathBuf>,
+    show_bindings: bool,
+    show_help: bool,
+    capturing: Option<ActionSpec>, // Waits for the next input to bind to this action
+    held_axes: HashMap<(usize, &'static str), f32>, // Shaped value per curve and axis action, in relative mode
+    midi: Result<MidiInputs, String>,
+    midi_learning: Option<&'static str>, // Axis action waiting for a MIDI control to move
+    audio: Option<AudioSource>,
+    audio_analyzer: AudioAnalyzer,
+    audio_frame: AudioFrame, // Of the last frame
+    audio_smoothed: Vec<f32>, // Per audio route of the displayed curve
+    audio_file: String,
+    audio_error: Option<String>,
+    show_exploration: bool,
+    exploration: Option<Exploration>,
+    exploration_error: Option<String>,
+    exploration_textures: Vec<egui::TextureHandle>, // Per state of the exploration
+    exploration_variants: usize,
+    exploration_strength: f32,
+    sweeps: HashMap<(usize, &'static str), ParamSweep>, // Per curve and parameter
+    sweep_period: f32,
+    osc: Option<OscRemote>,
+    http: Option<HttpApi>,
+    tiles: Vec<Tile>, // Per tile of the layout, row by row
+    focused_tile: usize,
+    linked_source: (usize, usize), // Focused tile and curve when the linked values were taken
+    linked_values: BTreeMap<String, f32>, // Parameters of the focused tile at the last frame
+    focus_click: bool, // The click focused a tile, so its release is not passed to the curve
+    pads: Vec<(GamepadId, Instant)>, // In the order they were first used, with their last input. Pad numbers start at 1.
     screen: graphics::ScreenImage,
+    renderer: GgezRenderer,
     mouse_pos: Vec2,
     drag_start: Vec2,
     mouse_down: bool,
@@ -29,16 +157,53 @@ pub struct LissajouApp {
 }
 
 impl LissajouApp {
-    pub fn new(ctx: &mut Context) -> LissajouApp {
+    pub fn new(ctx: &mut Context, title: &str, registry: CurveRegistry) -> LissajouApp {
+        if registry.is_empty() {
+            panic!("At least one curve must be registered");
+        }
+        let curves = registry.instantiate();
         LissajouApp {
-            curves: [
-                Box::new(DeJongAttractor::new()),
-                Box::new(Lissajou::new()),
-                Box::new(Harmonograph::new()),
-                Box::new(MandelbrotSet::new()),
-            ],
+            title: title.to_string(),
+            histories: curves.iter().map(|curve| History::new(curve.save_state())).collect(),
+            curves,
+            registry,
             curve_index: 0,
+            curve_search: String::new(),
+            bookmarks: Bookmarks::in_memory(vec![]),
+            bookmark_name: String::new(),
+            thumbnails: HashMap::new(),
+            config: Config::default(),
+            config_path: None,
+            show_bindings: false,
+            show_help: false,
+            capturing: None,
+            held_axes: HashMap::new(),
+            midi: MidiInputs::open(MIDI_CLIENT_NAME),
+            midi_learning: None,
+            audio: None,
+            audio_analyzer: AudioAnalyzer::new(DEFAULT_SAMPLE_RATE),
+            audio_frame: AudioFrame::default(),
+            audio_smoothed: vec![],
+            audio_file: String::new(),
+            audio_error: None,
+            show_exploration: false,
+            exploration: None,
+            exploration_error: None,
+            exploration_textures: vec![],
+            exploration_variants: DEFAULT_EXPLORATION_VARIANTS,
+            exploration_strength: DEFAULT_EXPLORATION_STRENGTH,
+            sweeps: HashMap::new(),
+            sweep_period: DEFAULT_SWEEP_PERIOD,
+            osc: None,
+            http: None,
+            tiles: vec![Tile { registration: 0, curve: None }],
+            focused_tile: 0,
+            linked_source: (0, 0),
+            linked_values: BTreeMap::new(),
+            focus_click: false,
+            pads: vec![],
             screen: graphics::ScreenImage::new(ctx, graphics::ImageFormat::Rgba8UnormSrgb, 1., 1., 1),
+            renderer: GgezRenderer::new(),
             mouse_pos: Vec2::new(0., 0.),
             drag_start: Vec2::new(0., 0.),
             mouse_down: false,
@@ -46,20 +211,1028 @@ impl LissajouApp {
         }
     }
 
+    pub fn select_curve(&mut self, index: usize) {
+        if index < self.curves.len() {
+            self.curve_index = index;
+        }
+    }
+
+    pub fn set_layout(&mut self, layout: Layout) {
+        let layout = Layout { linked: layout.linked, ..Layout::new(layout.columns, layout.rows) };
+        if self.focused_tile >= layout.tiles() {
+            self.focus_tile(0);
+        }
+        self.tiles.truncate(layout.tiles());
+        // The new tiles show the next curves, to compare them
+        while self.tiles.len() < layout.tiles() {
+            let registration = self.tiles.len() % self.curves.len();
+            let curve = (self.registry.registrations()[registration].factory)();
+            self.tiles.push(Tile { registration, curve: Some(curve) });
+        }
+        self.config.layout = layout;
+    }
+
+    // The displayed curve goes to its tile, and the curve of the tile becomes the displayed one
+    fn focus_tile(&mut self, tile: usize) {
+        if tile == self.focused_tile || tile >= self.tiles.len() {
+            return;
+        }
+        let state = self.curves[self.curve_index].save_state();
+        self.tiles[self.focused_tile] = Tile { registration: self.curve_index, curve: Some(self.copy_displayed_curve(&state)) };
+
+        let Tile { registration, curve } = std::mem::replace(&mut self.tiles[tile], Tile { registration: 0, curve: None });
+        if let Some(curve) = curve {
+            if let Err(err) = self.curves[registration].restore_state(&curve.save_state()) {
+                eprintln!("The curve of the tile replaces the displayed one, as its state cannot be restored: {}", err);
+                self.curves[registration] = curve;
+            }
+        }
+        self.curve_index = registration;
+        self.focused_tile = tile;
+    }
+
+    // Every tile shows the displayed curve, to vary it from there
+    fn fill_tiles(&mut self) {
+        let state = self.curves[self.curve_index].save_state();
+        for index in (0..self.tiles.len()).filter(|index| *index != self.focused_tile) {
+            self.tiles[index] = Tile { registration: self.curve_index, curve: Some(self.copy_displayed_curve(&state)) };
+        }
+    }
+
+    // New instance of the displayed curve in this state. The curves that cannot restore their state start afresh.
+    fn copy_displayed_curve(&self, state: &Value) -> Box<dyn InteractiveCurve> {
+        let mut curve = (self.registry.registrations()[self.curve_index].factory)();
+        if let Err(err) = curve.restore_state(state) {
+            eprintln!("The tile starts from a new curve: {}", err);
+        }
+        curve
+    }
+
+    // With linked parameters, the changes on the focused tile also apply to the other tiles of the same curve
+    fn link_tiles(&mut self) {
+        let values = params::values(self.curves[self.curve_index].as_ref());
+        let source = (self.focused_tile, self.curve_index);
+        if self.config.layout.linked && self.linked_source == source {
+            let changed = values.iter().filter(|(id, value)| self.linked_values.get(*id) != Some(*value));
+            for (id, value) in changed {
+                let curves = self.tiles
+                    .iter_mut()
+                    .filter(|tile| tile.registration == self.curve_index)
+                    .filter_map(|tile| tile.curve.as_mut());
+                for curve in curves {
+                    curve.set_param(id, *value);
+                }
+            }
+        }
+        self.linked_source = source;
+        self.linked_values = values;
+    }
+
+    // Origin and size of the area of the tiles, right of the side panel
+    fn tiles_area(ctx: &Context) -> (Vec2, Vec2) {
+        let frame = ctx.gfx.frame();
+        let size = Vec2::new(frame.width() as f32 - SIDE_PANEL_WIDTH_PX, frame.height() as f32);
+        (Vec2::new(SIDE_PANEL_WIDTH_PX, 0.0), size)
+    }
+
+    fn layout_ui(&mut self, ui: &mut egui::Ui) {
+        let mut layout = self.config.layout;
+        ui.horizontal(|ui| {
+            ui.label(tr("layout.columns"));
+            ui.add(egui::DragValue::new(&mut layout.columns).clamp_range(1..=MAX_TILES_PER_SIDE));
+            ui.label(tr("layout.rows"));
+            ui.add(egui::DragValue::new(&mut layout.rows).clamp_range(1..=MAX_TILES_PER_SIDE));
+        });
+        ui.checkbox(&mut layout.linked, tr("layout.linked")).on_hover_text(tr("layout.linked_hint"));
+        if layout.is_split() {
+            if ui.button(tr("layout.same_curve")).clicked() {
+                self.fill_tiles();
+            }
+            ui.label(tr("layout.hint"));
+        }
+        if layout != self.config.layout {
+            self.set_layout(layout);
+            self.save_config();
+        }
+    }
+
+    // Remote control over UDP, e.g. from TouchOSC or Max/MSP, on every network interface
+    pub fn start_osc(&mut self, port: u16) {
+        match OscRemote::bind(("0.0.0.0", port)) {
+            Ok(remote) => {
+                println!("OSC server listening on {}", remote.local_addr());
+                self.osc = Some(remote);
+            },
+            Err(err) => eprintln!("OSC server not started on port {}: {}", port, err),
+        }
+    }
+
+    fn handle_osc(&mut self, ctx: &mut Context) {
+        let Some(osc) = &mut self.osc else { return };
+        let actions = osc.apply(&self.registry, &mut self.curves, &mut self.curve_index);
+        for action in actions {
+            if APP_ACTIONS.iter().any(|known| known.id == action) {
+                self.run_app_action(ctx, &action);
+                continue;
+            }
+            let pointer = self.mouse_pos;
+            let trigger = self.curve().actions().into_iter().find(|known| known.id == action && known.kind == ActionKind::Trigger);
+            match trigger {
+                Some(trigger) => self.curve().on_trigger(trigger.id, pointer),
+                None => eprintln!("OSC action {} ignored: unknown for {}", action, tr(self.curve().name())),
+            }
+        }
+    }
+
+    // JSON API for scripts, on localhost only
+    pub fn start_http(&mut self, port: u16) {
+        match HttpApi::bind(("127.0.0.1", port)) {
+            Ok(api) => {
+                if let Some(address) = api.local_addr() {
+                    println!("HTTP API listening on http://{}", address);
+                }
+                self.http = Some(api);
+            },
+            Err(err) => eprintln!("HTTP API not started on port {}: {}", port, err),
+        }
+    }
+
+    fn handle_http(&mut self, ctx: &mut Context) {
+        let Some(http) = &mut self.http else { return };
+        let pending = http.handle(&self.registry, &mut self.curves, &mut self.curve_index);
+        for request in pending {
+            match request.kind {
+                WindowRequest::Screenshot => match self.save_screenshot(ctx) {
+                    Ok(path) => request.respond_json(serde_json::json!({ "path": path })),
+                    Err(err) => request.respond_error(&err),
+                },
+                WindowRequest::Frame => {
+                    let mut png = vec![];
+                    match self.write_frame_png(ctx, &mut png) {
+                        Ok(()) => request.respond_png(png),
+                        Err(err) => request.respond_error(&err),
+                    }
+                },
+            }
+        }
+    }
+
+    // The config is saved in this file after each change
+    pub fn load_config(&mut self, path: &Path) {
+        match Config::load(path) {
+            Ok(config) => {
+                self.config = config;
+                self.config_path = Some(path.to_path_buf());
+                self.set_layout(self.config.layout);
+            },
+            Err(err) => {
+                // Not saved, so that the unreadable file is kept as is
+                eprintln!("Config cannot be loaded: {}", err);
+                self.config = Config::default();
+                self.config_path = None;
+                self.set_layout(self.config.layout);
+            }
+        }
+        i18n::set_language(self.config.language);
+    }
+
+    pub fn set_language(&mut self, language: i18n::Language) {
+        i18n::set_language(language);
+        if self.config.language != language {
+            self.config.language = language;
+            self.save_config();
+        }
+    }
+
+    fn save_config(&self) {
+        if let Some(path) = &self.config_path {
+            if let Err(err) = self.config.save(path) {
+                eprintln!("Config cannot be saved: {}", err);
+            }
+        }
+    }
+
+    // Bookmarks are saved in this file after each change
+    pub fn load_bookmarks(&mut self, path: &Path) {
+        self.bookmarks = match Bookmarks::load(path, &self.registry) {
+            Ok(bookmarks) => bookmarks,
+            Err(err) => {
+                // Not saved, so that the unreadable file is kept as is
+                eprintln!("Bookmarks cannot be loaded: {}", err);
+                Bookmarks::in_memory(Bookmarks::defaults(&self.registry))
+            }
+        };
+        self.thumbnails.clear();
+    }
+
+    fn add_bookmark(&mut self) {
+        let registration = &self.registry.registrations()[self.curve_index];
+        let name = if self.bookmark_name.trim().is_empty() {
+            format!("{} {}", tr(&registration.name), self.bookmarks.of_curve(&registration.name).count() + 1)
+        } else {
+            self.bookmark_name.trim().to_string()
+        };
+        let result = Bookmark::new(&name, registration, self.curves[self.curve_index].save_state())
+            .and_then(|bookmark| self.bookmarks.add(bookmark));
+        match result {
+            Ok(()) => self.bookmark_name.clear(),
+            Err(err) => eprintln!("Bookmark cannot be saved: {}", err),
+        }
+    }
+
+    fn restore_bookmark(&mut self, index: usize) {
+        let Some(bookmark) = self.bookmarks.get(index) else { return };
+        let curve_index = self.registry
+            .registrations()
+            .iter()
+            .position(|registration| registration.name == bookmark.curve);
+        if let Some(curve_index) = curve_index {
+            self.curve_index = curve_index;
+            if let Err(err) = self.curves[curve_index].restore_state(&bookmark.state) {
+                eprintln!("Bookmark {} cannot be restored: {}", bookmark.name, err);
+            }
+        }
+    }
+
+    fn remove_bookmark(&mut self, index: usize) {
+        if let Err(err) = self.bookmarks.remove(index) {
+            eprintln!("Bookmarks cannot be saved: {}", err);
+        }
+        // Indices changed
+        self.thumbnails.clear();
+    }
+
+    fn thumbnail(&mut self, ctx: &egui::Context, index: usize) -> Option<egui::TextureId> {
+        if !self.thumbnails.contains_key(&index) {
+            let png = self.bookmarks.get(index)?.thumbnail_png()?;
+            let image = ::image::load_from_memory(&png).ok()?.to_rgba8();
+            let size = [image.width() as usize, image.height() as usize];
+            let texture = ctx.load_texture(
+                format!("bookmark_{}", index),
+                egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()),
+                egui::TextureOptions::default(),
+            );
+            self.thumbnails.insert(index, texture);
+        }
+        self.thumbnails.get(&index).map(|texture| texture.id())
+    }
+
+    fn bookmarks_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.bookmark_name).hint_text(tr("app.bookmark_name")).desired_width(150.));
+            if ui.button(tr("app.add")).clicked() {
+                self.add_bookmark();
+            }
+        });
+
+        let curve_name = &self.registry.registrations()[self.curve_index].name;
+        let indices: Vec<usize> = self.bookmarks.of_curve(curve_name).collect();
+        let mut clicked = None;
+        let mut removed = None;
+        ui.horizontal_wrapped(|ui| {
+            for index in indices {
+                let Some(texture) = self.thumbnail(ui.ctx(), index) else { continue };
+                let size = bookmarks::THUMBNAIL_SIZE as f32;
+                let response = ui
+                    .add(egui::ImageButton::new(texture, [size, size]))
+                    .on_hover_text(tr(&self.bookmarks.all()[index].name));
+                if response.clicked() {
+                    clicked = Some(index);
+                }
+                response.context_menu(|ui| {
+                    if ui.button(tr("app.remove")).clicked() {
+                        removed = Some(index);
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        if let Some(index) = clicked {
+            self.restore_bookmark(index);
+        }
+        if let Some(index) = removed {
+            self.remove_bookmark(index);
+        }
+    }
+
+    // Runs the actions bound to the input: the ones of the application, or else the ones of the displayed curve.
+    // A gamepad with a role only drives the curve actions of its role.
+    fn handle_input(&mut self, ctx: &mut Context, input: Input, value: f32, pad: Option<usize>) {
+        if let Some(action) = self.capturing {
+            if input.is_axis() && value.abs() < CAPTURE_AXIS_THRESHOLD {
+                return;
+            }
+            if self.config.bindings.bind(&action, input) {
+                self.capturing = None;
+                self.save_config();
+            }
+            return;
+        }
+
+        let app_actions: Vec<&str> = self.config.bindings.triggered(&input, &APP_ACTIONS).iter().map(|action| action.id).collect();
+        if !app_actions.is_empty() {
+            for action in app_actions {
+                self.run_app_action(ctx, action);
+            }
+            return;
+        }
+
+        let curve_actions = self.curve().actions();
+        let pointer = self.mouse_pos;
+        let axes = self.config.gamepad.axes(&self.registry.registrations()[self.curve_index].name);
+        let role = pad.and_then(|pad| self.pad_role(pad));
+        for action in self.config.bindings.triggered(&input, &curve_actions) {
+            if role.is_some_and(|role| !role.allows(action.id)) {
+                continue;
+            }
+            match action.kind {
+                ActionKind::Trigger => self.curve().on_trigger(action.id, pointer),
+                ActionKind::Axis => {
+                    let shaped = axes.shape(value);
+                    match axes.mode {
+                        AxisMode::Absolute => self.curve().on_axis(action.id, shaped),
+                        AxisMode::Relative => {
+                            self.held_axes.insert((self.curve_index, action.id), shaped);
+                        },
+                    }
+                },
+            }
+        }
+    }
+
+    // In relative mode, the held axes change the parameters of the displayed curve at each frame
+    fn apply_held_axes(&mut self, dt: f32) {
+        let curve_index = self.curve_index;
+        let axes = self.config.gamepad.axes(&self.registry.registrations()[curve_index].name);
+        // Axes held before switching to absolute mode
+        self.held_axes.retain(|(index, _), shaped| *shaped != 0.0 && (*index != curve_index || axes.mode == AxisMode::Relative));
+        let held: Vec<(&str, f32)> = self.held_axes
+            .iter()
+            .filter(|((index, _), _)| *index == curve_index)
+            .map(|((_, action), shaped)| (*action, *shaped))
+            .collect();
+        for (action, shaped) in held {
+            let delta = axes.rate(shaped, dt);
+            self.curve().on_axis_relative(action, delta);
+        }
+    }
+
+    // Audio-reactive mode, with a WAV file in place of the live input
+    pub fn open_audio_file(&mut self, path: &Path) {
+        self.audio_file = path.display().to_string();
+        self.set_audio_source(WavPlayer::load(path).map(AudioSource::File));
+    }
+
+    fn open_live_audio(&mut self) {
+        self.set_audio_source(LiveInput::open().map(AudioSource::Live));
+    }
+
+    fn set_audio_source(&mut self, source: Result<AudioSource, String>) {
+        match source {
+            Ok(source) => {
+                self.audio_analyzer = AudioAnalyzer::new(source.sample_rate());
+                self.audio = Some(source);
+                self.audio_error = None;
+            },
+            Err(err) => {
+                eprintln!("Audio cannot be opened: {}", err);
+                self.audio_error = Some(err);
+            }
+        }
+    }
+
+    // The routed audio signals drive the parameters of the displayed curve. Like the animation, it is not undoable.
+    fn apply_audio(&mut self, dt: f32) {
+        let Some(source) = &mut self.audio else { return };
+        self.audio_frame = self.audio_analyzer.analyze(&source.samples(dt));
+        let routes = self.config.audio.routes(&self.registry.registrations()[self.curve_index].name);
+        self.audio_smoothed.resize(routes.len(), 0.0);
+        for (route, smoothed) in routes.iter().zip(self.audio_smoothed.iter_mut()) {
+            *smoothed = route.smooth(*smoothed, &self.audio_frame, dt);
+            self.curves[self.curve_index].set_param_position(&route.target, route.position(*smoothed));
+        }
+    }
+
+    // Like the audio, the sweeps are not undoable
+    fn apply_sweeps(&mut self, dt: f32) {
+        let curve = &mut self.curves[self.curve_index];
+        for ((index, id), sweep) in self.sweeps.iter_mut() {
+            if *index == self.curve_index {
+                curve.set_param_position(id, sweep.advance(dt, self.sweep_period));
+            }
+        }
+    }
+
+    // Controls generated from the parameter descriptions of the displayed curve
+    fn params_ui(&mut self, ui: &mut egui::Ui) {
+        let curve_index = self.curve_index;
+        let curve = &mut self.curves[curve_index];
+        egui::Grid::new("params")
+            .num_columns(3)
+            .show(ui, |ui| {
+                for spec in curve.param_specs() {
+                    let Some(mut value) = curve.param(spec.id) else { continue };
+                    ui.label(&spec.label);
+                    if ui.add(spec.slider(&mut value)).changed() {
+                        curve.set_param(spec.id, value);
+                    }
+                    let mut sweeping = self.sweeps.contains_key(&(curve_index, spec.id));
+                    if ui.checkbox(&mut sweeping, "").on_hover_text(tr("params.sweep")).changed() {
+                        if sweeping {
+                            self.sweeps.insert((curve_index, spec.id), ParamSweep::from_position(spec.position(value)));
+                        } else {
+                            self.sweeps.remove(&(curve_index, spec.id));
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.label(tr("params.sweep_period"));
+            ui.add(egui::Slider::new(&mut self.sweep_period, 1.0..=60.0).logarithmic(true).suffix(" s"));
+        });
+        if ui.button(tr("params.randomize")).clicked() {
+            params::randomize(curve.as_mut(), &mut rand::thread_rng());
+        }
+    }
+
+    fn audio_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.audio_file).hint_text(tr("audio.wav_file")).desired_width(150.));
+            if ui.button(tr("audio.open")).clicked() {
+                let path = PathBuf::from(self.audio_file.trim());
+                self.open_audio_file(&path);
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button(tr("audio.live")).clicked() {
+                self.open_live_audio();
+            }
+            if ui.add_enabled(self.audio.is_some(), egui::Button::new(tr("audio.stop"))).clicked() {
+                self.audio = None;
+                self.audio_frame = AudioFrame::default();
+            }
+        });
+        if let Some(err) = &self.audio_error {
+            ui.colored_label(egui::Color32::DARK_RED, err);
+        }
+        let Some(source) = &self.audio else {
+            ui.label(tr("audio.no_source"));
+            return;
+        };
+        ui.label(tr_args("audio.source", &[&source.name()]));
+
+        egui::Grid::new("audio_signals")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for signal in AUDIO_SIGNALS {
+                    ui.label(tr(signal.name()));
+                    ui.add(egui::ProgressBar::new(self.audio_frame.signal(signal)).desired_width(120.));
+                    ui.end_row();
+                }
+            });
+
+        let curve_name = self.registry.registrations()[self.curve_index].name.clone();
+        let targets = self.curves[self.curve_index].param_specs();
+        if targets.is_empty() {
+            return;
+        }
+        ui.separator();
+        let routes = self.config.audio.routes_mut(&curve_name);
+        let mut changed = false;
+        let mut removed = None;
+        for (index, route) in routes.iter_mut().enumerate() {
+            ui.push_id(("audio_route", index), |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("signal")
+                        .width(70.)
+                        .selected_text(tr(route.signal.name()))
+                        .show_ui(ui, |ui| {
+                            for signal in AUDIO_SIGNALS {
+                                changed |= ui.selectable_value(&mut route.signal, signal, tr(signal.name())).changed();
+                            }
+                        });
+                    let selected = targets.iter().find(|target| target.id == route.target).map(|target| target.label.as_str()).unwrap_or("?");
+                    egui::ComboBox::from_id_source("target")
+                        .width(120.)
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for target in &targets {
+                                if ui.selectable_label(route.target == target.id, &target.label).clicked() {
+                                    route.target = target.id.to_string();
+                                    changed = true;
+                                }
+                            }
+                        });
+                    if ui.button("x").on_hover_text(tr("audio.remove_route")).clicked() {
+                        removed = Some(index);
+                    }
+                });
+                egui::Grid::new("audio_route_settings")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label(tr("audio.gain"));
+                        changed |= ui.add(egui::Slider::new(&mut route.gain, 0.0..=4.0).fixed_decimals(2)).changed();
+                        ui.end_row();
+                        ui.label(tr("audio.smoothing"));
+                        changed |= ui.add(egui::Slider::new(&mut route.smoothing, 0.0..=2.0).fixed_decimals(2).suffix(" s")).changed();
+                        ui.end_row();
+                    });
+            });
+        }
+        if let Some(index) = removed {
+            routes.remove(index);
+            changed = true;
+        }
+        if ui.button(tr("audio.add_route")).clicked() {
+            routes.push(AudioRoute::new(AudioSignal::Bass, targets[0].id));
+            changed = true;
+        }
+        if changed {
+            self.config.audio.prune();
+            self.save_config();
+        }
+    }
+
+    fn handle_midi(&mut self, control: MidiControl, value: u8) {
+        let curve_name = &self.registry.registrations()[self.curve_index].name;
+        let learning = self.midi_learning.take();
+        if self.config.midi.handle(curve_name, self.curves[self.curve_index].as_mut(), learning, control, value) {
+            self.save_config();
+        }
+    }
+
+    fn midi_ui(&mut self, ui: &mut egui::Ui) {
+        match &self.midi {
+            Ok(inputs) if inputs.ports.is_empty() => { ui.label(tr("midi.no_port")); },
+            Ok(inputs) => { ui.label(tr_args("midi.ports", &[&inputs.ports.join(", ")])); },
+            Err(err) => { ui.label(tr_args("midi.unavailable", &[err])); },
+        }
+        let curve_name = self.registry.registrations()[self.curve_index].name.clone();
+        let actions: Vec<ActionSpec> = self.curve().actions().into_iter().filter(|action| action.kind == ActionKind::Axis).collect();
+        let mut changed = false;
+        egui::Grid::new("midi_mappings")
+            .num_columns(3)
+            .show(ui, |ui| {
+                for action in &actions {
+                    let learning = self.midi_learning == Some(action.id);
+                    if ui.selectable_label(learning, tr(action.id)).on_hover_text(tr("midi.learn")).clicked() {
+                        self.midi_learning = if learning { None } else { Some(action.id) };
+                    }
+                    let controls = self.config.midi.controls(&curve_name, action.id);
+                    if learning {
+                        ui.label(tr("midi.move_control"));
+                    } else {
+                        ui.label(controls.iter().map(MidiControl::to_string).collect::<Vec<_>>().join(", "));
+                    }
+                    if ui.add_enabled(!controls.is_empty(), egui::Button::new("x")).on_hover_text(tr("midi.forget")).clicked() {
+                        self.config.midi.forget(&curve_name, action.id);
+                        changed = true;
+                    }
+                    ui.end_row();
+                }
+            });
+        if changed {
+            self.save_config();
+        }
+    }
+
+    // Number of the gamepad, registered on its first input
+    fn pad_number(&mut self, id: GamepadId) -> usize {
+        let now = Instant::now();
+        match self.pads.iter().position(|(pad, _)| *pad == id) {
+            Some(index) => {
+                self.pads[index].1 = now;
+                index + 1
+            },
+            None => {
+                self.pads.push((id, now));
+                self.pads.len()
+            },
+        }
+    }
+
+    fn pad_role(&self, pad: usize) -> Option<GamepadRole> {
+        let role = self.config.gamepad.role(&self.registry.registrations()[self.curve_index].name, pad)?;
+        // Roles of the config that the curve does not have anymore are ignored
+        self.curves[self.curve_index].gamepad_roles().into_iter().find(|known| known.id == role)
+    }
+
+    fn role_color(&self, role: &GamepadRole) -> egui::Color32 {
+        let index = self.curves[self.curve_index]
+            .gamepad_roles()
+            .iter()
+            .position(|known| known.id == role.id)
+            .unwrap_or_default();
+        ROLE_COLORS[index % ROLE_COLORS.len()]
+    }
+
+    fn pad_roles_ui(&mut self, ui: &mut egui::Ui) {
+        let roles = self.curves[self.curve_index].gamepad_roles();
+        if roles.is_empty() {
+            return;
+        }
+        ui.separator();
+        if self.pads.is_empty() {
+            ui.label(tr("gamepad.no_pad"));
+            return;
+        }
+        let curve_name = self.registry.registrations()[self.curve_index].name.clone();
+        let mut changed = false;
+        egui::Grid::new("gamepad_roles")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for pad in 1..=self.pads.len() {
+                    ui.label(tr_args("gamepad.pad", &[&pad]));
+                    let mut role = self.pad_role(pad).map(|role| role.id);
+                    let selected = role.map_or(tr("gamepad.all"), tr);
+                    let mut pad_changed = false;
+                    egui::ComboBox::from_id_source(("gamepad_role", pad))
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            pad_changed |= ui.selectable_value(&mut role, None, tr("gamepad.all")).changed();
+                            for known in &roles {
+                                pad_changed |= ui.selectable_value(&mut role, Some(known.id), tr(known.id)).changed();
+                            }
+                        });
+                    if pad_changed {
+                        self.config.gamepad.set_role(&curve_name, pad, role);
+                        changed = true;
+                    }
+                    ui.end_row();
+                }
+            });
+        if changed {
+            self.save_config();
+        }
+    }
+
+    // One indicator per gamepad with a role, showing its inputs and lighting up when it is used
+    fn pad_roles_indicator(&self, ctx: &egui::Context) {
+        let assigned: Vec<(usize, GamepadRole, Instant)> = self.pads
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, last_input))| Some((index + 1, self.pad_role(index + 1)?, *last_input)))
+            .collect();
+        if assigned.is_empty() {
+            return;
+        }
+        let actions = self.curves[self.curve_index].actions();
+        egui::Area::new("gamepad_roles_indicator")
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10., -10.])
+            .show(ctx, |ui| {
+                for (pad, role, last_input) in assigned {
+                    let color = self.role_color(&role);
+                    let active = last_input.elapsed().as_secs_f32() < PAD_ACTIVITY_SECONDS;
+                    egui::Frame::popup(ui.style())
+                        .stroke(egui::Stroke::new(2., color))
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                let (rect, _) = ui.allocate_exact_size(egui::vec2(12., 12.), egui::Sense::hover());
+                                if active {
+                                    ui.painter().circle_filled(rect.center(), 6., color);
+                                } else {
+                                    ui.painter().circle_stroke(rect.center(), 5., egui::Stroke::new(2., color));
+                                }
+                                ui.colored_label(color, format!("{} · {}", tr_args("gamepad.pad", &[&pad]), tr(role.id)));
+                            });
+                            for action in actions.iter().filter(|action| role.allows(action.id)) {
+                                let inputs: Vec<String> = self.config.bindings
+                                    .inputs(action)
+                                    .iter()
+                                    .filter(|input| matches!(input, Input::Button(_) | Input::Axis(_)))
+                                    .map(Input::to_string)
+                                    .collect();
+                                if !inputs.is_empty() {
+                                    ui.small(format!("[{}]: {}", inputs.join(", "), tr(action.id)));
+                                }
+                            }
+                        });
+                }
+            });
+        if self.pads.iter().any(|(_, last_input)| last_input.elapsed().as_secs_f32() < PAD_ACTIVITY_SECONDS) {
+            ctx.request_repaint();
+        }
+    }
+
+    fn gamepad_ui(&mut self, ui: &mut egui::Ui) {
+        let curve_name = self.registry.registrations()[self.curve_index].name.clone();
+        let mut settings = self.config.gamepad.axes(&curve_name);
+        let mut changed = false;
+        egui::Grid::new("gamepad_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(tr("gamepad.mode"));
+                egui::ComboBox::from_id_source("gamepad_mode")
+                    .selected_text(tr(settings.mode.name()))
+                    .show_ui(ui, |ui| {
+                        for mode in AXIS_MODES {
+                            changed |= ui.selectable_value(&mut settings.mode, mode, tr(mode.name())).changed();
+                        }
+                    });
+                ui.end_row();
+                // The stick position maps linearly to the value in absolute mode
+                let relative = settings.mode == AxisMode::Relative;
+                ui.label(tr("gamepad.dead_zone"));
+                changed |= ui.add_enabled(relative, egui::Slider::new(&mut settings.dead_zone, 0.0..=0.5).fixed_decimals(2)).changed();
+                ui.end_row();
+                ui.label(tr("gamepad.response"));
+                ui.add_enabled_ui(relative, |ui| {
+                    egui::ComboBox::from_id_source("gamepad_response")
+                        .selected_text(tr(settings.response.name()))
+                        .show_ui(ui, |ui| {
+                            for response in RESPONSE_CURVES {
+                                changed |= ui.selectable_value(&mut settings.response, response, tr(response.name())).changed();
+                            }
+                        });
+                });
+                ui.end_row();
+                ui.label(tr("gamepad.sensitivity"));
+                changed |= ui
+                    .add_enabled(relative, egui::Slider::new(&mut settings.sensitivity, 0.02..=2.0).logarithmic(true).suffix(" /s"))
+                    .on_hover_text(tr("gamepad.sensitivity_hint"))
+                    .changed();
+                ui.end_row();
+            });
+        if changed {
+            self.config.gamepad.set_axes(&curve_name, settings);
+            self.save_config();
+        }
+        self.pad_roles_ui(ui);
+    }
+
+    fn run_app_action(&mut self, ctx: &mut Context, action: &str) {
+        match action {
+            ACTION_UNDO => self.undo(),
+            ACTION_REDO => self.redo(),
+            ACTION_NEXT_CURVE => self.curve_index = (self.curve_index + 1) % self.curves.len(),
+            ACTION_SCREENSHOT => {
+                if let Err(err) = self.save_screenshot(ctx) {
+                    eprintln!("Screenshot not saved: {}", err);
+                }
+            },
+            ACTION_OSCILLOSCOPE => self.save_oscilloscope_sound(),
+            ACTION_HELP => self.show_help = !self.show_help,
+            ACTION_EXPLORE => self.show_exploration = !self.show_exploration,
+            _ => {
+                if let Some(index) = CURVE_ACTIONS.iter().position(|curve_action| *curve_action == action) {
+                    self.select_curve(index);
+                }
+            }
+        }
+    }
+
+    fn app_action(id: &str) -> &'static ActionSpec {
+        APP_ACTIONS.iter().find(|action| action.id == id).unwrap()
+    }
+
+    fn bindings_window(&mut self, ctx: &egui::Context) {
+        let groups = [
+            (tr("app.bindings_app").to_string(), APP_ACTIONS.to_vec()),
+            (tr(self.curves[self.curve_index].name()).to_string(), self.curves[self.curve_index].actions()),
+        ];
+        let mut open = self.show_bindings;
+        let mut changed = false;
+        egui::Window::new(tr("app.bindings"))
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                if let Some(action) = self.capturing {
+                    ui.horizontal(|ui| {
+                        ui.label(tr_args("app.press_input", &[&tr(action.id)]));
+                        if ui.button(tr("app.cancel")).clicked() {
+                            self.capturing = None;
+                        }
+                    });
+                    ui.separator();
+                }
+                for (title, actions) in &groups {
+                    ui.strong(title);
+                    egui::Grid::new(title).num_columns(3).striped(true).show(ui, |ui| {
+                        for action in actions {
+                            ui.label(tr(action.id));
+                            ui.horizontal(|ui| {
+                                for input in self.config.bindings.inputs(action) {
+                                    if ui.button(input.to_string()).on_hover_text(tr("app.unbind")).clicked() {
+                                        self.config.bindings.unbind(action, &input);
+                                        changed = true;
+                                    }
+                                }
+                                if ui.button("+").on_hover_text(tr("app.bind")).clicked() {
+                                    self.capturing = Some(*action);
+                                }
+                            });
+                            let is_default = self.config.bindings.is_default(action);
+                            if ui.add_enabled(!is_default, egui::Button::new("r")).on_hover_text(tr("app.reset_binding")).clicked() {
+                                self.config.bindings.reset(action);
+                                changed = true;
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    ui.separator();
+                }
+                if ui.button(tr("app.reset_all_bindings")).clicked() {
+                    self.config.bindings.reset_all();
+                    changed = true;
+                }
+            });
+        if !open {
+            self.capturing = None;
+        }
+        self.show_bindings = open;
+        if changed {
+            self.save_config();
+        }
+    }
+
+    fn exploration_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_exploration;
+        egui::Window::new(tr("explore.title"))
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| self.exploration_ui(ui));
+        self.show_exploration = open;
+    }
+
+    fn exploration_ui(&mut self, ui: &mut egui::Ui) {
+        let registration = &self.registry.registrations()[self.curve_index];
+        let explored = self.exploration.as_ref().is_some_and(|exploration| exploration.curve() == registration.name);
+        ui.horizontal(|ui| {
+            ui.label(tr("explore.variants"));
+            ui.add(egui::DragValue::new(&mut self.exploration_variants).clamp_range(1..=24));
+            ui.label(tr("explore.strength"));
+            ui.add(egui::Slider::new(&mut self.exploration_strength, 0.01..=0.5).logarithmic(true).fixed_decimals(2));
+        });
+        // Starts again from the displayed curve
+        if !explored || ui.button(tr("explore.restart")).clicked() {
+            let state = self.curves[self.curve_index].save_state();
+            let result = Exploration::new(registration, state, self.exploration_variants, self.exploration_strength, &mut rand::thread_rng());
+            self.set_exploration(ui.ctx(), result);
+        }
+        if let Some(err) = &self.exploration_error {
+            ui.label(err);
+            return;
+        }
+        let Some(exploration) = &mut self.exploration else { return };
+
+        ui.label(tr_args("explore.generation", &[&exploration.generation]));
+        ui.label(tr("explore.hint"));
+        let size = EXPLORATION_THUMBNAIL_SIZE as f32;
+        let mut chosen = None;
+        egui::Grid::new("exploration").num_columns(EXPLORATION_COLUMNS).show(ui, |ui| {
+            for (index, texture) in self.exploration_textures.iter().enumerate() {
+                ui.vertical(|ui| {
+                    let hint = tr(if index == 0 { "explore.parent" } else { "explore.variant" });
+                    if ui.add(egui::ImageButton::new(texture.id(), [size, size])).on_hover_text(hint).clicked() {
+                        chosen = Some(index);
+                    }
+                    let favorite = exploration.favorites.contains(&index);
+                    if ui.selectable_label(favorite, "★").on_hover_text(tr("explore.favorite")).clicked() {
+                        exploration.toggle_favorite(index);
+                    }
+                });
+                if (index + 1) % EXPLORATION_COLUMNS == 0 {
+                    ui.end_row();
+                }
+            }
+        });
+
+        let registration = &self.registry.registrations()[self.curve_index];
+        let (variants, strength, rng) = (self.exploration_variants, self.exploration_strength, &mut rand::thread_rng());
+        let crossing = ui
+            .add_enabled(exploration.favorites.len() == 2, egui::Button::new(tr("explore.cross")))
+            .clicked();
+        let result = if crossing {
+            exploration.cross(registration, variants, strength, rng)
+        } else if let Some(index) = chosen {
+            exploration.breed(registration, index, variants, strength, rng)
+        } else {
+            return;
+        };
+        if let Err(err) = result {
+            self.exploration_error = Some(err);
+            return;
+        }
+        // The parent of the new generation is displayed, as an undoable change
+        let parent = &exploration.states[0];
+        let textures = self.curves[self.curve_index]
+            .restore_state(parent)
+            .and_then(|()| Self::exploration_textures(ui.ctx(), exploration, registration));
+        match textures {
+            Ok(textures) => self.exploration_textures = textures,
+            Err(err) => self.exploration_error = Some(err),
+        }
+    }
+
+    fn set_exploration(&mut self, ctx: &egui::Context, result: Result<Exploration, String>) {
+        let registration = &self.registry.registrations()[self.curve_index];
+        let result = result.and_then(|exploration| Ok((Self::exploration_textures(ctx, &exploration, registration)?, exploration)));
+        match result {
+            Ok((textures, exploration)) => {
+                self.exploration_textures = textures;
+                self.exploration = Some(exploration);
+                self.exploration_error = None;
+            },
+            Err(err) => {
+                self.exploration = None;
+                self.exploration_error = Some(err);
+            },
+        }
+    }
+
+    fn exploration_textures(
+        ctx: &egui::Context,
+        exploration: &Exploration,
+        registration: &CurveRegistration,
+    ) -> Result<Vec<egui::TextureHandle>, String> {
+        Ok(exploration
+            .render_thumbnails(registration, EXPLORATION_THUMBNAIL_SIZE)?
+            .iter()
+            .enumerate()
+            .map(|(index, image)| ctx.load_texture(
+                format!("exploration_{}", index),
+                egui::ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.pixels()),
+                egui::TextureOptions::default(),
+            ))
+            .collect())
+    }
+
+    // Generated from the bindings, so that it stays true after a rebinding
+    fn help_window(&mut self, ctx: &egui::Context) {
+        let groups = [
+            (tr("app.bindings_app").to_string(), APP_ACTIONS.to_vec()),
+            (tr(self.curves[self.curve_index].name()).to_string(), self.curves[self.curve_index].actions()),
+        ];
+        let bindings = &self.config.bindings;
+        egui::Window::new(tr("app.help"))
+            .open(&mut self.show_help)
+            .anchor(egui::Align2::RIGHT_TOP, [-10., 10.])
+            .resizable(false)
+            .show(ctx, |ui| {
+                for (title, actions) in &groups {
+                    ui.strong(title);
+                    egui::Grid::new(format!("help_{}", title)).num_columns(2).show(ui, |ui| {
+                        for action in actions.iter().filter(|action| !bindings.inputs(action).is_empty()) {
+                            ui.label(bindings.describe(action));
+                            ui.label(tr(action.id));
+                            ui.end_row();
+                        }
+                    });
+                }
+            });
+    }
+
     fn curve(&mut self) -> &mut Box<dyn InteractiveCurve> {
         &mut self.curves[self.curve_index]
     }
 
-    fn save_screenshot(&mut self, ctx: &mut Context) {
-        let mut screenshot_filepath = std::env::current_dir().expect("Find current directory");
+    fn record_history(&mut self) {
+        let state = self.curves[self.curve_index].save_state();
+        self.histories[self.curve_index].record(state, Instant::now(), self.mouse_down);
+    }
+
+    fn undo(&mut self) {
+        let curve = &mut self.curves[self.curve_index];
+        if let Err(err) = self.histories[self.curve_index].undo(|state| curve.restore_state(state)) {
+            eprintln!("Undo skipped a state that cannot be restored: {}", err);
+        }
+    }
+
+    fn redo(&mut self) {
+        let curve = &mut self.curves[self.curve_index];
+        if let Err(err) = self.histories[self.curve_index].redo(|state| curve.restore_state(state)) {
+            eprintln!("Redo skipped a state that cannot be restored: {}", err);
+        }
+    }
+
+    // Next to the screenshots
+    fn save_oscilloscope_sound(&mut self) {
+        let mut path = std::env::current_dir().expect("Find current directory");
+        path.push(self.curve().screenshot_file_name());
+        path.set_extension("wav");
+        match export::export_xy_wav(self.curve().as_ref(), &path, OSCILLOSCOPE_SAMPLE_RATE, OSCILLOSCOPE_DURATION, OSCILLOSCOPE_DRAWINGS_PER_SECOND) {
+            Ok(()) => println!("Oscilloscope sound written to {}", path.display()),
+            Err(err) => eprintln!("Oscilloscope sound cannot be written: {}", err),
+        }
+    }
+
+    fn save_screenshot(&mut self, ctx: &mut Context) -> Result<PathBuf, String> {
+        let mut screenshot_filepath = std::env::current_dir().map_err(|err| err.to_string())?;
         screenshot_filepath.push(self.curve().screenshot_file_name());
         screenshot_filepath.set_extension("png");
-        let screenshot_filepath = screenshot_filepath.as_path();
-        let f = File::create(screenshot_filepath).expect("File created");
-        let writer = &mut std::io::BufWriter::new(f);
+        let f = File::create(&screenshot_filepath).map_err(|err| err.to_string())?;
+        self.write_frame_png(ctx, &mut std::io::BufWriter::new(f))?;
 
+        println!("Screenshot written to {}", screenshot_filepath.display());
+        Ok(screenshot_filepath)
+    }
+
+    // The last rendered frame, with the side panel
+    fn write_frame_png(&mut self, ctx: &mut Context, writer: impl std::io::Write) -> Result<(), String> {
         let image = self.screen.image(ctx);
-        if image.width() % 64 != 0 {
+        if !image.width().is_multiple_of(64) {
             let _good_width = (image.width()/64 + 1) * 64;
             println!("Screenshot has not a width multiple of 64 and cannot be saved")
             // Pad or something
@@ -67,19 +1240,40 @@ impl LissajouApp {
 
         let pixels = image
             .to_pixels(ctx)
-            .expect("Got pixels");
+            .map_err(|err| err.to_string())?;
         PngEncoder::new(writer)
             .write_image(&pixels, image.width(), image.height(), ::image::ColorType::Rgba8)
-            .expect("Image written");
-
-        println!("Screenshot written to {}", screenshot_filepath.display())
+            .map_err(|err| err.to_string())
     }
 }
 
 impl event::EventHandler<GameError> for LissajouApp {
 
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        ctx.gfx.window().set_title(&format!("{}", self.curve()));
+        let title = format!("{} - {}", self.title, self.curves[self.curve_index]);
+        ctx.gfx.window().set_title(&title);
+
+        // Changes from the input events, then the animation, that is not undoable
+        let midi_messages = self.midi.as_ref().map(MidiInputs::poll).unwrap_or_default();
+        for (control, value) in midi_messages {
+            self.handle_midi(control, value);
+        }
+        self.handle_osc(ctx);
+        self.handle_http(ctx);
+        self.apply_held_axes(ctx.time.delta().as_secs_f32());
+        self.record_history();
+        self.apply_audio(ctx.time.delta().as_secs_f32());
+        self.apply_sweeps(ctx.time.delta().as_secs_f32());
+        self.curve().tick(ctx.time.delta().as_secs_f32());
+        for curve in self.tiles.iter_mut().filter_map(|tile| tile.curve.as_mut()) {
+            curve.tick(ctx.time.delta().as_secs_f32());
+        }
+        self.link_tiles();
+        let state = self.curves[self.curve_index].save_state();
+        self.histories[self.curve_index].rebase(state);
+        if let Some(osc) = &mut self.osc {
+            osc.send_feedback(&self.registry, self.curve_index, self.curves[self.curve_index].as_ref());
+        }
 
         let gui_ctx = self.gui.ctx();
         let style = Style {
@@ -91,47 +1285,140 @@ impl event::EventHandler<GameError> for LissajouApp {
             .exact_width(256.)
             .show(&gui_ctx, |ui|  {
                 ui.horizontal(|ui| {
-                    if ui.add_enabled(self.curve_index > 0, egui::Button::new("<")).clicked() {
-                        self.curve_index -= 1;
+                    ui.label(tr("app.language"));
+                    let mut language = i18n::language();
+                    egui::ComboBox::from_id_source("language")
+                        .selected_text(language.name())
+                        .show_ui(ui, |ui| {
+                            for l in LANGUAGES {
+                                ui.selectable_value(&mut language, l, l.name());
+                            }
+                        });
+                    self.set_language(language);
+                });
+                ui.add(egui::TextEdit::singleline(&mut self.curve_search).hint_text(tr("app.search_curve")));
+                let search = self.curve_search.to_lowercase();
+                for category in self.registry.categories() {
+                    let matching: Vec<_> = self.registry
+                        .registrations()
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, registration)| registration.category == category)
+                        .filter(|(_, registration)|
+                            tr(&registration.name).to_lowercase().contains(&search) || tr(category).to_lowercase().contains(&search)
+                        )
+                        .collect();
+                    if matching.is_empty() {
+                        continue;
+                    }
+                    egui::CollapsingHeader::new(tr(category))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for (i, registration) in matching {
+                                ui.selectable_value(&mut self.curve_index, i, tr(&registration.name));
+                            }
+                        });
+                }
+                ui.separator();
+
+                let curve = self.curve();
+                ui.hyperlink_to(tr(curve.name()), curve.inspiration_url());
+                ui.horizontal(|ui| {
+                    let history = &self.histories[self.curve_index];
+                    let (can_undo, can_redo) = (history.can_undo(), history.can_redo());
+                    let undo_hint = self.config.bindings.describe(Self::app_action(ACTION_UNDO));
+                    let redo_hint = self.config.bindings.describe(Self::app_action(ACTION_REDO));
+                    if ui.add_enabled(can_undo, egui::Button::new(tr("app.undo"))).on_hover_text(undo_hint).clicked() {
+                        self.undo();
+                    }
+                    if ui.add_enabled(can_redo, egui::Button::new(tr("app.redo"))).on_hover_text(redo_hint).clicked() {
+                        self.redo();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(tr("app.bindings")).clicked() {
+                        self.show_bindings = !self.show_bindings;
                     }
-                    let curve = self.curve();
-                    ui.hyperlink_to(curve.name(), curve.inspiration_url());
-                    if ui.add_enabled(self.curve_index < (self.curves.len() - 1), egui::Button::new(">")).clicked() {
-                        self.curve_index += 1;
+                    let help_hint = self.config.bindings.describe(Self::app_action(ACTION_HELP));
+                    if ui.button(tr("app.help")).on_hover_text(help_hint).clicked() {
+                        self.show_help = !self.show_help;
                     }
                 });
                 ui.separator();
+                egui::CollapsingHeader::new(tr("gamepad.title"))
+                    .show(ui, |ui| self.gamepad_ui(ui));
+                egui::CollapsingHeader::new(tr("midi.title"))
+                    .show(ui, |ui| self.midi_ui(ui));
+                egui::CollapsingHeader::new(tr("audio.title"))
+                    .show(ui, |ui| self.audio_ui(ui));
+                egui::CollapsingHeader::new(tr("layout.title"))
+                    .show(ui, |ui| self.layout_ui(ui));
+                if !self.curves[self.curve_index].param_specs().is_empty() {
+                    egui::CollapsingHeader::new(tr("params.title"))
+                        .show(ui, |ui| self.params_ui(ui));
+                }
+                egui::CollapsingHeader::new(tr("app.bookmarks"))
+                    .default_open(true)
+                    .show(ui, |ui| self.bookmarks_ui(ui));
+                ui.separator();
 
                 self.curve().update_ui(ui);
+
+                // Keyboard and mouse shortcuts of the curve
+                let actions = self.curve().actions();
+                let shortcuts: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| {
+                        let inputs: Vec<String> = self.config.bindings
+                            .inputs(action)
+                            .iter()
+                            .filter(|input| matches!(input, Input::Key { .. } | Input::Mouse(_)))
+                            .map(Input::to_string)
+                            .collect();
+                        (!inputs.is_empty()).then(|| format!("[{}]: {}", inputs.join(", "), tr(action.id)))
+                    })
+                    .collect();
+                if !shortcuts.is_empty() {
+                    ui.separator();
+                    for shortcut in shortcuts {
+                        ui.label(shortcut);
+                    }
+                }
             });
+        self.bindings_window(&gui_ctx);
+        self.help_window(&gui_ctx);
+        if self.show_exploration {
+            self.exploration_window(&gui_ctx);
+        }
+        self.pad_roles_indicator(&gui_ctx);
         self.gui.update(ctx);
+        self.record_history();
 
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        let size = Vec2::new(
-            ctx.gfx.frame().width() as f32 - SIDE_PANEL_WIDTH_PX,
-            ctx.gfx.frame().height() as f32
-        );
-        let dest = Vec2::new(
-            SIDE_PANEL_WIDTH_PX + size.x / 2.0,
-            size.y / 2.0,
-        );
-
+        let (origin, area) = Self::tiles_area(ctx);
+        let layout = self.config.layout;
         let mut canvas = Canvas::from_screen_image(ctx, &mut self.screen, Color::WHITE);
-        for drawable in self.curve().compute_drawables(ctx, dest, size)? {
-            match drawable {
-                Image(img, params) => canvas.draw(
-                    img,
-                    params
-                ),
-                Meshes(builder, params) => canvas.draw(
-                    &Mesh::from_data(ctx, builder.build()),
-                    params
-                )
+        for (index, (dest, size)) in layout.tile_rects(origin, area).into_iter().enumerate() {
+            let rect = Rect::new(dest.x - size.x / 2.0, dest.y - size.y / 2.0, size.x, size.y);
+            // Each curve is clipped to its tile
+            if layout.is_split() && canvas.set_scissor_rect(rect).is_err() {
+                continue;
+            }
+            let curve = match &mut self.tiles[index].curve {
+                Some(curve) => curve,
+                None => &mut self.curves[self.curve_index],
+            };
+            let drawables = curve.compute_drawables(dest, size);
+            self.renderer.draw(ctx, &mut canvas, &drawables)?;
+            if layout.is_split() && index == self.focused_tile {
+                let border = Mesh::new_rectangle(ctx, DrawMode::stroke(FOCUSED_TILE_BORDER_PX), rect, FOCUSED_TILE_COLOR)?;
+                canvas.draw(&border, DrawParam::default());
             }
         }
+        canvas.set_default_scissor_rect();
 
         canvas.draw(&self.gui, DrawParam::new().dest(Vec2::ZERO));
         canvas.finish(ctx)?;
@@ -141,32 +1428,46 @@ impl event::EventHandler<GameError> for LissajouApp {
 
     fn mouse_button_down_event(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         button: MouseButton,
         x: f32,
         y: f32,
     ) -> GameResult {
-        Ok(
-            match button {
-                MouseButton::Left => {
-                    self.mouse_down = true;
-                    self.drag_start = Vec2::new(x, y)
-                },
-                _ => ()
+        if button == MouseButton::Left && !self.gui.ctx().is_pointer_over_area() {
+            let (origin, area) = Self::tiles_area(ctx);
+            let tile = self.config.layout.tile_at(origin, area, Vec2::new(x, y));
+            if let Some(tile) = tile.filter(|tile| *tile != self.focused_tile) {
+                self.focus_tile(tile);
+                self.focus_click = true;
+                return Ok(());
             }
-        )
+        }
+        if button == MouseButton::Left {
+            self.mouse_down = true;
+            self.drag_start = Vec2::new(x, y)
+        }
+        Ok(())
     }
 
     fn mouse_button_up_event(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         button: MouseButton,
         x: f32,
         y: f32,
     ) -> GameResult {
         self.mouse_down = false;
+        if button == MouseButton::Left && std::mem::take(&mut self.focus_click) {
+            return Ok(());
+        }
         let drag_start = self.drag_start;
-        Ok(self.curve().adjust_for_mouse_button_up(button, x, y, drag_start))
+        self.curve().adjust_for_mouse_button_up(button, x, y, drag_start);
+        // The left button stays for the side panel, the drags and the pickers
+        let over_gui = self.gui.ctx().is_pointer_over_area();
+        if button != MouseButton::Left && !(over_gui && self.capturing.is_none()) {
+            self.handle_input(ctx, Input::Mouse(format!("{:?}", button)), 1.0, None);
+        }
+        Ok(())
     }
 
     fn mouse_motion_event(
@@ -187,47 +1488,48 @@ impl event::EventHandler<GameError> for LissajouApp {
 
     fn mouse_wheel_event(&mut self, _ctx: &mut Context, _wheel_x: f32, wheel_y: f32) -> GameResult {
         let pos = self.mouse_pos;
-        Ok(self.curve().adjust_for_mouse_wheel(pos.x, pos.y, wheel_y))
+        self.curve().adjust_for_mouse_wheel(pos.x, pos.y, wheel_y);
+        Ok(())
     }
 
-    fn key_up_event(&mut self, _ctx: &mut Context, input: KeyInput) -> GameResult {
-        Ok(
-            match input.keycode {
-                Some(KeyCode::Numpad1) | Some(KeyCode::Key1) => self.curve_index = 0,
-                Some(KeyCode::Numpad2) | Some(KeyCode::Key2) => self.curve_index = 1,
-                Some(KeyCode::Numpad3) | Some(KeyCode::Key3) => self.curve_index = 2,
-                Some(KeyCode::Numpad4) | Some(KeyCode::Key4) => self.curve_index = 3,
-                _ => self.curve().adjust_for_key_up(input)
-            }
-
-        )
+    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, repeated: bool) -> GameResult {
+        let Some(keycode) = input.keycode else { return Ok(()) };
+        let name = format!("{:?}", keycode);
+        // Keys typed in the text fields of the side panel are not shortcuts
+        if repeated || MODIFIER_KEYS.contains(&name.as_str()) || (self.gui.ctx().wants_keyboard_input() && self.capturing.is_none()) {
+            return Ok(());
+        }
+        let key = Input::key(
+            &name,
+            input.mods.contains(KeyMods::CTRL),
+            input.mods.contains(KeyMods::SHIFT),
+            input.mods.contains(KeyMods::ALT),
+        );
+        self.handle_input(ctx, key, 1.0, None);
+        Ok(())
     }
 
     fn gamepad_button_down_event(
         &mut self,
         ctx: &mut Context,
         btn: Button,
-        _id: GamepadId,
+        id: GamepadId,
     ) -> GameResult {
-        Ok(
-            match btn {
-                Button::Select => self.curve_index = (self.curve_index + 1) % self.curves.len(),
-                Button::Start => self.save_screenshot(ctx),
-                _ => self.curve().adjust_for_button(btn)
-            }
-        )
+        let pad = self.pad_number(id);
+        self.handle_input(ctx, Input::Button(format!("{:?}", btn)), 1.0, Some(pad));
+        Ok(())
     }
 
     fn gamepad_axis_event(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         axis: Axis,
         value: f32,
-        _id: GamepadId,
+        id: GamepadId,
     ) -> GameResult {
-        Ok(
-            self.curve().adjust_for_axis(axis, value)
-        )
+        let pad = self.pad_number(id);
+        self.handle_input(ctx, Input::Axis(format!("{:?}", axis)), value, Some(pad));
+        Ok(())
     }
 
     fn quit_event(&mut self, _ctx: &mut Context) -> Result<bool, GameError> {
@@ -236,6 +1538,7 @@ impl event::EventHandler<GameError> for LissajouApp {
     }
 
     fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> GameResult {
-        Ok(self.gui.input.resize_event(width, height))
+        self.gui.input.resize_event(width, height);
+        Ok(())
     }
 }
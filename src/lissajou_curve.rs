@@ -1,167 +1,591 @@
 use std::collections::HashMap;
 use std::f32::consts::PI;
 use std::fmt::{Display, Formatter};
-use ggez::event::Button;
-use ggez::{Context, GameResult};
-use ggez::glam::Vec2;
-use ggez::graphics::{Color, DrawParam, MeshBuilder};
+#[cfg(feature = "gui")]
+use ggez::event::MouseButton;
+use glam::{Mat3, Vec2, Vec3};
+#[cfg(feature = "gui")]
+use ggegui::egui;
+#[cfg(feature = "gui")]
+use ggegui::egui::Ui;
 use rand::prelude::StdRng;
 use rand::{Rng, SeedableRng};
-use rstar::RTree;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::utils;
+use crate::animation::Animation;
+use crate::neighbor_graph::{GraphMode, GraphParams};
+#[cfg(feature = "gui")]
+use crate::neighbor_graph::GRAPH_MODES;
+use crate::bindings::{ActionSpec, GamepadRole};
+use crate::curve_registry::CurveRegistry;
 use crate::interactive_curve::{DrawData, InteractiveCurve};
-use crate::interactive_curve::DrawData::Meshes;
+use crate::params::ParamSpec;
+use crate::render::Rgba;
+use crate::i18n::tr_args;
+#[cfg(feature = "gui")]
+use crate::i18n::tr;
 
+const NAME: &str = "curve.lissajou";
+const CATEGORY: &str = "category.oscillations";
+const ACTION_FREQ_X_DOWN: &str = "action.lissajou.freq_x_down";
+const ACTION_FREQ_X_UP: &str = "action.lissajou.freq_x_up";
+const ACTION_FREQ_Y_DOWN: &str = "action.lissajou.freq_y_down";
+const ACTION_FREQ_Y_UP: &str = "action.lissajou.freq_y_up";
+const ACTION_PHASE_DOWN: &str = "action.lissajou.phase_down";
+const ACTION_PHASE_UP: &str = "action.lissajou.phase_up";
+const ACTION_POINTS_DOWN: &str = "action.lissajou.points_down";
+const ACTION_POINTS_UP: &str = "action.lissajou.points_up";
+const ACTION_JITTER_DOWN: &str = "action.lissajou.jitter_down";
+const ACTION_JITTER_UP: &str = "action.lissajou.jitter_up";
+const ACTION_MAX_DISTANCE_DOWN: &str = "action.lissajou.max_distance_down";
+const ACTION_MAX_DISTANCE_UP: &str = "action.lissajou.max_distance_up";
+const ACTIONS: [ActionSpec; 12] = [
+    ActionSpec::trigger(ACTION_FREQ_X_DOWN, &["Pad DPadDown"]),
+    ActionSpec::trigger(ACTION_FREQ_X_UP, &["Pad DPadUp"]),
+    ActionSpec::trigger(ACTION_FREQ_Y_DOWN, &["Pad DPadLeft"]),
+    ActionSpec::trigger(ACTION_FREQ_Y_UP, &["Pad DPadRight"]),
+    ActionSpec::trigger(ACTION_PHASE_DOWN, &["Pad LeftTrigger"]),
+    ActionSpec::trigger(ACTION_PHASE_UP, &["Pad RightTrigger"]),
+    ActionSpec::trigger(ACTION_POINTS_DOWN, &["Pad LeftTrigger2"]),
+    ActionSpec::trigger(ACTION_POINTS_UP, &["Pad RightTrigger2"]),
+    ActionSpec::trigger(ACTION_JITTER_DOWN, &["Pad West"]),
+    ActionSpec::trigger(ACTION_JITTER_UP, &["Pad North"]),
+    ActionSpec::trigger(ACTION_MAX_DISTANCE_DOWN, &["Pad South"]),
+    ActionSpec::trigger(ACTION_MAX_DISTANCE_UP, &["Pad East"]),
+];
+const ROLES: [GamepadRole; 2] = [
+    GamepadRole::new("role.lissajou.frequencies", &[
+        ACTION_FREQ_X_DOWN, ACTION_FREQ_X_UP, ACTION_FREQ_Y_DOWN, ACTION_FREQ_Y_UP, ACTION_PHASE_DOWN, ACTION_PHASE_UP,
+    ]),
+    GamepadRole::new("role.lissajou.points", &[
+        ACTION_POINTS_DOWN, ACTION_POINTS_UP, ACTION_JITTER_DOWN, ACTION_JITTER_UP, ACTION_MAX_DISTANCE_DOWN, ACTION_MAX_DISTANCE_UP,
+    ]),
+];
 const TWO_PI: f32 = 2.0 * PI;
 const T_OFFSET: f32 = 0.012;
 const END_T: f32 = T_OFFSET + TWO_PI;
+#[cfg(feature = "gui")]
 const D_INCREMENT: f32 = PI / 18.0;
-const NB_POINT_INCREMENT: usize = 100;
+#[cfg(feature = "gui")]
+const NB_POINT_INCREMENT: f32 = 100.0;
+const MIN_NB_POINTS: usize = 2;
+const MAX_NB_POINTS: usize = 20_000;
+#[cfg(feature = "gui")]
 const JITTER_FACTOR_INCREMENT: f32 = 0.002;
+#[cfg(feature = "gui")]
 const MAX_DISTANCE_RATIO_INCREMENT: f32 = 0.05;
 const FREQ_X: usize = 0;
 const FREQ_Y: usize = 1;
+const FREQ_Z: usize = 2;
+#[cfg(feature = "gui")]
+const ROTATION_PER_PIXEL: f32 = PI / 360.0;
+const PERSPECTIVE_DISTANCE: f32 = 3.0; // Camera distance, in curve radius
 const SIZE_RATIO: f32 = 0.9;
+const DEFAULT_K_NEIGHBORS: usize = 6;
+const DEFAULT_MAX_EDGES: usize = 100_000;
+// One per graph mode, in the GRAPH_MODES order
+const DEFAULT_EDGE_STYLES: [EdgeStyle; 4] = [
+    EdgeStyle::new(2.0, [0.0, 0.0, 0.0], [0.6, 0.6, 0.6]),
+    EdgeStyle::new(1.5, [0.05, 0.1, 0.4], [0.5, 0.7, 0.9]),
+    EdgeStyle::new(1.0, [0.0, 0.0, 0.0], [0.5, 0.5, 0.5]),
+    EdgeStyle::new(3.0, [0.5, 0.0, 0.0], [1.0, 0.6, 0.2]),
+];
+// Also the message keys of their labels
+const PARAM_FREQ_X: &str = "lissajou.freq_x";
+const PARAM_FREQ_Y: &str = "lissajou.freq_y";
+const PARAM_FREQ_Z: &str = "lissajou.freq_z";
+const PARAM_PHASE_X: &str = "lissajou.phase_x";
+const PARAM_PHASE_Y: &str = "lissajou.phase_y";
+const PARAM_PHASE_Z: &str = "lissajou.phase_z";
+const PARAM_NB_POINTS: &str = "lissajou.points";
+const PARAM_JITTER: &str = "lissajou.jitter";
+const PARAM_MAX_DISTANCE: &str = "lissajou.max_distance";
+const PARAMS: [&str; 9] = [
+    PARAM_FREQ_X, PARAM_FREQ_Y, PARAM_FREQ_Z, PARAM_PHASE_X, PARAM_PHASE_Y, PARAM_PHASE_Z, PARAM_NB_POINTS, PARAM_JITTER, PARAM_MAX_DISTANCE,
+];
+// Only listed in 3D, where they change the drawing
+const PARAMS_Z: [&str; 2] = [PARAM_FREQ_Z, PARAM_PHASE_Z];
+const MAX_FREQ: f32 = 10.0;
+const MAX_JITTER: f32 = 0.1;
+const DEFAULT_ANIMATION_SPEED: f32 = 0.5;
+#[cfg(feature = "gui")]
+const MAX_ANIMATION_SPEED: f32 = 10.0;
+const PEN_COLOR: Rgba = Rgba::RED;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct EdgeStyle {
+    line_width: f32,
+    near_color: [f32; 3], // Color of the lines between the closest points
+    far_color: [f32; 3], // Color of the lines between points at max distance
+}
+
+impl EdgeStyle {
+    const fn new(line_width: f32, near_color: [f32; 3], far_color: [f32; 3]) -> Self {
+        Self { line_width, near_color, far_color }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LissajouParams {
+    pub freq: [f32; 3],
+    pub phase: f32, // Phase of X
+    pub phase_y: f32,
+    pub phase_z: f32,
+    pub three_d: bool,
+    pub jitter_factor: f32,
+    pub nb_points: usize,
+    pub seed: u64,
+}
+
+impl LissajouParams {
+    /// A point of the curve at time t, in [-radius, radius] on each axis. z is 0 in 2D.
+    pub fn point(&self, radius: f32, t: f32, rng: &mut impl Rng) -> [f32; 3] {
+        let rx = radius * self.jitter(rng, 1.4);
+        let ry = radius * self.jitter(rng, 1.4);
+        let a = self.freq[FREQ_X] * self.jitter(rng, 1.0);
+        let b = self.freq[FREQ_Y] * self.jitter(rng, 1.0);
+        let z = if self.three_d {
+            let rz = radius * self.jitter(rng, 1.4);
+            let c = self.freq[FREQ_Z] * self.jitter(rng, 1.0);
+            rz * f32::sin(c * t + self.phase_z)
+        } else {
+            0.0
+        };
+
+        [
+            rx * f32::sin(a * t + self.phase),
+            ry * f32::sin(b * t + self.phase_y),
+            z,
+        ]
+    }
+
+    /// The nb_points points of the curve from its start until end_t, jittered with the seed.
+    pub fn points(&self, radius: f32, end_t: f32) -> Vec<[f32; 3]> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let t_increment = TWO_PI / (self.nb_points as f32);
+        let mut t = T_OFFSET;
+        let mut res = vec![];
+
+        while t < end_t {
+            res.push(self.point(radius, t, &mut rng));
+            t += t_increment
+        }
+
+        res
+    }
+
+    fn jitter(&self, rng: &mut impl Rng, factor_amp: f32) -> f32 {
+        if self.jitter_factor == 0.0 {
+            return 1.0
+        }
+        let jitter_factor = f32::abs(self.jitter_factor) * factor_amp;
+        rng.gen_range((1.0 - jitter_factor)..(1.0 + jitter_factor))
+    }
+}
+
+impl Default for LissajouParams {
+    fn default() -> Self {
+        Self {
+            freq: [2.0, 3.0, 5.0],
+            phase: 0.0,
+            phase_y: 0.0,
+            phase_z: 0.0,
+            three_d: false,
+            jitter_factor: 0.0,
+            nb_points: 500,
+            seed: 0,
+        }
+    }
+}
+
+// What is saved for undo and bookmarks
+#[derive(Serialize, Deserialize)]
+struct LissajouState {
+    params: LissajouParams,
+    yaw: f32,
+    pitch: f32,
+    max_distance_ratio: f32,
+    graph_mode: GraphMode,
+    k_neighbors: usize,
+    max_edges: usize,
+    edge_styles: [EdgeStyle; 4],
+}
 
 pub struct Lissajou {
-    freq: [f32; 2],
-    phase: f32,
-    jitter_factor: f32,
-    nb_points: usize,
+    params: LissajouParams,
+    yaw: f32,
+    pitch: f32,
+    #[cfg(feature = "gui")]
+    drag_start_rotation: Option<(f32, f32)>,
     max_distance_ratio: f32,
+    graph_mode: GraphMode,
+    k_neighbors: usize,
+    max_edges: usize,
+    edge_styles: [EdgeStyle; 4],
+    animation: Animation,
 }
 
 impl Lissajou {
+    pub fn register(registry: &mut CurveRegistry) {
+        registry.register(NAME, CATEGORY, || Box::new(Self::new()));
+    }
+
+    fn param_spec(id: &'static str) -> ParamSpec {
+        match id {
+            PARAM_FREQ_X | PARAM_FREQ_Y | PARAM_FREQ_Z => ParamSpec::new(id, 0.0, MAX_FREQ).step(0.01),
+            PARAM_PHASE_X | PARAM_PHASE_Y | PARAM_PHASE_Z => ParamSpec::new(id, 0.0, TWO_PI).unit("rad"),
+            PARAM_NB_POINTS => ParamSpec::new(id, MIN_NB_POINTS as f32, MAX_NB_POINTS as f32).step(1.0).log(),
+            PARAM_JITTER => ParamSpec::new(id, 0.0, MAX_JITTER),
+            _ => ParamSpec::new(id, 0.0, 1.0),
+        }
+    }
+
+    // The trigger actions step the parameters in their range. The phase turns around.
+    #[cfg(feature = "gui")]
+    fn step_param(&mut self, id: &'static str, delta: f32) {
+        let Some(value) = self.param(id) else { return };
+        let value = if id == PARAM_PHASE_X { (value + delta).rem_euclid(TWO_PI) } else { value + delta };
+        self.set_param(id, Self::param_spec(id).snap(value));
+    }
+
     pub fn new() -> Self {
+        Self::with_params(LissajouParams::default())
+    }
+
+    pub fn with_params(params: LissajouParams) -> Self {
         Self {
-            freq: [2.0, 3.0],
-            phase: 0.0,
-            jitter_factor: 0.0,
-            nb_points: 500,
+            params,
+            yaw: PI / 6.0,
+            pitch: PI / 8.0,
+            #[cfg(feature = "gui")]
+            drag_start_rotation: None,
             max_distance_ratio: 0.2,
+            graph_mode: GraphMode::AllWithin,
+            k_neighbors: DEFAULT_K_NEIGHBORS,
+            max_edges: DEFAULT_MAX_EDGES,
+            edge_styles: DEFAULT_EDGE_STYLES,
+            animation: Animation::new(DEFAULT_ANIMATION_SPEED),
         }
     }
 
-    fn jitter(&self, rng: &mut StdRng, factor_amp: f32) -> f32 {
-        if self.jitter_factor == 0.0 {
-            return 1.0
-        }
-        let jitter_factor = f32::abs(self.jitter_factor) * factor_amp;
-        rng.gen_range((1.0 - jitter_factor)..(1.0 + jitter_factor))
+    pub fn params(&self) -> &LissajouParams {
+        &self.params
     }
 
-    fn color(&self, dist_ratio: f32) -> Color {
-        let gray_level = 0.6 * dist_ratio;
-        let transparency_level = 1.0 - dist_ratio;
-        Color::new(gray_level, gray_level, gray_level, transparency_level)
+    pub fn params_mut(&mut self) -> &mut LissajouParams {
+        &mut self.params
     }
 
-    fn z(&self, dist_ratio: f32) -> i32 {
-        -(5.0 * dist_ratio) as i32
+    // depth_ratio goes from 0 (closest to the viewer) to 1 (farthest)
+    fn color(&self, dist_ratio: f32, depth_ratio: f32) -> Rgba {
+        let [r, g, b] = self.edge_style().near_color;
+        let near = Rgba::rgb(r, g, b);
+        let [r, g, b] = self.edge_style().far_color;
+        let far = Rgba::rgb(r, g, b);
+        let mut color = utils::interpolate_color(&near, &far, f32::max(dist_ratio, depth_ratio));
+        color.a = (1.0 - dist_ratio) * (1.0 - 0.8 * depth_ratio);
+        color
     }
 
-    fn point(self: &Self, radius_x: f32, radius_y: f32, t: f32, rng: &mut StdRng) -> (f32, f32) {
-        let rx = radius_x * self.jitter(rng, 1.4);
-        let ry = radius_y * self.jitter(rng, 1.4);
-        let a = self.freq[FREQ_X] * self.jitter(rng, 1.0);
-        let b = self.freq[FREQ_Y] * self.jitter(rng, 1.0);
+    fn edge_style(&self) -> &EdgeStyle {
+        &self.edge_styles[self.graph_mode.index()]
+    }
 
-        return (
-            rx * f32::sin(a * t + self.phase),
-            ry * f32::sin(b * t),
-        )
+    #[cfg(feature = "gui")]
+    fn edge_style_mut(&mut self) -> &mut EdgeStyle {
+        &mut self.edge_styles[self.graph_mode.index()]
     }
 
-    fn points(self: &Self, radius_x: f32, radius_y: f32) -> RTree<(f32, f32)> {
-        let mut rng = StdRng::seed_from_u64(0);
-        let t_increment = TWO_PI / (self.nb_points as f32);
-        let mut t = T_OFFSET;
-        let mut res = RTree::new();
+    fn z(&self, dist_ratio: f32, depth_ratio: f32) -> i32 {
+        -(5.0 * dist_ratio + 5.0 * depth_ratio) as i32
+    }
 
-        while t < END_T {
-            res.insert(self.point(radius_x, radius_y, t, &mut rng));
-            t += t_increment
+    // Rotates then projects a point of the curve with a perspective camera.
+    // Returns the screen position and the depth ratio of the point.
+    fn project(&self, pt: &[f32; 3], radius: f32) -> (Vec2, f32) {
+        if !self.params.three_d {
+            return (Vec2::new(pt[0], pt[1]), 0.0);
         }
+        let rotated = self.rotation() * Vec3::from_array(*pt);
+        let camera_distance = PERSPECTIVE_DISTANCE * radius;
+        let scale = camera_distance / (camera_distance + rotated.z);
+        let depth_ratio = ((rotated.z / radius + 1.0) / 2.0).clamp(0.0, 1.0);
+        (scale * rotated.truncate(), depth_ratio)
+    }
 
-        res
+    fn rotation(&self) -> Mat3 {
+        Mat3::from_rotation_x(self.pitch) * Mat3::from_rotation_y(self.yaw)
+    }
+
+    fn end_t(&self) -> f32 {
+        if self.animation.is_enabled() {
+            T_OFFSET + self.animation.t()
+        } else {
+            END_T
+        }
     }
 
-    fn line_for_points(&self, p1: &(f32, f32), p2: &(f32, f32)) -> [Vec2; 2] {
-        [Vec2::new(p1.0, p1.1), Vec2::new(p2.0, p2.1)]
+}
+
+impl Default for Lissajou {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Display for Lissajou {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "LISSAJOU freq-X (U / D): {:<5} freq-Y (L / R): {:<5} phase (LT / RT): {:<5} points (BLT / BRT): {:<5} jitter (X / Y): {:<5} max_dist (A / B): {:<5}",
-            self.freq[FREQ_X], self.freq[FREQ_Y], self.phase, self.nb_points, self.jitter_factor, self.max_distance_ratio
-        )
+        let values = [
+            self.params.freq[FREQ_X], self.params.freq[FREQ_Y], self.params.phase, self.params.nb_points as f32, self.params.jitter_factor, self.max_distance_ratio
+        ].map(|value| format!("{:<5}", value));
+        let args: Vec<&dyn Display> = values.iter().map(|value| value as &dyn Display).collect();
+        write!(f, "{}", tr_args("lissajou.status", &args))?;
+        if self.params.three_d {
+            write!(f, "{}", tr_args("lissajou.status_z", &[&format!("{:<5}", self.params.freq[FREQ_Z])]))?;
+        }
+        Ok(())
     }
 }
 
 impl InteractiveCurve for Lissajou {
-    fn compute_drawables(&mut self, _ctx: &mut Context, dest: Vec2, size: Vec2) -> GameResult<Vec<DrawData>> {
-        let min_size = size.min_element();
-        let point_index = self.points(SIZE_RATIO * min_size / 2.0, SIZE_RATIO * min_size / 2.0);
-        let max_distance = size.x * self.max_distance_ratio;
-        let max_distance2 = max_distance * max_distance;
-        let mut layers = HashMap::new();
+    #[cfg(feature = "gui")]
+    fn update_ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("lissajou_params")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(tr("lissajou.three_d"));
+                ui.checkbox(&mut self.params.three_d, "");
+                ui.end_row();
+                if self.params.three_d {
+                    ui.label(tr("lissajou.yaw"));
+                    ui.add(egui::Slider::new(&mut self.yaw, -PI..=PI).fixed_decimals(2));
+                    ui.end_row();
+                    ui.label(tr("lissajou.pitch"));
+                    ui.add(egui::Slider::new(&mut self.pitch, -PI..=PI).fixed_decimals(2));
+                    ui.end_row();
+                }
+                ui.label(tr("lissajou.seed"));
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut self.params.seed));
+                    if ui.button("?").clicked() {
+                        self.params.seed = rand::random();
+                    }
+                });
+                ui.end_row();
+            });
+        ui.separator();
 
-        for pt in point_index.iter() {
-            for (npt, dist2) in point_index.nearest_neighbor_iter_with_distance_2(pt) {
-                if dist2 > max_distance2 {
-                    break;
+        egui::Grid::new("lissajou_graph")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(tr("lissajou.graph"));
+                egui::ComboBox::from_id_source("lissajou_graph_mode")
+                    .selected_text(tr(self.graph_mode.name()))
+                    .show_ui(ui, |ui| {
+                        for mode in GRAPH_MODES {
+                            ui.selectable_value(&mut self.graph_mode, mode, tr(mode.name()));
+                        }
+                    });
+                ui.end_row();
+                if self.graph_mode == GraphMode::KNearest {
+                    ui.label("k:");
+                    ui.add(egui::Slider::new(&mut self.k_neighbors, 1..=30));
+                    ui.end_row();
                 }
-                let dist_ratio = dist2.sqrt() / max_distance;
-                layers
-                    .entry(self.z(dist_ratio))
-                    .or_insert(MeshBuilder::new())
-                    .line(&self.line_for_points(pt, npt), 2.0, self.color(dist_ratio))?;
-            }
+                ui.label(tr("lissajou.max_edges"));
+                ui.add(egui::DragValue::new(&mut self.max_edges).speed(1000).clamp_range(0..=10_000_000));
+                ui.end_row();
+                let style = self.edge_style_mut();
+                ui.label(tr("lissajou.edge_width"));
+                ui.add(egui::Slider::new(&mut style.line_width, 0.1..=10.0).fixed_decimals(1));
+                ui.end_row();
+                ui.label(tr("lissajou.near_color"));
+                ui.color_edit_button_rgb(&mut style.near_color);
+                ui.end_row();
+                ui.label(tr("lissajou.far_color"));
+                ui.color_edit_button_rgb(&mut style.far_color);
+                ui.end_row();
+            });
+        if ui.button("r").clicked() {
+            *self.edge_style_mut() = DEFAULT_EDGE_STYLES[self.graph_mode.index()];
+        }
+        ui.separator();
+
+        self.animation.update_ui(ui, MAX_ANIMATION_SPEED);
+    }
+
+    fn tick(&mut self, dt: f32) {
+        let phase_drift = self.animation.tick(dt, TWO_PI);
+        if phase_drift != 0.0 {
+            self.params.phase = (self.params.phase + phase_drift).rem_euclid(TWO_PI);
         }
+    }
 
-        Ok(
+    fn compute_drawables(&mut self, dest: Vec2, size: Vec2) -> Vec<DrawData<'_>> {
+        let radius = SIZE_RATIO * size.min_element() / 2.0;
+        let points = self.params.points(radius, self.end_t());
+        let (screen_points, depth_ratios): (Vec<Vec2>, Vec<f32>) = points
+            .iter()
+            .map(|pt| self.project(pt, radius))
+            .unzip();
+        let graph_params = GraphParams {
+            mode: self.graph_mode,
+            k: self.k_neighbors,
+            max_distance: size.x * self.max_distance_ratio,
+            max_edges: self.max_edges,
+        };
+        let line_width = self.edge_style().line_width;
+        let mut layers = HashMap::new();
+
+        // Neighbors are searched in the curve space, so that they do not change with the projection
+        for edge in graph_params.edges(&points, &screen_points) {
+            let dist_ratio = edge.distance / graph_params.max_distance;
+            let depth_ratio = (depth_ratios[edge.from] + depth_ratios[edge.to]) / 2.0;
+            let z = self.z(dist_ratio, depth_ratio);
             layers
-                .drain()
-                .map(|(z, builder)|
-                    Meshes(builder, DrawParam::new().dest(dest).z(z))
-                )
-                .collect()
-        )
-    }
-
-    fn adjust_for_button(self: &mut Self, btn: Button) {
-        match btn {
-            Button::DPadDown        => self.freq[FREQ_X] -= 1.0,
-            Button::DPadUp          => self.freq[FREQ_X] += 1.0,
-            Button::DPadLeft        => self.freq[FREQ_Y] -= 1.0,
-            Button::DPadRight       => self.freq[FREQ_Y] += 1.0,
-            Button::LeftTrigger     => self.phase -= D_INCREMENT,
-            Button::RightTrigger    => self.phase += D_INCREMENT,
-            Button::LeftTrigger2    => self.nb_points -= NB_POINT_INCREMENT,
-            Button::RightTrigger2   => self.nb_points += NB_POINT_INCREMENT,
-            Button::West            => if self.jitter_factor >= JITTER_FACTOR_INCREMENT {
-                self.jitter_factor -= JITTER_FACTOR_INCREMENT
-            } else {
-                self.jitter_factor = 0.0;
-            },
-            Button::North           => self.jitter_factor += JITTER_FACTOR_INCREMENT,
-            Button::South           => self.max_distance_ratio -= MAX_DISTANCE_RATIO_INCREMENT,
-            Button::East            => self.max_distance_ratio += MAX_DISTANCE_RATIO_INCREMENT,
+                .entry(z)
+                .or_insert_with(|| DrawData::new().dest(dest).z(z))
+                .line(&[screen_points[edge.from], screen_points[edge.to]], line_width, self.color(dist_ratio, depth_ratio));
+        }
+
+        let mut result: Vec<DrawData> = layers.into_values().collect();
+
+        if let (true, Some(pen)) = (self.animation.is_enabled(), screen_points.last()) {
+            let mut data = DrawData::new().dest(dest).z(1);
+            data.circle(*pen, 3.0 * line_width, None, PEN_COLOR);
+            result.push(data);
+        }
+
+        result
+    }
+
+    fn actions(&self) -> Vec<ActionSpec> {
+        ACTIONS.to_vec()
+    }
+
+    fn gamepad_roles(&self) -> Vec<GamepadRole> {
+        ROLES.to_vec()
+    }
+
+    // Whole curve, even during an animation, in the current 3D rotation
+    fn pen_path(&self) -> Vec<Vec2> {
+        self.params.points(1.0, END_T).iter().map(|point| self.project(point, 1.0).0).collect()
+    }
+
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        PARAMS
+            .into_iter()
+            .filter(|id| self.params.three_d || !PARAMS_Z.contains(id))
+            .map(Self::param_spec)
+            .collect()
+    }
+
+    fn param(&self, id: &str) -> Option<f32> {
+        match id {
+            PARAM_FREQ_X => Some(self.params.freq[FREQ_X]),
+            PARAM_FREQ_Y => Some(self.params.freq[FREQ_Y]),
+            PARAM_FREQ_Z => Some(self.params.freq[FREQ_Z]),
+            PARAM_PHASE_X => Some(self.params.phase.rem_euclid(TWO_PI)),
+            PARAM_PHASE_Y => Some(self.params.phase_y.rem_euclid(TWO_PI)),
+            PARAM_PHASE_Z => Some(self.params.phase_z.rem_euclid(TWO_PI)),
+            PARAM_NB_POINTS => Some(self.params.nb_points as f32),
+            PARAM_JITTER => Some(self.params.jitter_factor),
+            PARAM_MAX_DISTANCE => Some(self.max_distance_ratio),
+            _ => None
+        }
+    }
+
+    fn set_param(&mut self, id: &str, value: f32) {
+        match id {
+            PARAM_FREQ_X => self.params.freq[FREQ_X] = value,
+            PARAM_FREQ_Y => self.params.freq[FREQ_Y] = value,
+            PARAM_FREQ_Z => self.params.freq[FREQ_Z] = value,
+            PARAM_PHASE_X => self.params.phase = value,
+            PARAM_PHASE_Y => self.params.phase_y = value,
+            PARAM_PHASE_Z => self.params.phase_z = value,
+            PARAM_NB_POINTS => self.params.nb_points = (value.round() as usize).clamp(MIN_NB_POINTS, MAX_NB_POINTS),
+            PARAM_JITTER => self.params.jitter_factor = value,
+            PARAM_MAX_DISTANCE => self.max_distance_ratio = value,
             _ => ()
         }
     }
 
+    #[cfg(feature = "gui")]
+    fn on_trigger(&mut self, action: &str, _pointer: Vec2) {
+        let (id, delta) = match action {
+            ACTION_FREQ_X_DOWN          => (PARAM_FREQ_X, -1.0),
+            ACTION_FREQ_X_UP            => (PARAM_FREQ_X, 1.0),
+            ACTION_FREQ_Y_DOWN          => (PARAM_FREQ_Y, -1.0),
+            ACTION_FREQ_Y_UP            => (PARAM_FREQ_Y, 1.0),
+            ACTION_PHASE_DOWN           => (PARAM_PHASE_X, -D_INCREMENT),
+            ACTION_PHASE_UP             => (PARAM_PHASE_X, D_INCREMENT),
+            ACTION_POINTS_DOWN          => (PARAM_NB_POINTS, -NB_POINT_INCREMENT),
+            ACTION_POINTS_UP            => (PARAM_NB_POINTS, NB_POINT_INCREMENT),
+            ACTION_JITTER_DOWN          => (PARAM_JITTER, -JITTER_FACTOR_INCREMENT),
+            ACTION_JITTER_UP            => (PARAM_JITTER, JITTER_FACTOR_INCREMENT),
+            ACTION_MAX_DISTANCE_DOWN    => (PARAM_MAX_DISTANCE, -MAX_DISTANCE_RATIO_INCREMENT),
+            ACTION_MAX_DISTANCE_UP      => (PARAM_MAX_DISTANCE, MAX_DISTANCE_RATIO_INCREMENT),
+            _ => return
+        };
+        self.step_param(id, delta);
+    }
+
+    #[cfg(feature = "gui")]
+    fn adjust_for_mouse_button_up(&mut self, _button: MouseButton, _x: f32, _y: f32, _drag_start: Vec2) {
+        self.drag_start_rotation = None;
+    }
+
+    #[cfg(feature = "gui")]
+    fn adjust_for_mouse_drag(&mut self, x: f32, y: f32, drag_start: Vec2) {
+        if !self.params.three_d {
+            return;
+        }
+        let (start_yaw, start_pitch) = *self.drag_start_rotation.get_or_insert((self.yaw, self.pitch));
+        self.yaw = start_yaw + (x - drag_start.x) * ROTATION_PER_PIXEL;
+        self.pitch = start_pitch - (y - drag_start.y) * ROTATION_PER_PIXEL;
+    }
+
+    fn save_state(&self) -> Value {
+        serde_json::to_value(LissajouState {
+            params: self.params,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            max_distance_ratio: self.max_distance_ratio,
+            graph_mode: self.graph_mode,
+            k_neighbors: self.k_neighbors,
+            max_edges: self.max_edges,
+            edge_styles: self.edge_styles,
+        }).unwrap()
+    }
+
+    fn restore_state(&mut self, state: &Value) -> Result<(), String> {
+        let state: LissajouState = serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.params = state.params;
+        self.yaw = state.yaw;
+        self.pitch = state.pitch;
+        self.max_distance_ratio = state.max_distance_ratio;
+        self.graph_mode = state.graph_mode;
+        self.k_neighbors = state.k_neighbors;
+        self.max_edges = state.max_edges;
+        self.edge_styles = state.edge_styles;
+        Ok(())
+    }
+
     fn screenshot_file_name(&self) -> String {
-        format!(
-            "lissajou_fx{}_fy{}_phs{}_pts{}_jtr{}_dst{}",
-            self.freq[FREQ_X], self.freq[FREQ_Y], self.phase, self.nb_points, self.jitter_factor, self.max_distance_ratio
-        )
+        let name = format!(
+            "lissajou_fx{}_fy{}_phs{}_phy{}_pts{}_jtr{}_sd{}_dst{}",
+            self.params.freq[FREQ_X], self.params.freq[FREQ_Y], self.params.phase, self.params.phase_y, self.params.nb_points, self.params.jitter_factor, self.params.seed, self.max_distance_ratio
+        );
+        if self.params.three_d {
+            format!("{}_fz{}_phz{}_yaw{}_pitch{}", name, self.params.freq[FREQ_Z], self.params.phase_z, self.yaw, self.pitch)
+        } else {
+            name
+        }
     }
 
     fn name(&self) -> &str {
-        "Courbe de Lissajou"
+        NAME
     }
 
     fn inspiration_url(&self) -> &str {
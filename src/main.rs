@@ -1,6 +1,25 @@
-use lissajous::run;
-use ggez::GameResult;
+use std::process::ExitCode;
+use lissajous::{run_cli, CurveRegistry};
 
-pub fn main() -> GameResult {
-    run()
+pub fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    #[cfg(feature = "gui")]
+    if args.is_empty() {
+        return match lissajous::run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    match run_cli(&args, &CurveRegistry::default()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -1,37 +1,64 @@
 use std::fmt::{Display, Formatter};
-use std::panic;
 use std::time::Instant;
+#[cfg(feature = "gui")]
 use ggez::event::MouseButton;
-use ggez::{Context, GameResult};
-use ggez::glam::{DVec2, Vec2};
-use ggez::glam::i32::IVec2;
-use ggez::graphics::{Color, DrawMode, DrawParam, Image as GImage, ImageFormat, MeshBuilder};
-use ggez::input::keyboard::{KeyCode, KeyInput};
+use glam::{DVec2, Vec2};
+use glam::i32::IVec2;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "gui")]
 use ggegui::egui;
+#[cfg(feature = "gui")]
 use ggegui::egui::{RichText, Ui};
 use crate::utils;
-use crate::color_picker::{ColorPicker, HSV};
+use crate::bindings::ActionSpec;
+use crate::color_picker::{self, ColorPicker, HSV};
+use crate::curve_registry::CurveRegistry;
 use crate::interactive_curve::{DrawData, InteractiveCurve};
-use crate::interactive_curve::DrawData::{Image, Meshes};
+use crate::params::ParamSpec;
+use crate::render::{self, Rgba};
+use crate::i18n::tr;
+
+const NAME: &str = "curve.mandelbrot";
+const CATEGORY: &str = "category.fractals";
+const ACTION_ZOOM_IN: &str = "action.mandelbrot.zoom_in";
+const ACTION_ZOOM_OUT: &str = "action.mandelbrot.zoom_out";
+const ACTION_RESET: &str = "action.mandelbrot.reset";
+const ACTION_HISTOGRAM: &str = "action.mandelbrot.histogram";
+const ACTION_POINT_DETAILS: &str = "action.mandelbrot.point_details";
+const ACTION_CLOSE_PICKER: &str = "action.mandelbrot.close_picker";
+const ACTIONS: [ActionSpec; 6] = [
+    ActionSpec::trigger(ACTION_POINT_DETAILS, &["Mouse Right"]),
+    ActionSpec::trigger(ACTION_ZOOM_IN, &["Z"]),
+    ActionSpec::trigger(ACTION_ZOOM_OUT, &["X"]),
+    ActionSpec::trigger(ACTION_RESET, &["R"]),
+    ActionSpec::trigger(ACTION_HISTOGRAM, &["H"]),
+    ActionSpec::trigger(ACTION_CLOSE_PICKER, &["Escape"]),
+];
 
 // Draw constants
 const TARGET_SIZE: f32 = 15.;
-const DARK_GREY: Color = Color {
-    r: 0.1,
-    g: 0.1,
-    b: 0.1,
-    a: 1.0,
-};
+const DARK_GREY: Rgba = Rgba::new(0.1, 0.1, 0.1, 1.0);
 
 // Params constants
 const MAX_ITERATIONS_PARAM: usize = 0;
 const OUT_COLOR_PARAM: usize = 1;
 const ALMOST_IN_COLOR_PARAM: usize = 2;
+#[cfg(feature = "gui")]
 const ZOOM_STEP_PCT: f64 = 0.25;
 const DEFAULT_BOX_LEFT_X: f64 = -2.;
 const DEFAULT_BOX_RIGHT_X: f64 = 0.5;
 const DEFAULT_SPAN: f64 = DEFAULT_BOX_RIGHT_X - DEFAULT_BOX_LEFT_X;
+const REMARKABLE_POINT_SPAN: f64 = 0.01;
+const REMARKABLE_POINT_ITERATIONS: usize = 500;
+// Parameters, also the message keys of their labels
+const PARAM_ZOOM: &str = "mandelbrot.zoom";
+const PARAM_DWELL: &str = "mandelbrot.dwell";
+// The zoom goes from the whole set to this factor, before the f64 precision runs out
+const MAX_ZOOM: f32 = 1e13;
+// Range of the max iterations of the dwell
+const DWELL_RANGE: (f32, f32) = (16.0, 65536.0);
 
 // Algorithm constants
 const EPSILON: f64 = 1e-17;
@@ -48,19 +75,20 @@ const KNOW_CIRCLES: [KnownCircle; 3] = [
     KnownCircle::new(DVec2::new(-0.125, -0.744), 0.092 * 0.092, 2.),
     KnownCircle::new(DVec2::new(-1.308, 0.0), 0.058 * 0.058, 2.),
 ];
-const REMARKABLE_POINTS: [(DVec2, &str); 7] = [
-    (DVec2::new(DEFAULT_BOX_LEFT_X + DEFAULT_SPAN / 2., 0.), "Défaut"),
-    (DVec2::new(-1.401155, 0.), "Feigenbaum"),
-    (DVec2::new(-0.743643887037151, 0.13182590420533), "Vallée hippocampes"),
-    (DVec2::new(-1.749214022, -0.000289489), "Mini mandelbrot à gauche"),
-    (DVec2::new(-0.1649200283, -1.0369146835), "En haut, mini Julia"),
-    (DVec2::new(-1.4838688322327218, 0.0000000000000003), "Ligne à gauche"),
-    (DVec2::new(0.3621185521154, -0.4261009708377), "Cheveux frisés en haut à droite"),
+// With the message keys of their labels
+pub const REMARKABLE_POINTS: [(DVec2, &str); 7] = [
+    (DVec2::new(DEFAULT_BOX_LEFT_X + DEFAULT_SPAN / 2., 0.), "mandelbrot.point.default"),
+    (DVec2::new(-1.401155, 0.), "mandelbrot.point.feigenbaum"),
+    (DVec2::new(-0.743643887037151, 0.13182590420533), "mandelbrot.point.seahorse_valley"),
+    (DVec2::new(-1.749214022, -0.000289489), "mandelbrot.point.left_mini"),
+    (DVec2::new(-0.1649200283, -1.0369146835), "mandelbrot.point.top_julia"),
+    (DVec2::new(-1.4838688322327218, 0.0000000000000003), "mandelbrot.point.left_line"),
+    (DVec2::new(0.3621185521154, -0.4261009708377), "mandelbrot.point.curly_hair"),
 ];
-struct IterationResult {
-    iterations: usize,
-    smooth: f64,
-    computed: usize,
+pub struct IterationResult {
+    pub iterations: usize, // max_iterations when the point is considered part of the set
+    pub smooth: f64, // Fractional part of the escape, for smooth coloring
+    pub computed: usize, // Iterations actually computed, 0 when a shortcut was found
 }
 
 struct KnownCircle {
@@ -78,7 +106,7 @@ impl KnownCircle {
         }
     }
 
-    fn is_part(self: &Self, c: DVec2) -> bool {
+    fn is_part(&self, c: DVec2) -> bool {
         let dx = c.x - self.center.x;
         let dy = c.y - self.center.y;
 
@@ -116,7 +144,7 @@ impl ViewBox {
     fn from_center_size(screen_center: Vec2, screen_size: Vec2, box_center: DVec2, box_size: DVec2) -> Self {
         let screen_size_i = screen_size.round().as_ivec2();
 
-        return Self {
+        Self {
             screen_min_i: (screen_center - screen_size / 2.).round().as_ivec2(),
             screen_center,
             screen_size_i,
@@ -127,7 +155,7 @@ impl ViewBox {
         }
     }
 
-    fn mandel_point_from_index(self: &Self, pixel_index: usize) -> DVec2 {
+    fn mandel_point_from_index(&self, pixel_index: usize) -> DVec2 {
         let screen_shift_x = (pixel_index as i32) % self.screen_size_i.x;
         let screen_shift_y = (pixel_index as i32) / self.screen_size_i.x;
 
@@ -137,14 +165,14 @@ impl ViewBox {
         )
     }
 
-    fn mandel_point(self: &Self, screen_pixel_x: i32, screen_pixel_y: i32) -> DVec2 {
+    fn mandel_point(&self, screen_pixel_x: i32, screen_pixel_y: i32) -> DVec2 {
         DVec2::new(
             ((screen_pixel_x as f64) - (self.screen_center.x as f64)) * self.box_screen_ratio + self.box_center.x,
             ((screen_pixel_y as f64) - (self.screen_center.y as f64)) * self.box_screen_ratio + self.box_center.y,
         )
     }
 
-    fn screen_pixel(self: &Self, mandel_point: &DVec2) -> Vec2 {
+    fn screen_pixel(&self, mandel_point: &DVec2) -> Vec2 {
         // For displaying circles
         Vec2::new(
             ((mandel_point.x - self.box_center.x) / self.box_screen_ratio) as f32 + self.screen_center.x,
@@ -152,12 +180,17 @@ impl ViewBox {
         )
     }
 
-    fn screen_pixel_index(self: &Self, screen_x: f32, screen_y: f32) -> usize {
-        // For displaying selected point info
-        ((screen_x - self.screen_min_i.x as f32) + (screen_y - self.screen_min_i.x as f32) * (self.screen_size_i.x as f32)).round() as usize
+    #[cfg(feature = "gui")]
+    fn screen_pixel_index(&self, screen_x: f32, screen_y: f32) -> Option<usize> {
+        // For displaying selected point info. None out of the view box, e.g. on another tile.
+        let pixel = Vec2::new(screen_x, screen_y).round().as_ivec2() - self.screen_min_i;
+        if pixel.x < 0 || pixel.y < 0 || pixel.x >= self.screen_size_i.x || pixel.y >= self.screen_size_i.y {
+            return None;
+        }
+        Some((pixel.x + pixel.y * self.screen_size_i.x) as usize)
     }
 
-    fn size_changed(self: &Self, other: &ViewBox) -> bool {
+    fn size_changed(&self, other: &ViewBox) -> bool {
         self.screen_size_i != other.screen_size_i
     }
 }
@@ -171,7 +204,7 @@ struct ParIterResult {
 
 impl ParIterResult {
     fn new(max_iterations: usize) -> Self {
-        return ParIterResult {
+        ParIterResult {
             histogram: vec![0usize; max_iterations],
             min_smooth: 100.,
             max_smooth: 0.,
@@ -209,20 +242,24 @@ impl ParIterResult {
     }
 }
 
-struct MandelIterator {
+pub struct MandelIterator {
     max_iterations: usize,
     escape_radius2: f64,
 }
 
 impl MandelIterator {
-    fn new(max_iterations: usize, escape_radius2: f64) -> MandelIterator {
+    pub fn new(max_iterations: usize, escape_radius2: f64) -> MandelIterator {
         MandelIterator {
             max_iterations,
             escape_radius2,
         }
     }
 
-    fn iter_to_divergence(&self, c: DVec2) -> IterationResult {
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    pub fn iter_to_divergence(&self, c: DVec2) -> IterationResult {
         // https://en.wikibooks.org/wiki/Fractals/Iterations_in_the_complex_plane/Mandelbrot_set/mandelbrot
 
         if KnownCircle::is_known_member(c) {
@@ -302,7 +339,8 @@ impl MandelIterator {
         }
     }
 
-    fn iteration_points(&self, c: DVec2) -> Vec<DVec2> {
+    // The orbit of c, until it escapes or max_iterations is reached
+    pub fn iteration_points(&self, c: DVec2) -> Vec<DVec2> {
         let mut res = vec![c];
         let mut y = c.y;
         let mut y2 = y * y;
@@ -325,7 +363,7 @@ impl MandelIterator {
         res
     }
 
-    fn is_part_of_bulb_or_main_cardioid(&self, c: DVec2, y2: f64) -> bool {
+    pub fn is_part_of_bulb_or_main_cardioid(&self, c: DVec2, y2: f64) -> bool {
         // https://en.wikibooks.org/wiki/Fractals/Iterations_in_the_complex_plane/Mandelbrot_set/mandelbrot
         let x_plus_1 = c.x + 1.;
 
@@ -342,19 +380,49 @@ impl MandelIterator {
 }
 
 #[derive(Clone, Copy)]
-struct PointDetail(DVec2, usize);
+struct PointDetail(DVec2, #[cfg(feature = "gui")] usize);
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MandelbrotParams {
+    pub box_center: DVec2, // Center of the view in the complex plane
+    pub box_size: DVec2, // Size of the view in the complex plane
+    pub max_iterations: usize,
+}
+
+impl MandelbrotParams {
+    pub fn iterator(&self) -> MandelIterator {
+        MandelIterator::new(self.max_iterations, ESCAPE_RADIUS * ESCAPE_RADIUS)
+    }
+}
+
+impl Default for MandelbrotParams {
+    fn default() -> Self {
+        Self {
+            box_center: REMARKABLE_POINTS[0].0,
+            box_size: DVec2::new(DEFAULT_SPAN, DEFAULT_SPAN),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+}
+
+// What is saved for undo and bookmarks
+#[derive(Serialize, Deserialize)]
+struct MandelbrotState {
+    params: MandelbrotParams,
+    out_color: HSV,
+    almost_in_color: HSV,
+}
 
 pub struct MandelbrotSet {
     iteration_rate: f32,
     compute_time_ms: [u128; 5],
     pixels: Vec<u8>,
-    img: Option<GImage>,
+    image_id: u64,
+    image_size: (u32, u32),
     iteration_counts: Vec<f32>,
     histogram: Vec<usize>,
-    box_center: DVec2,
-    box_size: DVec2,
-    max_iterations: usize,
-    last_colors: [Color; 2],
+    params: MandelbrotParams,
+    last_colors: [Rgba; 2],
     last_max_iterations: usize,
     last_view_box: ViewBox,
     out_color_picker: ColorPicker,
@@ -364,24 +432,30 @@ pub struct MandelbrotSet {
     show_histogram: bool,
     drag_translation: Vec2,
     show_point_details: Option<PointDetail>,
+    #[cfg(feature = "gui")]
     selected_remarkable_point: usize,
 }
 
 impl MandelbrotSet {
+    pub fn register(registry: &mut CurveRegistry) {
+        registry.register(NAME, CATEGORY, || Box::new(Self::new()));
+    }
+
     pub fn new() -> Self {
-        let max_iterations = DEFAULT_MAX_ITERATIONS;
-        let default_location = REMARKABLE_POINTS[0].0;
+        Self::with_params(MandelbrotParams::default())
+    }
+
+    pub fn with_params(params: MandelbrotParams) -> Self {
         Self {
             iteration_rate: 0.,
             compute_time_ms: [0; 5],
             pixels: vec![],
-            img: None,
+            image_id: 0,
+            image_size: (0, 0),
             iteration_counts: vec![],
-            histogram: vec![0usize; max_iterations],
-            box_center: default_location,
-            box_size: DVec2::new(DEFAULT_SPAN, DEFAULT_SPAN),
-            max_iterations,
-            last_colors: [Color::BLACK; 2],
+            histogram: vec![0usize; params.max_iterations],
+            params,
+            last_colors: [Rgba::BLACK; 2],
             last_max_iterations: 0,
             last_view_box: ViewBox::zero(),
             out_color_picker: ColorPicker::new("Out", HSV::new(236., 0.96, 0.94), 1./2., Vec2::new(-1./5., 0.)),
@@ -391,22 +465,33 @@ impl MandelbrotSet {
             show_histogram: false,
             drag_translation: Vec2::ZERO,
             show_point_details: None,
+            #[cfg(feature = "gui")]
             selected_remarkable_point: 0,
         }
     }
 
+    pub fn params(&self) -> &MandelbrotParams {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut MandelbrotParams {
+        &mut self.params
+    }
+
+    #[cfg(feature = "gui")]
     fn reset_to_remarkable_point(&mut self) {
-        self.box_center = REMARKABLE_POINTS[self.selected_remarkable_point].0;
-        self.box_size = DVec2::new(DEFAULT_SPAN, DEFAULT_SPAN);
-        self.max_iterations = 100;
+        self.params.box_center = REMARKABLE_POINTS[self.selected_remarkable_point].0;
+        self.params.box_size = DVec2::new(DEFAULT_SPAN, DEFAULT_SPAN);
+        self.params.max_iterations = 100;
     }
 
     fn iterator(&self) -> MandelIterator {
-        MandelIterator::new(self.max_iterations, self.escape_radius2)
+        MandelIterator::new(self.params.max_iterations, self.escape_radius2)
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_zoom(&mut self, dir: i8) {
-        self.box_size *= 1. + (dir as f64) * ZOOM_STEP_PCT;
+        self.params.box_size *= 1. + (dir as f64) * ZOOM_STEP_PCT;
         self.show_point_details = None;
     }
 
@@ -433,7 +518,7 @@ impl MandelbrotSet {
     fn record_last_values(&mut self, view_box: ViewBox) {
         self.last_colors[0] = self.out_color_picker.color();
         self.last_colors[1] = self.almost_in_color_picker.color();
-        self.last_max_iterations = self.max_iterations;
+        self.last_max_iterations = self.params.max_iterations;
         self.last_view_box = view_box;
     }
 
@@ -443,9 +528,9 @@ impl MandelbrotSet {
 
     fn need_recompute_iterations(&self, view_box: &ViewBox) -> bool {
         self.need_recreate_pixel_cache(view_box) ||
-        self.last_view_box.box_center != self.box_center ||
-        self.last_view_box.box_size != self.box_size ||
-        self.last_max_iterations != self.max_iterations
+        self.last_view_box.box_center != self.params.box_center ||
+        self.last_view_box.box_size != self.params.box_size ||
+        self.last_max_iterations != self.params.max_iterations
     }
 
     fn need_recompute_image(&self, view_box: &ViewBox) -> bool {
@@ -453,101 +538,103 @@ impl MandelbrotSet {
         self.color_changed()
     }
 
-    fn draw_histogram(&self, dest: Vec2, size: Vec2) -> GameResult<DrawData> {
+    fn draw_histogram(&self, dest: Vec2, size: Vec2) -> DrawData<'_> {
         let palette_size: f32 = 20.;
         let histogram_size: f32 = 200.;
-        let mut builder = MeshBuilder::new();
-        let step = size.x / (self.max_iterations as f32) ;
-        let mut prev_point = Vec2::new(step / 2., 0.);
+        let mut points = vec![];
+        let step = size.x / (self.params.max_iterations as f32) ;
+        points.push(Vec2::new(step / 2., 0.));
         let hist_max = self.histogram.iter().fold(0, |a, b| if a > *b { a } else { *b }) as f32;
         for i in 1..self.histogram.len() {
             let new_point = Vec2::new(
                 (i as f32)*step + step / 2.,
                 palette_size + (self.histogram[i] as f32) * histogram_size / hist_max
             );
-            let panic_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-                builder.line(&[prev_point, new_point], 1., Color::RED).unwrap();
-            }));
-            if let Err(err) = panic_result {
-                println!("Panic line: ({} {}), ({} {})", prev_point.x, prev_point.y, new_point.x, new_point.y);
-                panic::resume_unwind(err);
-            }
-            prev_point = new_point;
+            points.push(new_point);
         }
 
-        Ok(Meshes(builder, DrawParam::new().dest(dest - size / 2.)))
+        let mut builder = DrawData::new().dest(dest - size / 2.);
+        builder.line(&points, 1., Rgba::RED);
+        builder
     }
 
-    fn draw_center_target(&self, dest: Vec2) -> GameResult<DrawData> {
-        let mut builder = MeshBuilder::new();
-        builder.line(&[Vec2::new(-TARGET_SIZE, 0.), Vec2::new(TARGET_SIZE, 0.)], 1., Color::RED)?;
-        builder.line(&[Vec2::new(0., -TARGET_SIZE), Vec2::new(0., TARGET_SIZE)], 1., Color::RED)?;
-        builder.circle(DrawMode::stroke(1.), Vec2::ZERO, TARGET_SIZE, 1., Color::WHITE)?;
+    fn draw_center_target(&self, dest: Vec2) -> DrawData<'_> {
+        let mut builder = DrawData::new().dest(dest);
+        builder.line(&[Vec2::new(-TARGET_SIZE, 0.), Vec2::new(TARGET_SIZE, 0.)], 1., Rgba::RED);
+        builder.line(&[Vec2::new(0., -TARGET_SIZE), Vec2::new(0., TARGET_SIZE)], 1., Rgba::RED);
+        builder.circle(Vec2::ZERO, TARGET_SIZE, Some(1.), Rgba::WHITE);
 
-        Ok(Meshes(builder, DrawParam::new().dest(dest)))
+        builder
     }
 
-    fn draw_point_details(&self, view_box: &ViewBox) -> GameResult<DrawData> {
-        let mut builder = MeshBuilder::new();
+    fn draw_point_details(&self, view_box: &ViewBox) -> DrawData<'_> {
+        let mut builder = DrawData::new();
         let iterator = MandelIterator::new(100, self.escape_radius2);
-        let c = match self.show_point_details { Some(PointDetail(p, _)) => p, _ => DVec2::ZERO };
+        let c = match self.show_point_details { Some(PointDetail(p, ..)) => p, _ => DVec2::ZERO };
         let points: Vec<Vec2> = iterator
             .iteration_points(c)
             .iter()
             .map(|p| view_box.screen_pixel(p))
             .collect();
         if points.len() > 1 {
-            builder.line(&points, 2., Color::RED)?;
+            builder.line(&points, 2., Rgba::RED);
         }
-        builder.circle(DrawMode::fill(), view_box.screen_pixel(&c), 5., 1., Color::RED)?;
+        builder.circle(view_box.screen_pixel(&c), 5., None, Rgba::RED);
+
+        builder
+    }
+}
 
-        Ok(Meshes(builder, DrawParam::new()))
+impl Default for MandelbrotSet {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Display for MandelbrotSet {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Ensemble de Mandelbrot")
+        write!(f, "{}", tr(NAME))
     }
 }
 
 impl InteractiveCurve for MandelbrotSet {
+    #[cfg(feature = "gui")]
     fn update_ui(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
-            ui.label("Dwell:");
+            ui.label(tr("mandelbrot.dwell"));
             if ui.button("-").clicked() {
-                self.max_iterations /= 2;
+                self.params.max_iterations /= 2;
             }
-            ui.label(format!("{}", self.max_iterations));
+            ui.label(format!("{}", self.params.max_iterations));
             if ui.button("+").clicked() {
-                self.max_iterations *= 2;
+                self.params.max_iterations *= 2;
             }
         });
-        ui.checkbox(&mut self.show_histogram, "Histogramme");
-        if ui.button(RichText::new("Couleur 1").background_color(self.out_color_picker.color32())).clicked() {
+        ui.checkbox(&mut self.show_histogram, tr("mandelbrot.histogram"));
+        if ui.button(RichText::new(tr("mandelbrot.color_1")).background_color(self.out_color_picker.color32())).clicked() {
             self.displayed_param = match self.displayed_param {
                 OUT_COLOR_PARAM => MAX_ITERATIONS_PARAM,
                 _ => OUT_COLOR_PARAM
             };
         }
-        if ui.button(RichText::new("Couleur 2").background_color(self.almost_in_color_picker.color32())).clicked() {
+        if ui.button(RichText::new(tr("mandelbrot.color_2")).background_color(self.almost_in_color_picker.color32())).clicked() {
             self.displayed_param = match self.displayed_param {
                 ALMOST_IN_COLOR_PARAM => MAX_ITERATIONS_PARAM,
                 _ => ALMOST_IN_COLOR_PARAM
             };
         }
         ui.horizontal(|ui| {
-            ui.label("Départ:");
+            ui.label(tr("mandelbrot.start"));
             egui::ComboBox::from_id_source("remarkable_points")
-                .selected_text(format!("{}", REMARKABLE_POINTS[self.selected_remarkable_point].1))
+                .selected_text(tr(REMARKABLE_POINTS[self.selected_remarkable_point].1))
                 .show_ui(ui, |ui| {
                     ui.style_mut().wrap = Some(false);
                     ui.set_min_width(60.0);
                     REMARKABLE_POINTS.iter().enumerate().for_each(|(i, (_, pt_name))| {
-                        ui.selectable_value(&mut self.selected_remarkable_point, i, *pt_name);
+                        ui.selectable_value(&mut self.selected_remarkable_point, i, tr(pt_name));
                     });
                 });
-            if ui.button("Aller").clicked() {
+            if ui.button(tr("mandelbrot.go")).clicked() {
                 self.reset_to_remarkable_point();
             }
         });
@@ -557,39 +644,31 @@ impl InteractiveCurve for MandelbrotSet {
             .spacing([40.0, 4.0])
             .striped(true)
             .show(ui, |ui| {
-                ui.label("Position:");
-                ui.label(format!("{:.5} , {:.5}", self.box_center.x, self.box_center.y));
+                ui.label(tr("mandelbrot.position"));
+                ui.label(format!("{:.5} , {:.5}", self.params.box_center.x, self.params.box_center.y));
                 ui.end_row();
-                ui.label("Zoom:");
-                ui.label(format!("{:.2e}", DEFAULT_SPAN / self.box_size.x));
+                ui.label(tr("mandelbrot.zoom"));
+                ui.label(format!("{:.2e}", DEFAULT_SPAN / self.params.box_size.x));
                 ui.end_row();
-                ui.label("Temps calcul:");
+                ui.label(tr("mandelbrot.compute_time"));
                 ui.label(format!("{} ms", self.compute_time_ms[0]));
                 ui.end_row();
-                ui.label("Points calculés:");
+                ui.label(tr("mandelbrot.computed_points"));
                 ui.label(format!("{:.1} %", self.iteration_rate * 100.));
                 ui.end_row();
 
-                match self.show_point_details {
-                    Some(pt) => {
-                        ui.label("Itérations au point:");
-                        ui.label(format!("{}",self.iteration_counts[pt.1]));
-                        ui.end_row();
-                    },
-                    _ => ()
+                // The counts of the point are gone after a resize
+                if let Some(count) = self.show_point_details.and_then(|pt| self.iteration_counts.get(pt.1)) {
+                    ui.label(tr("mandelbrot.point_iterations"));
+                    ui.label(format!("{}", count));
+                    ui.end_row();
                 }
             });
-        ui.separator();
-        ui.label("[clic droit]: calcul au point");
-        ui.label("[Z]: zoomer");
-        ui.label("[X]: dézoomer");
-        ui.label("[R]: zoom à 0");
-        ui.label("[H]: histogramme");
     }
 
-    fn compute_drawables(&mut self, ctx: &mut Context, dest: Vec2, size: Vec2) -> GameResult<Vec<DrawData>> {
+    fn compute_drawables(&mut self, dest: Vec2, size: Vec2) -> Vec<DrawData<'_>> {
         let start = Instant::now();
-        let view_box = ViewBox::from_center_size(dest, size, self.box_center, self.box_size);
+        let view_box = ViewBox::from_center_size(dest, size, self.params.box_center, self.params.box_size);
 
         if self.need_recreate_pixel_cache(&view_box) {
             self.pixels = vec![255u8; 4 * view_box.pixel_count];
@@ -617,14 +696,14 @@ impl InteractiveCurve for MandelbrotSet {
                 );
 
             //println!("Smooth: [{}, {}]", final_acc.min_smooth, final_acc.max_smooth);
-            self.iteration_rate = (final_acc.computed_iteration_count as f32) / ((self.max_iterations as f32) * size.x * size.y);
+            self.iteration_rate = (final_acc.computed_iteration_count as f32) / ((self.params.max_iterations as f32) * size.x * size.y);
             self.histogram = final_acc.histogram;
             self.compute_time_ms[1] = start.elapsed().as_millis();
         }
 
         if self.need_recompute_image(&view_box) {
             let fill_start = Instant::now();
-            let colors = [self.out_color_picker.color(), Color::WHITE, self.almost_in_color_picker.color(), DARK_GREY, self.out_color_picker.color()];
+            let colors = [self.out_color_picker.color(), Rgba::WHITE, self.almost_in_color_picker.color(), DARK_GREY, self.out_color_picker.color()];
 
             self.pixels
                 .par_iter_mut()
@@ -633,9 +712,9 @@ impl InteractiveCurve for MandelbrotSet {
                 .for_each(|(px_index, mut pixel_slice)| {
                     let iteration_count = self.iteration_counts[px_index];
 
-                    let color: (u8,u8,u8) =
-                        if iteration_count >= (self.max_iterations as f32) {
-                            Color::BLACK.to_rgb()
+                    let color: [u8; 4] =
+                        if iteration_count >= (self.params.max_iterations as f32) {
+                            Rgba::BLACK.to_rgba8()
                         } else {
                             let n_colors = colors.len() as f32 - 1.;
                             let interpolation = (iteration_count / 25.) % n_colors;
@@ -646,126 +725,341 @@ impl InteractiveCurve for MandelbrotSet {
                             // Adjust the subinterpolation so as to decrease emphasis on black and white
                             let sub_interpolation = f32::sin(interpolation * 2. * std::f32::consts::PI / n_colors - std::f32::consts::PI / 2.);
                             let sign: f32 = if color1_index < 2 { 1. } else { -1. };
-                            let adder: f32 = if color1_index % 2 == 0 { 1. } else { 0. };
+                            let adder: f32 = if color1_index.is_multiple_of(2) { 1. } else { 0. };
                             utils::interpolate_color(
                                 color1,
                                 color2,
                                 adder + sign * sub_interpolation,
-                            ).to_rgb()
+                            ).to_rgba8()
                         };
 
-                    *pixel_slice[0] = color.2;
-                    *pixel_slice[1] = color.1;
-                    *pixel_slice[2] = color.0;
+                    *pixel_slice[0] = color[0];
+                    *pixel_slice[1] = color[1];
+                    *pixel_slice[2] = color[2];
                 });
             self.compute_time_ms[2] = fill_start.elapsed().as_millis();
 
-            self.img = Some(GImage::from_pixels(
-                ctx,
-                &self.pixels,
-                ImageFormat::Bgra8Unorm,
-                view_box.screen_size_i.x as u32,
-                view_box.screen_size_i.y as u32
-            ));
+            self.image_id = render::next_raster_id();
+            self.image_size = (view_box.screen_size_i.x as u32, view_box.screen_size_i.y as u32);
         }
 
         if self.need_recompute_iterations(&view_box) {
             self.compute_time_ms[0] = start.elapsed().as_millis();
         }
 
-        match self.displayed_color_picker_mut() {
-            Some(picker) => picker.set_view(size, dest),
-            None => ()
+        if let Some(picker) = self.displayed_color_picker_mut() {
+            picker.set_view(size, dest)
         }
 
         self.record_last_values(view_box.clone());
 
         let mut result : Vec<DrawData> = vec!();
 
-        match self.show_point_details {
-            Some(_) => result.push(self.draw_point_details(&view_box)?),
-            _ => ()
+        if self.show_point_details.is_some() {
+            result.push(self.draw_point_details(&view_box))
         }
 
-        match self.displayed_color_picker() {
-            Some(picker) => result.push(picker.meshes()?),
-            None => ()
+        if let Some(picker) = self.displayed_color_picker() {
+            result.push(picker.meshes())
         }
 
         if self.show_histogram {
-            result.push(self.draw_histogram(dest, size)?);
+            result.push(self.draw_histogram(dest, size));
         }
 
         if self.drag_translation != Vec2::ZERO {
-            result.push(self.draw_center_target(dest)?);
+            result.push(self.draw_center_target(dest));
         }
 
-        result.push(Image(self.img.as_ref().unwrap(), DrawParam::new().z(-1).dest((dest - size / 2.) + self.drag_translation)));
+        let (width, height) = self.image_size;
+        result.push(
+            DrawData::image(&self.pixels, width, height, self.image_id)
+                .z(-1)
+                .dest((dest - size / 2.) + self.drag_translation)
+        );
 
-        Ok(result)
+        result
     }
 
-    fn adjust_for_mouse_button_up(self: &mut Self, button: MouseButton, x: f32, y: f32, drag_start: Vec2) {
+    #[cfg(feature = "gui")]
+    fn adjust_for_mouse_button_up(&mut self, button: MouseButton, x: f32, y: f32, drag_start: Vec2) {
         self.drag_translation = Vec2::ZERO;
         match self.displayed_color_picker_mut() {
             Some(picker) => picker.adjust_for_click(button, x, y),
             None if button == MouseButton::Left => {
-                self.box_center += DVec2::new(
+                self.params.box_center += DVec2::new(
                     ((drag_start.x - x) as f64) * self.last_view_box.box_screen_ratio,
                     ((drag_start.y - y) as f64) * self.last_view_box.box_screen_ratio,
                 );
                 self.show_point_details = None;
             },
-            None if button == MouseButton::Right => {
-                self.show_point_details = Some(
-                    PointDetail(
-                        self.last_view_box.mandel_point(x as i32, y as i32),
-                        self.last_view_box.screen_pixel_index(x, y),
-                    )
-                )
-            },
             _ => ()
         }
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_for_mouse_drag(&mut self, x: f32, y: f32, drag_start: Vec2) {
         self.drag_translation = Vec2::new(x, y) - drag_start;
         self.show_point_details = None;
     }
 
+    #[cfg(feature = "gui")]
     fn adjust_for_mouse_wheel(&mut self, _x: f32, _y: f32, wheel_y_dir: f32) {
         if f32::abs(wheel_y_dir) >= 0.5 {
             self.adjust_zoom((-wheel_y_dir / wheel_y_dir) as i8);
         }
     }
 
-    fn adjust_for_key_up(&mut self, input: KeyInput) {
-        match input.keycode {
-            Some(KeyCode::Escape) => self.displayed_param = MAX_ITERATIONS_PARAM,
-            Some(KeyCode::R) => self.reset_to_remarkable_point(),
-            Some(KeyCode::H) => self.show_histogram = !self.show_histogram,
-            Some(KeyCode::Z) => self.adjust_zoom(-1),
-            Some(KeyCode::X) => self.adjust_zoom(1),
+    fn actions(&self) -> Vec<ActionSpec> {
+        [ACTIONS.as_slice(), &color_picker::ACTIONS].concat()
+    }
+
+    // Both on a log scale. The zoom keeps the center and the proportions of the view.
+    fn param_specs(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new(PARAM_ZOOM, 1.0, MAX_ZOOM).log().unit("x"),
+            ParamSpec::new(PARAM_DWELL, DWELL_RANGE.0, DWELL_RANGE.1).log().step(1.0),
+        ]
+    }
+
+    fn param(&self, id: &str) -> Option<f32> {
+        match id {
+            PARAM_ZOOM => Some((DEFAULT_SPAN / self.params.box_size.x) as f32),
+            PARAM_DWELL => Some(self.params.max_iterations as f32),
+            _ => None
+        }
+    }
+
+    fn set_param(&mut self, id: &str, value: f32) {
+        match id {
+            PARAM_ZOOM => {
+                let span = DEFAULT_SPAN / value.max(1.0) as f64;
+                self.params.box_size *= span / self.params.box_size.x;
+            },
+            PARAM_DWELL => self.params.max_iterations = value.round().max(1.0) as usize,
             _ => ()
         }
+    }
 
-        match self.displayed_color_picker_mut() {
-            Some(picker) => picker.adjust_for_key(input),
-            None => (),
+    #[cfg(feature = "gui")]
+    fn on_trigger(&mut self, action: &str, pointer: Vec2) {
+        match action {
+            ACTION_CLOSE_PICKER => self.displayed_param = MAX_ITERATIONS_PARAM,
+            ACTION_RESET => self.reset_to_remarkable_point(),
+            ACTION_HISTOGRAM => self.show_histogram = !self.show_histogram,
+            ACTION_ZOOM_IN => self.adjust_zoom(-1),
+            ACTION_ZOOM_OUT => self.adjust_zoom(1),
+            ACTION_POINT_DETAILS if self.displayed_color_picker().is_none() => {
+                if let Some(index) = self.last_view_box.screen_pixel_index(pointer.x, pointer.y) {
+                    self.show_point_details = Some(
+                        PointDetail(self.last_view_box.mandel_point(pointer.x as i32, pointer.y as i32), index)
+                    )
+                }
+            },
+            _ => ()
+        }
+
+        if let Some(picker) = self.displayed_color_picker_mut() {
+            picker.on_trigger(action)
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    fn on_axis(&mut self, action: &str, value: f32) {
+        if let Some(picker) = self.displayed_color_picker_mut() {
+            picker.on_axis(action, value)
         }
     }
 
+    #[cfg(feature = "gui")]
+    fn on_axis_relative(&mut self, action: &str, delta: f32) {
+        if let Some(picker) = self.displayed_color_picker_mut() {
+            picker.on_axis_relative(action, delta)
+        }
+    }
+
+    fn save_state(&self) -> Value {
+        serde_json::to_value(MandelbrotState {
+            params: self.params,
+            out_color: self.out_color_picker.hsv(),
+            almost_in_color: self.almost_in_color_picker.hsv(),
+        }).unwrap()
+    }
+
+    fn restore_state(&mut self, state: &Value) -> Result<(), String> {
+        let state: MandelbrotState = serde_json::from_value(state.clone()).map_err(|err| err.to_string())?;
+        self.params = state.params;
+        self.out_color_picker.set_hsv(state.out_color);
+        self.almost_in_color_picker.set_hsv(state.almost_in_color);
+        self.show_point_details = None;
+        Ok(())
+    }
+
+    fn default_bookmarks(&self) -> Vec<(String, Value)> {
+        REMARKABLE_POINTS
+            .iter()
+            .enumerate()
+            .map(|(i, (center, label))| {
+                // The first point is the whole set
+                let (span, max_iterations) = if i == 0 {
+                    (DEFAULT_SPAN, DEFAULT_MAX_ITERATIONS)
+                } else {
+                    (REMARKABLE_POINT_SPAN, REMARKABLE_POINT_ITERATIONS)
+                };
+                let state = MandelbrotState {
+                    params: MandelbrotParams {
+                        box_center: *center,
+                        box_size: DVec2::new(span, span),
+                        max_iterations,
+                    },
+                    out_color: self.out_color_picker.hsv(),
+                    almost_in_color: self.almost_in_color_picker.hsv(),
+                };
+                (label.to_string(), serde_json::to_value(state).unwrap())
+            })
+            .collect()
+    }
+
     fn screenshot_file_name(&self) -> String {
         format!(
-            "mandel_{}", self.max_iterations
+            "mandel_{}", self.params.max_iterations
         )
     }
 
     fn name(&self) -> &str {
-        "Ensemble Mandelbrot"
+        NAME
     }
 
     fn inspiration_url(&self) -> &str {
         "https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ESCAPE_RADIUS2: f64 = ESCAPE_RADIUS * ESCAPE_RADIUS;
+
+    // Plain z = z² + c iterations, without any shortcut. Like MandelIterator, counts from z = c.
+    fn brute_force_iterations(c: DVec2, max_iterations: usize) -> usize {
+        let mut z = c;
+        for i in 0..max_iterations {
+            if z.length_squared() >= ESCAPE_RADIUS2 {
+                return i;
+            }
+            z = DVec2::new(z.x * z.x - z.y * z.y + c.x, 2. * z.x * z.y + c.y);
+        }
+        max_iterations
+    }
+
+    #[test]
+    fn points_inside_the_set_reach_max_iterations() {
+        let iterator = MandelIterator::new(500, ESCAPE_RADIUS2);
+        for c in [DVec2::ZERO, DVec2::new(-1., 0.), DVec2::new(-0.1, 0.1), DVec2::new(0.25, 0.), DVec2::new(-1.75, 0.)] {
+            assert_eq!(iterator.iter_to_divergence(c).iterations, 500, "{} should be in the set", c);
+        }
+    }
+
+    #[test]
+    fn points_outside_the_set_escape() {
+        let iterator = MandelIterator::new(500, ESCAPE_RADIUS2);
+        // Iterations are counted from z = c
+        assert_eq!(iterator.iter_to_divergence(DVec2::new(3., 0.)).iterations, 0);
+        assert_eq!(iterator.iter_to_divergence(DVec2::new(0., 2.)).iterations, 0);
+        assert_eq!(iterator.iter_to_divergence(DVec2::new(1., 0.)).iterations, 1);
+        assert_eq!(iterator.iter_to_divergence(DVec2::new(0.5, 0.5)).iterations, 4);
+        let result = iterator.iter_to_divergence(DVec2::new(0.26, 0.));
+        assert!(result.iterations > 10 && result.iterations < 500);
+        assert!((0.0..=1.0).contains(&result.smooth));
+    }
+
+    #[test]
+    fn cardioid_and_bulb_are_shortcut() {
+        let iterator = MandelIterator::new(1000, ESCAPE_RADIUS2);
+        for c in [DVec2::new(-0.5, 0.), DVec2::new(0.2, 0.2), DVec2::new(-1., 0.2), DVec2::new(-1.2, 0.)] {
+            assert!(iterator.is_part_of_bulb_or_main_cardioid(c, c.y * c.y), "{} should be shortcut", c);
+            let result = iterator.iter_to_divergence(c);
+            assert_eq!(result.iterations, 1000);
+            assert_eq!(result.computed, 0);
+        }
+        for c in [DVec2::new(0.3, 0.), DVec2::new(-1.3, 0.), DVec2::new(-0.75, 0.2)] {
+            assert!(!iterator.is_part_of_bulb_or_main_cardioid(c, c.y * c.y), "{} should not be shortcut", c);
+        }
+    }
+
+    #[test]
+    fn shortcuts_agree_with_brute_force() {
+        let max_iterations = 300;
+        let iterator = MandelIterator::new(max_iterations, ESCAPE_RADIUS2);
+        for xi in 0..120 {
+            for yi in 0..60 {
+                let c = DVec2::new(-2.2 + (xi as f64) * 0.025, (yi as f64) * 0.025);
+                assert_eq!(
+                    iterator.iter_to_divergence(c).iterations,
+                    brute_force_iterations(c, max_iterations),
+                    "mismatch at {}", c
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn periodic_orbits_are_detected() {
+        // Period 8 bulb and period 3 mini Mandelbrot, outside of the shortcuts: the cycle detection stops early
+        let iterator = MandelIterator::new(100_000, ESCAPE_RADIUS2);
+        for c in [DVec2::new(-1.385, 0.), DVec2::new(-1.755, 0.)] {
+            assert!(!KnownCircle::is_known_member(c));
+            assert!(!iterator.is_part_of_bulb_or_main_cardioid(c, c.y * c.y));
+            let result = iterator.iter_to_divergence(c);
+            assert_eq!(result.iterations, 100_000);
+            assert_eq!(result.iterations, brute_force_iterations(c, 100_000));
+            assert!(result.computed < 100_000);
+        }
+    }
+
+    fn view_box() -> ViewBox {
+        ViewBox::from_center_size(Vec2::new(300., 200.), Vec2::new(400., 300.), DVec2::new(-0.5, 0.1), DVec2::new(3., 3.))
+    }
+
+    #[test]
+    fn view_box_round_trips() {
+        let view_box = view_box();
+        for (x, y) in [(100, 50), (300, 200), (499, 349), (250, 120)] {
+            let mandel_point = view_box.mandel_point(x, y);
+            let screen_pixel = view_box.screen_pixel(&mandel_point);
+            assert!((screen_pixel.x - x as f32).abs() < 1e-3 && (screen_pixel.y - y as f32).abs() < 1e-3);
+        }
+        assert_eq!(view_box.mandel_point(300, 200), DVec2::new(-0.5, 0.1));
+        assert_eq!(view_box.screen_pixel(&DVec2::new(-0.5, 0.1)), Vec2::new(300., 200.));
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn view_box_pixel_index_round_trips() {
+        let view_box = view_box();
+        assert_eq!(view_box.pixel_count, 400 * 300);
+        assert_eq!(view_box.screen_pixel_index(100., 50.), Some(0));
+        for index in [0, 1, 399, 400, 401, 12_345, 400 * 300 - 1] {
+            let mandel_point = view_box.mandel_point_from_index(index);
+            let screen_pixel = view_box.screen_pixel(&mandel_point);
+            assert_eq!(view_box.screen_pixel_index(screen_pixel.x, screen_pixel.y), Some(index));
+        }
+        for (x, y) in [(99., 50.), (500., 50.), (100., 350.), (700., 600.)] {
+            assert_eq!(view_box.screen_pixel_index(x, y), None);
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn point_details_are_only_for_the_pointers_in_the_view() {
+        let mut curve = MandelbrotSet::new();
+        curve.compute_drawables(Vec2::ZERO, Vec2::new(40., 30.));
+        // On another tile
+        curve.on_trigger(ACTION_POINT_DETAILS, Vec2::new(300., 200.));
+        assert!(curve.show_point_details.is_none());
+        curve.on_trigger(ACTION_POINT_DETAILS, Vec2::new(10., 10.));
+        let index = curve.show_point_details.unwrap().1;
+        assert!(index < curve.iteration_counts.len());
+    }
+}
@@ -0,0 +1,279 @@
+#[cfg(feature = "gui")]
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "gui")]
+use crate::interactive_curve::InteractiveCurve;
+
+const CONTROL_CHANGE: u8 = 0xB0;
+
+// A knob or a fader of a MIDI controller: a control change number on a channel, from 1 to 16.
+// Written as text in the config file: "Ch 1 CC 74".
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MidiControl {
+    pub channel: u8,
+    pub controller: u8,
+}
+
+impl MidiControl {
+    // The control and its value, in [0, 127], when the bytes are a control change message
+    pub fn parse(message: &[u8]) -> Option<(MidiControl, u8)> {
+        match message {
+            [status, controller, value] if status & 0xF0 == CONTROL_CHANGE => Some((
+                MidiControl { channel: (status & 0x0F) + 1, controller: *controller },
+                *value,
+            )),
+            _ => None,
+        }
+    }
+
+    // Bytes of a control change message, e.g. for a loopback test
+    pub fn message(&self, value: u8) -> [u8; 3] {
+        [CONTROL_CHANGE | (self.channel - 1), self.controller, value]
+    }
+}
+
+// Controls drive axis actions, as a gamepad axis with its stick at this position
+#[cfg(feature = "gui")]
+pub fn axis_value(value: u8) -> f32 {
+    value.min(127) as f32 / 127.0 * 2.0 - 1.0
+}
+
+impl Display for MidiControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Ch {} CC {}", self.channel, self.controller)
+    }
+}
+
+impl FromStr for MidiControl {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid MIDI control '{}', expected e.g. 'Ch 1 CC 74'", text);
+        let (channel, controller) = text
+            .trim()
+            .strip_prefix("Ch ")
+            .and_then(|rest| rest.split_once(" CC "))
+            .ok_or_else(invalid)?;
+        let channel: u8 = channel.parse().map_err(|_| invalid())?;
+        let controller: u8 = controller.parse().map_err(|_| invalid())?;
+        if !(1..=16).contains(&channel) || controller > 127 {
+            return Err(invalid());
+        }
+        Ok(MidiControl { channel, controller })
+    }
+}
+
+impl TryFrom<String> for MidiControl {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<MidiControl> for String {
+    fn from(control: MidiControl) -> Self {
+        control.to_string()
+    }
+}
+
+// The axis action of each control, per curve registration name. A control drives one action of a curve.
+#[cfg(feature = "gui")]
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MidiMappings {
+    curves: BTreeMap<String, BTreeMap<MidiControl, String>>,
+}
+
+#[cfg(feature = "gui")]
+impl MidiMappings {
+    pub fn action(&self, curve: &str, control: &MidiControl) -> Option<&str> {
+        self.curves.get(curve)?.get(control).map(String::as_str)
+    }
+
+    pub fn controls(&self, curve: &str, action: &str) -> Vec<MidiControl> {
+        self.curves
+            .get(curve)
+            .map(|controls| controls.iter().filter(|(_, bound)| *bound == action).map(|(control, _)| *control).collect())
+            .unwrap_or_default()
+    }
+
+    // MIDI-learn: the control now drives this action, in place of the one it drove before
+    pub fn learn(&mut self, curve: &str, control: MidiControl, action: &str) {
+        self.curves.entry(curve.to_string()).or_default().insert(control, action.to_string());
+    }
+
+    pub fn forget(&mut self, curve: &str, action: &str) {
+        if let Some(controls) = self.curves.get_mut(curve) {
+            controls.retain(|_, bound| bound != action);
+            if controls.is_empty() {
+                self.curves.remove(curve);
+            }
+        }
+    }
+
+    // A control moved while this curve is displayed. The action waiting for MIDI-learn gets the control first,
+    // unless the curve changed while learning. The control then drives its action, always in absolute mode, since
+    // knobs and faders stay in place. True when the mappings changed.
+    pub fn handle(&mut self, curve_name: &str, curve: &mut dyn InteractiveCurve, learning: Option<&str>, control: MidiControl, value: u8) -> bool {
+        let learned = learning.filter(|action| curve.actions().iter().any(|known| known.id == *action));
+        if let Some(action) = learned {
+            self.learn(curve_name, control, action);
+        }
+        if let Some(action) = self.action(curve_name, &control) {
+            curve.on_axis(action, axis_value(value));
+        }
+        learned.is_some()
+    }
+}
+
+// Connections to the MIDI inputs. The messages arrive on the threads of the MIDI backend, and wait to be polled.
+pub struct MidiInputs {
+    #[cfg(feature = "midi")]
+    _connections: Vec<midir::MidiInputConnection<()>>,
+    #[cfg(feature = "midi")]
+    receiver: std::sync::mpsc::Receiver<(MidiControl, u8)>,
+    pub ports: Vec<String>,
+}
+
+#[cfg(feature = "midi")]
+impl MidiInputs {
+    // Connects to every input port. Where the platform allows it, also opens a virtual port with the client name,
+    // for the software that can only send to a port.
+    pub fn open(client_name: &str) -> Result<Self, String> {
+        use midir::{Ignore, MidiInput};
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let new_input = || -> Result<MidiInput, String> {
+            let mut input = MidiInput::new(client_name).map_err(|err| err.to_string())?;
+            input.ignore(Ignore::All);
+            Ok(input)
+        };
+        let callback = |sender: std::sync::mpsc::Sender<(MidiControl, u8)>| {
+            move |_: u64, message: &[u8], _: &mut ()| {
+                if let Some(control) = MidiControl::parse(message) {
+                    // The receiver is gone when the application closes
+                    let _ = sender.send(control);
+                }
+            }
+        };
+
+        let mut connections = vec![];
+        let mut ports = vec![];
+        let probe = new_input()?;
+        for port in probe.ports() {
+            let Ok(name) = probe.port_name(&port) else { continue };
+            // Our own virtual port
+            if name.contains(client_name) {
+                continue;
+            }
+            if let Ok(connection) = new_input()?.connect(&port, client_name, callback(sender.clone()), ()) {
+                connections.push(connection);
+                ports.push(name);
+            }
+        }
+        #[cfg(unix)]
+        {
+            use midir::os::unix::VirtualInput;
+            if let Ok(connection) = new_input()?.create_virtual(client_name, callback(sender.clone()), ()) {
+                connections.push(connection);
+                ports.push(client_name.to_string());
+            }
+        }
+        Ok(Self { _connections: connections, receiver, ports })
+    }
+
+    pub fn poll(&self) -> Vec<(MidiControl, u8)> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(not(feature = "midi"))]
+impl MidiInputs {
+    pub fn open(_client_name: &str) -> Result<Self, String> {
+        Err("Built without the midi feature".to_string())
+    }
+
+    pub fn poll(&self) -> Vec<(MidiControl, u8)> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_changes_are_parsed() {
+        let control = MidiControl { channel: 3, controller: 74 };
+        assert_eq!(MidiControl::parse(&control.message(100)), Some((control, 100)));
+        // Note on
+        assert_eq!(MidiControl::parse(&[0x92, 60, 100]), None);
+        assert_eq!(control.to_string(), "Ch 3 CC 74");
+        assert_eq!("Ch 3 CC 74".parse(), Ok(control));
+        assert!("Ch 17 CC 1".parse::<MidiControl>().is_err());
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn control_values_span_the_axis() {
+        assert_eq!(axis_value(0), -1.0);
+        assert_eq!(axis_value(127), 1.0);
+    }
+
+    #[test]
+    #[cfg(feature = "gui")]
+    fn mappings_are_per_curve() {
+        let mut mappings = MidiMappings::default();
+        let knob = MidiControl { channel: 1, controller: 1 };
+        mappings.learn("curve.dejong", knob, "action.dejong.a");
+        assert_eq!(mappings.action("curve.dejong", &knob), Some("action.dejong.a"));
+        assert_eq!(mappings.action("curve.harmonograph", &knob), None);
+
+        // Learning again moves the control
+        mappings.learn("curve.dejong", knob, "action.dejong.b");
+        assert!(mappings.controls("curve.dejong", "action.dejong.a").is_empty());
+        assert_eq!(mappings.controls("curve.dejong", "action.dejong.b"), [knob]);
+
+        let json = serde_json::to_string(&mappings).unwrap();
+        assert_eq!(json, r#"{"curve.dejong":{"Ch 1 CC 1":"action.dejong.b"}}"#);
+        assert_eq!(serde_json::from_str::<MidiMappings>(&json).unwrap(), mappings);
+
+        mappings.forget("curve.dejong", "action.dejong.b");
+        assert_eq!(mappings, MidiMappings::default());
+    }
+
+    #[test]
+    #[cfg(feature = "gui")]
+    fn learned_controls_drive_the_curve() {
+        use std::f32::consts::PI;
+        use crate::DeJongAttractor;
+
+        let mut mappings = MidiMappings::default();
+        let mut curve = DeJongAttractor::new();
+        let knob = MidiControl { channel: 1, controller: 74 };
+        let fader = MidiControl { channel: 1, controller: 7 };
+        let a = curve.param("action.dejong.a").unwrap();
+
+        // Not learned yet
+        assert!(!mappings.handle("curve.dejong", &mut curve, None, knob, 127));
+        assert_eq!(curve.param("action.dejong.a"), Some(a));
+
+        // An action of another curve, left from before a curve change
+        assert!(!mappings.handle("curve.dejong", &mut curve, Some("action.harmonograph.paper_x"), knob, 127));
+        assert_eq!(mappings, MidiMappings::default());
+
+        assert!(mappings.handle("curve.dejong", &mut curve, Some("action.dejong.a"), knob, 127));
+        assert_eq!(curve.param("action.dejong.a"), Some(PI));
+        assert!(!mappings.handle("curve.dejong", &mut curve, None, knob, 0));
+        assert_eq!(curve.param("action.dejong.a"), Some(-PI));
+
+        let b = curve.param("action.dejong.b");
+        assert!(!mappings.handle("curve.dejong", &mut curve, None, fader, 127));
+        assert_eq!(curve.param("action.dejong.a"), Some(-PI));
+        assert_eq!(curve.param("action.dejong.b"), b);
+    }
+}
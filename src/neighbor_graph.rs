@@ -0,0 +1,233 @@
+use std::collections::HashSet;
+use glam::{Vec2, Vec3};
+use rstar::primitives::GeomWithData;
+use rstar::RTree;
+use serde::{Deserialize, Serialize};
+use spade::{DelaunayTriangulation, HasPosition, Point2, Triangulation};
+
+// Ways of connecting a cloud of points with lines.
+// In every mode, edges longer than the max distance are dropped.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GraphMode {
+    AllWithin, // Every pair of points closer than the max distance: O(n²) edges when it is large
+    KNearest, // Each point to its k nearest neighbors
+    Delaunay, // Delaunay triangulation of the points, as displayed on screen
+    SpanningTree, // Euclidean minimum spanning tree
+}
+
+pub const GRAPH_MODES: [GraphMode; 4] = [GraphMode::AllWithin, GraphMode::KNearest, GraphMode::Delaunay, GraphMode::SpanningTree];
+
+impl GraphMode {
+    pub fn index(&self) -> usize {
+        GRAPH_MODES.iter().position(|mode| mode == self).unwrap()
+    }
+
+    // Message key, see i18n
+    pub fn name(&self) -> &'static str {
+        match self {
+            GraphMode::AllWithin => "graph.all_within",
+            GraphMode::KNearest => "graph.k_nearest",
+            GraphMode::Delaunay => "graph.delaunay",
+            GraphMode::SpanningTree => "graph.spanning_tree",
+        }
+    }
+}
+
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub distance: f32,
+}
+
+// Nearest neighbors of each point among which the spanning tree edges are searched
+const SPANNING_TREE_CANDIDATES: usize = 12;
+
+type IndexedPoint = GeomWithData<[f32; 3], usize>;
+
+struct ScreenPoint {
+    position: Point2<f32>,
+    index: usize,
+}
+
+impl HasPosition for ScreenPoint {
+    type Scalar = f32;
+
+    fn position(&self) -> Point2<f32> {
+        self.position
+    }
+}
+
+pub struct GraphParams {
+    pub mode: GraphMode,
+    pub k: usize,
+    pub max_distance: f32,
+    pub max_edges: usize,
+}
+
+impl GraphParams {
+    // Computes the edges of the graph between points (in curve space).
+    // screen_points are the same points once projected, used by the Delaunay mode.
+    // No more than max_edges edges are generated.
+    pub fn edges(&self, points: &[[f32; 3]], screen_points: &[Vec2]) -> Vec<Edge> {
+        match self.mode {
+            GraphMode::AllWithin => nearest_edges(points, None, self.max_distance, self.max_edges),
+            GraphMode::KNearest => nearest_edges(points, Some(self.k), self.max_distance, self.max_edges),
+            GraphMode::Delaunay => delaunay_edges(self, points, screen_points),
+            GraphMode::SpanningTree => spanning_tree_edges(self, points),
+        }
+    }
+}
+
+fn distance(p1: &[f32; 3], p2: &[f32; 3]) -> f32 {
+    Vec3::from_array(*p1).distance(Vec3::from_array(*p2))
+}
+
+// Without k, connects all the neighbors within the max distance
+fn nearest_edges(points: &[[f32; 3]], k: Option<usize>, max_distance: f32, max_edges: usize) -> Vec<Edge> {
+    let index = RTree::bulk_load(
+        points.iter().enumerate().map(|(i, pt)| IndexedPoint::new(*pt, i)).collect()
+    );
+    let max_distance2 = max_distance * max_distance;
+    // Each pair can be found from both of its ends
+    let mut seen_pairs = HashSet::new();
+    let mut edges = vec![];
+
+    for (from, pt) in points.iter().enumerate() {
+        let neighbors = index
+            .nearest_neighbor_iter_with_distance_2(pt)
+            .filter(|(npt, _)| npt.data != from)
+            .take(k.unwrap_or(usize::MAX));
+        for (npt, dist2) in neighbors {
+            if dist2 > max_distance2 {
+                break;
+            }
+            if seen_pairs.insert((usize::min(from, npt.data), usize::max(from, npt.data))) {
+                edges.push(Edge { from, to: npt.data, distance: dist2.sqrt() });
+                if edges.len() >= max_edges {
+                    return edges;
+                }
+            }
+        }
+    }
+
+    edges
+}
+
+fn delaunay_edges(params: &GraphParams, points: &[[f32; 3]], screen_points: &[Vec2]) -> Vec<Edge> {
+    let vertices = screen_points
+        .iter()
+        .enumerate()
+        .map(|(index, pt)| ScreenPoint { position: Point2::new(pt.x, pt.y), index })
+        .collect();
+    let triangulation: DelaunayTriangulation<ScreenPoint> = match DelaunayTriangulation::bulk_load(vertices) {
+        Ok(triangulation) => triangulation,
+        Err(_) => return vec![], // Non finite coordinates
+    };
+
+    triangulation
+        .undirected_edges()
+        .map(|edge| {
+            let [from, to] = edge.vertices().map(|vertex| vertex.data().index);
+            Edge { from, to, distance: distance(&points[from], &points[to]) }
+        })
+        .filter(|edge| edge.distance <= params.max_distance)
+        .take(params.max_edges)
+        .collect()
+}
+
+// Kruskal's algorithm on the edges to the nearest neighbors of each point, so that dense clouds stay interactive.
+// The tree is exact unless an edge of the minimum tree joins two points that are not among each other's nearest
+// neighbors, which only happens with far away clusters.
+fn spanning_tree_edges(params: &GraphParams, points: &[[f32; 3]]) -> Vec<Edge> {
+    let mut candidates = nearest_edges(points, Some(SPANNING_TREE_CANDIDATES), params.max_distance, usize::MAX);
+    candidates.sort_by(|e1, e2| e1.distance.total_cmp(&e2.distance));
+    let mut components = DisjointSets::new(points.len());
+    let max_edges = params.max_edges.min(points.len().saturating_sub(1));
+    let mut edges = vec![];
+
+    for edge in candidates {
+        if edges.len() >= max_edges {
+            break;
+        }
+        if components.union(edge.from, edge.to) {
+            edges.push(edge);
+        }
+    }
+
+    edges
+}
+
+// Union-find, with path halving
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(size: usize) -> Self {
+        Self { parents: (0..size).collect() }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    // False when they were already in the same set
+    fn union(&mut self, i: usize, j: usize) -> bool {
+        let (root_i, root_j) = (self.find(i), self.find(j));
+        self.parents[root_i] = root_j;
+        root_i != root_j
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    fn random_points(n: usize) -> Vec<[f32; 3]> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..n).map(|_| [rng.gen(), rng.gen(), rng.gen()]).collect()
+    }
+
+    // Prim's algorithm on the complete graph
+    fn exact_tree_length(points: &[[f32; 3]]) -> f32 {
+        let mut best_distance = vec![f32::INFINITY; points.len()];
+        let mut in_tree = vec![false; points.len()];
+        let mut current = 0;
+        let mut length = 0.0;
+        for _ in 1..points.len() {
+            in_tree[current] = true;
+            for (i, point) in points.iter().enumerate().filter(|(i, _)| !in_tree[*i]) {
+                best_distance[i] = best_distance[i].min(distance(&points[current], point));
+            }
+            current = (0..points.len()).filter(|i| !in_tree[*i]).min_by(|i, j| best_distance[*i].total_cmp(&best_distance[*j])).unwrap();
+            length += best_distance[current];
+        }
+        length
+    }
+
+    #[test]
+    fn spanning_tree_is_minimal() {
+        let points = random_points(500);
+        let params = GraphParams { mode: GraphMode::SpanningTree, k: 0, max_distance: f32::INFINITY, max_edges: usize::MAX };
+        let tree = params.edges(&points, &[]);
+        assert_eq!(tree.len(), points.len() - 1);
+        let length: f32 = tree.iter().map(|edge| edge.distance).sum();
+        assert!((length - exact_tree_length(&points)).abs() < 1e-3, "{}", length);
+    }
+
+    #[test]
+    fn edges_stop_at_the_max() {
+        let points = random_points(300);
+        let screen_points: Vec<Vec2> = points.iter().map(|pt| Vec2::new(pt[0], pt[1])).collect();
+        for mode in GRAPH_MODES {
+            let params = GraphParams { mode, k: 6, max_distance: 0.5, max_edges: 40 };
+            assert_eq!(params.edges(&points, &screen_points).len(), 40, "{:?}", mode);
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use crate::curve_registry::CurveRegistry;
+use crate::i18n::tr;
+use crate::interactive_curve::InteractiveCurve;
+
+const BUNDLE_TAG: &str = "#bundle";
+// Bigger than the packets of the controllers, that fit in an ethernet frame
+const MAX_PACKET_SIZE: usize = 8192;
+// Feedback of the displayed curve and the application, to the clients
+pub const ADDRESS_SELECTED: &str = "/curve/selected";
+// Commands of the application. The parameters of the curves have an address made from their id.
+pub const ADDRESS_SELECT: &str = "/curve/select";
+pub const ADDRESS_ACTION: &str = "/action";
+pub const ADDRESS_STATE: &str = "/state";
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+    Bool(bool),
+}
+
+impl OscArg {
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            OscArg::Int(value) => Some(*value as f32),
+            OscArg::Float(value) => Some(*value),
+            OscArg::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+            OscArg::Str(_) => None,
+        }
+    }
+
+    fn type_tag(&self) -> char {
+        match self {
+            OscArg::Int(_) => 'i',
+            OscArg::Float(_) => 'f',
+            OscArg::Str(_) => 's',
+            OscArg::Bool(true) => 'T',
+            OscArg::Bool(false) => 'F',
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+impl OscMessage {
+    pub fn new(address: &str, args: Vec<OscArg>) -> Self {
+        Self { address: address.to_string(), args }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        write_string(&mut bytes, &self.address);
+        let tags: String = std::iter::once(',').chain(self.args.iter().map(OscArg::type_tag)).collect();
+        write_string(&mut bytes, &tags);
+        for arg in &self.args {
+            match arg {
+                OscArg::Int(value) => bytes.extend(value.to_be_bytes()),
+                OscArg::Float(value) => bytes.extend(value.to_be_bytes()),
+                OscArg::Str(value) => write_string(&mut bytes, value),
+                OscArg::Bool(_) => (),
+            }
+        }
+        bytes
+    }
+
+    // The message of the packet, or the messages of its bundles. Their time tags are ignored: all run at once.
+    pub fn decode_packet(bytes: &[u8]) -> Result<Vec<OscMessage>, String> {
+        let mut reader = Reader { bytes, position: 0 };
+        if bytes.first() == Some(&b'#') {
+            if reader.string()? != BUNDLE_TAG {
+                return Err("Invalid bundle".to_string());
+            }
+            reader.take(8)?; // Time tag
+            let mut messages = vec![];
+            while reader.position < bytes.len() {
+                let size = reader.i32()?;
+                let size = usize::try_from(size).map_err(|_| format!("Invalid bundle element size {}", size))?;
+                messages.extend(Self::decode_packet(reader.take(size)?)?);
+            }
+            return Ok(messages);
+        }
+
+        let address = reader.string()?;
+        if !address.starts_with('/') {
+            return Err(format!("Invalid address '{}'", address));
+        }
+        // Old implementations may omit the type tags of messages without arguments
+        if reader.position == bytes.len() {
+            return Ok(vec![OscMessage::new(&address, vec![])]);
+        }
+        let tags = reader.string()?;
+        let tags = tags.strip_prefix(',').ok_or(format!("Invalid type tags '{}'", tags))?;
+        let mut args = vec![];
+        for tag in tags.chars() {
+            args.push(match tag {
+                'i' => OscArg::Int(reader.i32()?),
+                'f' => OscArg::Float(f32::from_bits(reader.i32()? as u32)),
+                's' => OscArg::Str(reader.string()?),
+                'T' => OscArg::Bool(true),
+                'F' => OscArg::Bool(false),
+                _ => return Err(format!("Unsupported type tag '{}' in {}", tag, address)),
+            });
+        }
+        Ok(vec![OscMessage::new(&address, args)])
+    }
+}
+
+// Null terminated, then padded to a multiple of 4 bytes
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend(value.as_bytes());
+    let padding = 4 - value.len() % 4;
+    bytes.extend(std::iter::repeat_n(0, padding));
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, size: usize) -> Result<&'a [u8], String> {
+        let taken = self.bytes.get(self.position..self.position + size).ok_or("Truncated packet")?;
+        self.position += size;
+        Ok(taken)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let rest = &self.bytes[self.position..];
+        let length = rest.iter().position(|byte| *byte == 0).ok_or("Unterminated string")?;
+        let value = std::str::from_utf8(&rest[..length]).map_err(|err| err.to_string())?.to_string();
+        self.take(length + 4 - length % 4)?;
+        Ok(value)
+    }
+}
+
+// Address of a parameter: "action.dejong.a" is "/dejong/a", "harmonograph.pen_x.freq" is "/harmonograph/penx/freq"
+pub fn address_of(target: &str) -> String {
+    let path = target.strip_prefix("action.").unwrap_or(target);
+    format!("/{}", path.replace('.', "/").replace('_', ""))
+}
+
+// A UDP socket that does not block. Every address that sent a message receives the feedback.
+pub struct OscServer {
+    socket: UdpSocket,
+    clients: Vec<SocketAddr>,
+}
+
+impl OscServer {
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, String> {
+        let socket = UdpSocket::bind(address).map_err(|err| err.to_string())?;
+        socket.set_nonblocking(true).map_err(|err| err.to_string())?;
+        Ok(Self { socket, clients: vec![] })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.socket.local_addr().expect("Bound socket")
+    }
+
+    // The messages received since the last call. Returns true as well when a new client joined.
+    pub fn receive(&mut self) -> (Vec<OscMessage>, bool) {
+        let mut messages = vec![];
+        let mut new_client = false;
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        while let Ok((size, sender)) = self.socket.recv_from(&mut buffer) {
+            if !self.clients.contains(&sender) {
+                self.clients.push(sender);
+                new_client = true;
+            }
+            match OscMessage::decode_packet(&buffer[..size]) {
+                Ok(packet) => messages.extend(packet),
+                Err(err) => eprintln!("OSC packet from {} ignored: {}", sender, err),
+            }
+        }
+        (messages, new_client)
+    }
+
+    pub fn send(&self, messages: &[OscMessage]) {
+        for message in messages {
+            let bytes = message.encode();
+            for client in &self.clients {
+                // A client that went away is not an error of the application
+                let _ = self.socket.send_to(&bytes, client);
+            }
+        }
+    }
+}
+
+// Remote control of the application: the messages of the clients change the curves, and the clients
+// receive the changes of the displayed curve, whatever changed it.
+pub struct OscRemote {
+    server: OscServer,
+    sent: HashMap<String, Vec<OscArg>>, // Last feedback per address
+}
+
+impl OscRemote {
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, String> {
+        Ok(Self { server: OscServer::bind(address)?, sent: HashMap::new() })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.server.local_addr()
+    }
+
+    // Applies the received messages. Returns the actions of the /action messages, for the application to run.
+    pub fn apply(
+        &mut self,
+        registry: &CurveRegistry,
+        curves: &mut [Box<dyn InteractiveCurve>],
+        curve_index: &mut usize,
+    ) -> Vec<String> {
+        let (messages, new_client) = self.server.receive();
+        if new_client {
+            self.sent.clear();
+        }
+        let mut actions = vec![];
+        for message in messages {
+            if let Err(err) = self.apply_message(&message, registry, curves, curve_index, &mut actions) {
+                eprintln!("OSC message {} ignored: {}", message.address, err);
+            }
+        }
+        actions
+    }
+
+    fn apply_message(
+        &mut self,
+        message: &OscMessage,
+        registry: &CurveRegistry,
+        curves: &mut [Box<dyn InteractiveCurve>],
+        curve_index: &mut usize,
+        actions: &mut Vec<String>,
+    ) -> Result<(), String> {
+        let first_arg = message.args.first();
+        match message.address.as_str() {
+            ADDRESS_SELECT => {
+                let curve = match first_arg {
+                    Some(OscArg::Str(name)) => name.clone(),
+                    Some(number) => number.as_f32().map(|number| (number as usize).to_string()).unwrap_or_default(),
+                    None => return Err("Missing curve".to_string()),
+                };
+                *curve_index = registry.find(&curve).ok_or(format!("Unknown curve '{}'", curve))?;
+            },
+            ADDRESS_ACTION => match first_arg {
+                Some(OscArg::Str(action)) => actions.push(action.clone()),
+                _ => return Err("Missing action".to_string()),
+            },
+            // Sends the whole state again
+            ADDRESS_STATE => self.sent.clear(),
+            address => {
+                let curve = &mut curves[*curve_index];
+                let spec = curve
+                    .param_specs()
+                    .into_iter()
+                    .find(|spec| address_of(spec.id) == address)
+                    .ok_or(format!("Unknown address for {}", tr(curve.name())))?;
+                let position = first_arg.and_then(OscArg::as_f32).ok_or("Missing value")?;
+                if !position.is_finite() {
+                    return Err(format!("Invalid value {}", position));
+                }
+                curve.set_param_position(spec.id, position);
+            },
+        }
+        Ok(())
+    }
+
+    // Sends what changed since the last feedback
+    pub fn send_feedback(&mut self, registry: &CurveRegistry, curve_index: usize, curve: &dyn InteractiveCurve) {
+        let messages: Vec<OscMessage> = feedback(registry, curve_index, curve)
+            .into_iter()
+            .filter(|message| self.sent.get(&message.address) != Some(&message.args))
+            .collect();
+        self.server.send(&messages);
+        for message in messages {
+            self.sent.insert(message.address, message.args);
+        }
+    }
+}
+
+// The displayed curve, with its number from 1 and its name, then the position of each of its parameters
+pub fn feedback(registry: &CurveRegistry, curve_index: usize, curve: &dyn InteractiveCurve) -> Vec<OscMessage> {
+    let selected = OscMessage::new(ADDRESS_SELECTED, vec![
+        OscArg::Int(curve_index as i32 + 1),
+        OscArg::Str(registry.registrations()[curve_index].name.clone()),
+    ]);
+    let params = curve.param_specs().into_iter().filter_map(|spec| {
+        let position = curve.param_position(spec.id)?;
+        Some(OscMessage::new(&address_of(spec.id), vec![OscArg::Float(position)]))
+    });
+    std::iter::once(selected).chain(params).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        let message = OscMessage::new("/harmonograph/penx/freq", vec![
+            OscArg::Float(0.5), OscArg::Int(-3), OscArg::Str("abcd".to_string()), OscArg::Bool(true),
+        ]);
+        let bytes = message.encode();
+        assert_eq!(bytes.len() % 4, 0);
+        assert_eq!(&bytes[..24], b"/harmonograph/penx/freq\0");
+        assert_eq!(&bytes[24..32], b",fisT\0\0\0");
+        assert_eq!(OscMessage::decode_packet(&bytes), Ok(vec![message]));
+    }
+
+    #[test]
+    fn bundles_are_unpacked() {
+        let messages = [
+            OscMessage::new("/dejong/a", vec![OscArg::Float(0.25)]),
+            OscMessage::new("/curve/select", vec![OscArg::Int(2)]),
+        ];
+        let mut bundle = vec![];
+        write_string(&mut bundle, BUNDLE_TAG);
+        bundle.extend([0, 0, 0, 0, 0, 0, 0, 1]);
+        for message in &messages {
+            let bytes = message.encode();
+            bundle.extend((bytes.len() as i32).to_be_bytes());
+            bundle.extend(bytes);
+        }
+        assert_eq!(OscMessage::decode_packet(&bundle).unwrap(), messages);
+        assert!(OscMessage::decode_packet(&bundle[..bundle.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn addresses_of_targets() {
+        assert_eq!(address_of("action.dejong.a"), "/dejong/a");
+        assert_eq!(address_of("harmonograph.pen_x.freq"), "/harmonograph/penx/freq");
+        assert_eq!(address_of("mandelbrot.zoom"), "/mandelbrot/zoom");
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "gui")]
+use ggegui::egui;
+use rand::Rng;
+use crate::i18n::tr;
+use crate::interactive_curve::InteractiveCurve;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scale {
+    Linear,
+    Log, // For ranges over several decades, with a min above 0
+}
+
+// Description of a numeric parameter of a curve. The sliders, the remote controls, the audio routes, the
+// randomization and the sweeps are generated from it. The id is stable, for the config and the remote controls.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParamSpec {
+    pub id: &'static str,
+    pub label: String,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32, // 0 when continuous
+    pub scale: Scale,
+    pub unit: &'static str,
+}
+
+impl ParamSpec {
+    // The label is the message of the id, as for the actions
+    pub fn new(id: &'static str, min: f32, max: f32) -> Self {
+        Self {
+            id,
+            label: tr(id).trim_end_matches(':').to_string(),
+            min,
+            max,
+            step: 0.0,
+            scale: Scale::Linear,
+            unit: "",
+        }
+    }
+
+    pub fn label(mut self, label: String) -> Self {
+        self.label = label;
+        self
+    }
+
+    pub fn step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    pub fn log(mut self) -> Self {
+        self.scale = Scale::Log;
+        self
+    }
+
+    pub fn unit(mut self, unit: &'static str) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    // Position of the value in the range, in [0, 1], along the scale
+    pub fn position(&self, value: f32) -> f32 {
+        let position = match self.scale {
+            Scale::Linear => (value - self.min) / (self.max - self.min),
+            Scale::Log => (value / self.min).ln() / (self.max / self.min).ln(),
+        };
+        if position.is_nan() { 0.0 } else { position.clamp(0.0, 1.0) }
+    }
+
+    // The inverse of position, without the step. The min when the position is not a number.
+    pub fn value_at(&self, position: f32) -> f32 {
+        if !position.is_finite() {
+            return self.min;
+        }
+        let position = position.clamp(0.0, 1.0);
+        match self.scale {
+            Scale::Linear => self.min + position * (self.max - self.min),
+            Scale::Log => self.min * (self.max / self.min).powf(position),
+        }
+    }
+
+    // The closest value on a step, in the range. The min when the value is not a number.
+    pub fn snap(&self, value: f32) -> f32 {
+        if !value.is_finite() {
+            return self.min;
+        }
+        let value = value.clamp(self.min, self.max);
+        if self.step > 0.0 {
+            (self.min + ((value - self.min) / self.step).round() * self.step).min(self.max)
+        } else {
+            value
+        }
+    }
+
+    // Uniform along the scale
+    pub fn random(&self, rng: &mut impl Rng) -> f32 {
+        self.snap(self.value_at(rng.gen_range(0.0..=1.0)))
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn slider<'a>(&self, value: &'a mut f32) -> egui::Slider<'a> {
+        let mut slider = egui::Slider::new(value, self.min..=self.max)
+            .logarithmic(self.scale == Scale::Log)
+            .clamp_to_range(false);
+        if self.step > 0.0 {
+            slider = slider.step_by(self.step as f64);
+        }
+        if !self.unit.is_empty() {
+            slider = slider.suffix(format!(" {}", self.unit));
+        }
+        slider
+    }
+}
+
+// Relative change of a parameter by delta times its range along the scale, without the step, so that small
+// changes add up
+#[cfg(feature = "gui")]
+pub fn nudge<C: InteractiveCurve + ?Sized>(curve: &mut C, id: &str, delta: f32) {
+    let Some(spec) = curve.param_specs().into_iter().find(|spec| spec.id == id) else { return };
+    if let Some(value) = curve.param(id) {
+        curve.set_param(id, spec.value_at(spec.position(value) + delta));
+    }
+}
+
+// The values of all the parameters, per id
+pub fn values(curve: &dyn InteractiveCurve) -> BTreeMap<String, f32> {
+    curve
+        .param_specs()
+        .into_iter()
+        .filter_map(|spec| Some((spec.id.to_string(), curve.param(spec.id)?)))
+        .collect()
+}
+
+// Sets some parameters, in their range. Fails without changing anything when an id is unknown.
+#[cfg(feature = "http")]
+pub fn set_values(curve: &mut dyn InteractiveCurve, values: &BTreeMap<String, f32>) -> Result<(), String> {
+    let specs = curve.param_specs();
+    let mut changes = vec![];
+    for (id, value) in values {
+        let spec = specs.iter().find(|spec| spec.id == id).ok_or(format!("Unknown parameter '{}'", id))?;
+        changes.push((spec.id, spec.snap(*value)));
+    }
+    for (id, value) in changes {
+        curve.set_param(id, value);
+    }
+    Ok(())
+}
+
+// Brings every parameter back on a step of its range, e.g. after a state from outside
+#[cfg(feature = "http")]
+pub fn snap_values(curve: &mut dyn InteractiveCurve) {
+    for spec in curve.param_specs() {
+        if let Some(value) = curve.param(spec.id) {
+            let snapped = spec.snap(value);
+            if snapped != value {
+                curve.set_param(spec.id, snapped);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+pub fn randomize(curve: &mut dyn InteractiveCurve, rng: &mut impl Rng) {
+    for spec in curve.param_specs() {
+        curve.set_param(spec.id, spec.random(rng));
+    }
+}
+
+// Moves a parameter back and forth over its range, along its scale
+#[cfg(feature = "gui")]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParamSweep {
+    phase: f32, // In [0, 1[ per period: up during the first half, down during the second
+}
+
+#[cfg(feature = "gui")]
+impl ParamSweep {
+    // Starts going up from the position, without a jump
+    pub fn from_position(position: f32) -> Self {
+        Self { phase: position.clamp(0.0, 1.0) / 2.0 }
+    }
+
+    // The position after dt seconds
+    pub fn advance(&mut self, dt: f32, period: f32) -> f32 {
+        self.phase = (self.phase + dt / period).fract();
+        1.0 - (1.0 - 2.0 * self.phase).abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    #[cfg(any(feature = "gui", feature = "http"))]
+    use crate::curve_registry::CurveRegistry;
+
+    #[test]
+    fn positions_follow_the_scale() {
+        let linear = ParamSpec::new("test.linear", -2.0, 2.0).step(0.5);
+        assert_eq!(linear.position(1.0), 0.75);
+        assert_eq!(linear.value_at(0.75), 1.0);
+        assert_eq!(linear.position(5.0), 1.0);
+        assert_eq!(linear.snap(0.6), 0.5);
+        assert_eq!(linear.snap(-3.0), -2.0);
+        assert_eq!(linear.value_at(f32::NAN), -2.0);
+        assert_eq!(linear.snap(f32::NAN), -2.0);
+        assert_eq!(linear.snap(f32::INFINITY), -2.0);
+
+        let log = ParamSpec::new("test.log", 1.0, 10_000.0).log();
+        assert!((log.position(100.0) - 0.5).abs() < 1e-6);
+        assert_eq!(log.value_at(f32::NAN), 1.0);
+        assert!((log.value_at(0.25) - 10.0).abs() < 1e-3);
+        assert_eq!(log.position(0.0), 0.0);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let value = linear.random(&mut rng);
+            assert!((-2.0..=2.0).contains(&value) && (value * 2.0).fract() == 0.0);
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn sweeps_go_back_and_forth_from_the_position() {
+        let mut sweep = ParamSweep::from_position(0.5);
+        assert!((sweep.advance(0.0, 4.0) - 0.5).abs() < 1e-6);
+        assert!((sweep.advance(1.0, 4.0) - 1.0).abs() < 1e-6);
+        assert!((sweep.advance(1.0, 4.0) - 0.5).abs() < 1e-6);
+        assert!(sweep.advance(1.0, 4.0).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn params_are_randomized_in_their_range() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for mut curve in CurveRegistry::default().instantiate() {
+            randomize(curve.as_mut(), &mut rng);
+            let randomized = values(curve.as_ref());
+            assert_eq!(randomized.len(), curve.param_specs().len());
+            for spec in curve.param_specs() {
+                let value = randomized[spec.id];
+                assert!(value >= spec.min * 0.9999 && value <= spec.max * 1.0001, "{} is {}", spec.id, value);
+            }
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn unknown_params_are_not_set() {
+        for mut curve in CurveRegistry::default().instantiate() {
+            let before = values(curve.as_ref());
+            let mut changes = before.clone();
+            changes.insert("unknown".to_string(), 1.0);
+            assert!(set_values(curve.as_mut(), &changes).is_err());
+            assert_eq!(values(curve.as_ref()), before);
+        }
+    }
+}
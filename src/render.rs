@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use glam::Vec2;
+
+// Backend-neutral description of what a curve draws.
+// Curves produce layers of shapes, that the ggez, raster and SVG backends turn into pixels or files.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Rgba {
+    pub const BLACK: Rgba = Rgba::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Rgba = Rgba::new(1.0, 1.0, 1.0, 1.0);
+    pub const RED: Rgba = Rgba::new(1.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [to_u8(self.r), to_u8(self.g), to_u8(self.b), to_u8(self.a)]
+    }
+}
+
+pub enum Shape<'a> {
+    // Either one color for the whole line, or one per segment
+    Polyline { points: Vec<Vec2>, width: f32, colors: Vec<Rgba> },
+    // Every 3 vertices make a triangle
+    Triangles { vertices: Vec<Vec2>, color: Rgba },
+    // Filled when there is no stroke width
+    Circle { center: Vec2, radius: f32, stroke: Option<f32>, color: Rgba },
+    Rectangle { min: Vec2, size: Vec2, color: Rgba },
+    // RGBA8 pixels, drawn with their top left corner at the origin. The id changes when the pixels change.
+    Raster { pixels: &'a [u8], width: u32, height: u32, id: u64 },
+}
+
+// A layer of shapes, in its own coordinates: screen position = dest + position * scale.
+// Layers with a lower z are drawn first.
+pub struct DrawData<'a> {
+    pub shapes: Vec<Shape<'a>>,
+    pub dest: Vec2,
+    pub scale: Vec2,
+    pub z: i32,
+}
+
+impl<'a> DrawData<'a> {
+    pub fn new() -> Self {
+        Self {
+            shapes: vec![],
+            dest: Vec2::ZERO,
+            scale: Vec2::ONE,
+            z: 0,
+        }
+    }
+
+    pub fn image(pixels: &'a [u8], width: u32, height: u32, id: u64) -> Self {
+        let mut data = Self::new();
+        data.shapes.push(Shape::Raster { pixels, width, height, id });
+        data
+    }
+
+    pub fn dest(mut self, dest: Vec2) -> Self {
+        self.dest = dest;
+        self
+    }
+
+    pub fn scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn z(mut self, z: i32) -> Self {
+        self.z = z;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    pub fn line(&mut self, points: &[Vec2], width: f32, color: Rgba) -> &mut Self {
+        self.shapes.push(Shape::Polyline { points: points.to_vec(), width, colors: vec![color] });
+        self
+    }
+
+    pub fn colored_line(&mut self, points: Vec<Vec2>, width: f32, colors: Vec<Rgba>) -> &mut Self {
+        self.shapes.push(Shape::Polyline { points, width, colors });
+        self
+    }
+
+    pub fn triangles(&mut self, vertices: Vec<Vec2>, color: Rgba) -> &mut Self {
+        self.shapes.push(Shape::Triangles { vertices, color });
+        self
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32, stroke: Option<f32>, color: Rgba) -> &mut Self {
+        self.shapes.push(Shape::Circle { center, radius, stroke, color });
+        self
+    }
+
+    pub fn rectangle(&mut self, min: Vec2, size: Vec2, color: Rgba) -> &mut Self {
+        self.shapes.push(Shape::Rectangle { min, size, color });
+        self
+    }
+
+    pub fn to_screen(&self, pt: Vec2) -> Vec2 {
+        self.dest + pt * self.scale
+    }
+}
+
+impl Default for DrawData<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape<'_> {
+    pub fn segment_color(colors: &[Rgba], segment: usize) -> Rgba {
+        if colors.len() == 1 { colors[0] } else { colors[segment] }
+    }
+}
+
+// Unique id for the pixels of a raster image
+pub fn next_raster_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+// Layers in drawing order
+pub fn sorted_by_z<'a, 'b>(layers: &'b [DrawData<'a>]) -> Vec<&'b DrawData<'a>> {
+    let mut sorted: Vec<&DrawData> = layers.iter().collect();
+    sorted.sort_by_key(|layer| layer.z);
+    sorted
+}
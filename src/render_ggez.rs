@@ -0,0 +1,94 @@
+use std::collections::{HashMap, HashSet};
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Image as GImage, ImageFormat, Mesh, MeshBuilder, Rect};
+use crate::render::{DrawData, Rgba, Shape};
+
+// Draws the shapes of the curves on a ggez canvas.
+// Raster images are uploaded to the GPU once, and kept as long as they are drawn.
+pub struct GgezRenderer {
+    images: HashMap<u64, GImage>,
+}
+
+impl GgezRenderer {
+    pub fn new() -> Self {
+        Self {
+            images: HashMap::new(),
+        }
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, layers: &[DrawData]) -> GameResult {
+        let mut drawn_images = HashSet::new();
+
+        for layer in layers {
+            let params = DrawParam::new().dest(layer.dest).scale(layer.scale).z(layer.z);
+            let mut builder = MeshBuilder::new();
+            let mut has_mesh = false;
+
+            for shape in &layer.shapes {
+                match shape {
+                    Shape::Raster { pixels, width, height, id } => {
+                        let image = self.images
+                            .entry(*id)
+                            .or_insert_with(|| GImage::from_pixels(ctx, pixels, ImageFormat::Rgba8Unorm, *width, *height));
+                        canvas.draw(image, params);
+                        drawn_images.insert(*id);
+                    },
+                    _ => has_mesh |= Self::add_to_mesh(&mut builder, shape)?,
+                }
+            }
+
+            if has_mesh {
+                canvas.draw(&Mesh::from_data(ctx, builder.build()), params);
+            }
+        }
+
+        self.images.retain(|id, _| drawn_images.contains(id));
+        Ok(())
+    }
+
+    // Returns whether something was added
+    fn add_to_mesh(builder: &mut MeshBuilder, shape: &Shape) -> GameResult<bool> {
+        match shape {
+            Shape::Polyline { points, width, colors } => {
+                if points.len() < 2 {
+                    return Ok(false);
+                }
+                if colors.len() == 1 {
+                    builder.line(points, *width, color(&colors[0]))?;
+                } else {
+                    for (i, segment) in points.windows(2).enumerate() {
+                        builder.line(segment, *width, color(&Shape::segment_color(colors, i)))?;
+                    }
+                }
+            },
+            Shape::Triangles { vertices, color: c } => {
+                if vertices.len() < 3 {
+                    return Ok(false);
+                }
+                builder.triangles(vertices, color(c))?;
+            },
+            Shape::Circle { center, radius, stroke, color: c } => {
+                let mode = match stroke {
+                    Some(width) => DrawMode::stroke(*width),
+                    None => DrawMode::fill(),
+                };
+                builder.circle(mode, *center, *radius, *radius / 100.0, color(c))?;
+            },
+            Shape::Rectangle { min, size, color: c } => {
+                builder.rectangle(DrawMode::fill(), Rect::new(min.x, min.y, size.x, size.y), color(c))?;
+            },
+            Shape::Raster { .. } => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl Default for GgezRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn color(rgba: &Rgba) -> Color {
+    Color::new(rgba.r, rgba.g, rgba.b, rgba.a)
+}
@@ -0,0 +1,168 @@
+use std::path::Path;
+use glam::Vec2;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder, ImageResult};
+use crate::render::{self, DrawData, Rgba, Shape};
+
+// Software rendering of the curves' shapes into RGBA8 pixels, without any GPU.
+// No anti-aliasing: a pixel is covered when its center is inside a shape.
+pub struct RasterImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RasterImage {
+    pub fn new(width: u32, height: u32, background: Rgba) -> Self {
+        let pixels = background.to_rgba8().repeat((width * height) as usize);
+        Self { width, height, pixels }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = 4 * (y * self.width + x) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    pub fn draw(&mut self, layers: &[DrawData]) {
+        for layer in render::sorted_by_z(layers) {
+            for shape in &layer.shapes {
+                self.draw_shape(layer, shape);
+            }
+        }
+    }
+
+    pub fn encode_png(&self) -> ImageResult<Vec<u8>> {
+        let mut png = vec![];
+        PngEncoder::new(&mut png).write_image(&self.pixels, self.width, self.height, ColorType::Rgba8)?;
+        Ok(png)
+    }
+
+    pub fn save_png(&self, path: &Path) -> ImageResult<()> {
+        std::fs::write(path, self.encode_png()?)?;
+        Ok(())
+    }
+
+    fn draw_shape(&mut self, layer: &DrawData, shape: &Shape) {
+        let scale = layer.scale.x;
+        match shape {
+            Shape::Polyline { points, width, colors } => {
+                for (i, segment) in points.windows(2).enumerate() {
+                    let color = Shape::segment_color(colors, i);
+                    self.draw_segment(layer.to_screen(segment[0]), layer.to_screen(segment[1]), width * scale, color);
+                }
+            },
+            Shape::Triangles { vertices, color } => {
+                for triangle in vertices.chunks_exact(3) {
+                    let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|pt| layer.to_screen(pt));
+                    self.fill_triangle(a, b, c, *color);
+                }
+            },
+            Shape::Circle { center, radius, stroke, color } => {
+                let stroke = stroke.map(|width| width * scale);
+                self.draw_circle(layer.to_screen(*center), radius * scale, stroke, *color);
+            },
+            Shape::Rectangle { min, size, color } => {
+                self.fill_rectangle(layer.to_screen(*min), *size * layer.scale, *color);
+            },
+            Shape::Raster { pixels, width, height, .. } => {
+                self.blit(pixels, *width, *height, layer.dest, layer.scale);
+            },
+        }
+    }
+
+    fn blend(&mut self, x: i32, y: i32, color: Rgba) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let i = 4 * (y as usize * self.width as usize + x as usize);
+        let source = color.to_rgba8();
+        let alpha = color.a.clamp(0.0, 1.0);
+        for (dest, source) in self.pixels[i..i + 3].iter_mut().zip(source) {
+            *dest = (alpha * (source as f32) + (1.0 - alpha) * (*dest as f32)).round() as u8;
+        }
+        let dest_alpha = self.pixels[i + 3] as f32 / 255.0;
+        self.pixels[i + 3] = ((alpha + dest_alpha * (1.0 - alpha)) * 255.0).round() as u8;
+    }
+
+    // Calls f for each pixel whose center is in the box, clipped to the image
+    fn for_each_pixel(&mut self, min: Vec2, max: Vec2, mut f: impl FnMut(&mut Self, i32, i32, Vec2)) {
+        let x_start = f32::max(min.x.floor(), 0.0) as i32;
+        let y_start = f32::max(min.y.floor(), 0.0) as i32;
+        let x_end = f32::min(max.x.ceil(), self.width as f32) as i32;
+        let y_end = f32::min(max.y.ceil(), self.height as f32) as i32;
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                f(self, x, y, Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+            }
+        }
+    }
+
+    fn fill_triangle(&mut self, a: Vec2, b: Vec2, c: Vec2, color: Rgba) {
+        let edge = |p: Vec2, q: Vec2, r: Vec2| (q - p).perp_dot(r - p);
+        let area = edge(a, b, c);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        self.for_each_pixel(a.min(b).min(c), a.max(b).max(c), |image, x, y, center| {
+            let w = [edge(b, c, center), edge(c, a, center), edge(a, b, center)];
+            if w.iter().all(|w| w * area >= 0.0) {
+                image.blend(x, y, color);
+            }
+        });
+    }
+
+    fn draw_segment(&mut self, from: Vec2, to: Vec2, width: f32, color: Rgba) {
+        let direction = (to - from).normalize_or_zero();
+        if direction == Vec2::ZERO {
+            return;
+        }
+        // At least one pixel wide, so that thin lines do not vanish
+        let normal = direction.perp() * f32::max(width, 1.0) / 2.0;
+        self.fill_triangle(from + normal, to + normal, to - normal, color);
+        self.fill_triangle(from + normal, to - normal, from - normal, color);
+    }
+
+    fn draw_circle(&mut self, center: Vec2, radius: f32, stroke: Option<f32>, color: Rgba) {
+        let half_stroke = stroke.map_or(0.0, |width| f32::max(width, 1.0) / 2.0);
+        let outer = radius + half_stroke;
+        self.for_each_pixel(center - outer, center + outer, |image, x, y, pixel_center| {
+            let distance = pixel_center.distance(center);
+            let inside = match stroke {
+                Some(_) => (distance - radius).abs() <= half_stroke,
+                None => distance <= radius,
+            };
+            if inside {
+                image.blend(x, y, color);
+            }
+        });
+    }
+
+    fn fill_rectangle(&mut self, min: Vec2, size: Vec2, color: Rgba) {
+        self.for_each_pixel(min, min + size, |image, x, y, _| image.blend(x, y, color));
+    }
+
+    fn blit(&mut self, pixels: &[u8], width: u32, height: u32, dest: Vec2, scale: Vec2) {
+        let size = Vec2::new(width as f32, height as f32) * scale;
+        self.for_each_pixel(dest, dest + size, |image, x, y, center| {
+            let source = (center - dest) / scale;
+            let (sx, sy) = (source.x as u32, source.y as u32);
+            if sx < width && sy < height {
+                let i = 4 * (sy * width + sx) as usize;
+                let a = pixels[i + 3] as f32 / 255.0;
+                image.blend(x, y, Rgba::new(pixels[i] as f32 / 255.0, pixels[i + 1] as f32 / 255.0, pixels[i + 2] as f32 / 255.0, a));
+            }
+        });
+    }
+}
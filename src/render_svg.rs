@@ -0,0 +1,121 @@
+use std::fmt::Write;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use glam::Vec2;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use crate::render::{self, DrawData, Rgba, Shape};
+
+// Writes the curves' shapes as an SVG document. Raster images are embedded as PNG.
+pub fn to_svg(width: u32, height: u32, background: Option<Rgba>, layers: &[DrawData]) -> String {
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        width, height, width, height
+    ).unwrap();
+    if let Some(color) = background {
+        writeln!(svg, r#"<rect width="100%" height="100%" {}/>"#, fill(&color)).unwrap();
+    }
+
+    for layer in render::sorted_by_z(layers) {
+        writeln!(
+            svg,
+            r#"<g transform="translate({} {}) scale({} {})">"#,
+            num(layer.dest.x), num(layer.dest.y), num(layer.scale.x), num(layer.scale.y)
+        ).unwrap();
+        for shape in &layer.shapes {
+            write_shape(&mut svg, shape);
+        }
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn write_shape(svg: &mut String, shape: &Shape) {
+    match shape {
+        Shape::Polyline { points, width, colors } if colors.len() == 1 => {
+            writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" {} stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                points_list(points), stroke(&colors[0]), num(*width)
+            ).unwrap();
+        },
+        Shape::Polyline { points, width, colors } => {
+            for (i, segment) in points.windows(2).enumerate() {
+                writeln!(
+                    svg,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {} stroke-width="{}" stroke-linecap="round"/>"#,
+                    num(segment[0].x), num(segment[0].y), num(segment[1].x), num(segment[1].y),
+                    stroke(&Shape::segment_color(colors, i)), num(*width)
+                ).unwrap();
+            }
+        },
+        Shape::Triangles { vertices, color } => {
+            let mut path = String::new();
+            for triangle in vertices.chunks_exact(3) {
+                write!(
+                    path,
+                    "M{} {}L{} {}L{} {}Z",
+                    num(triangle[0].x), num(triangle[0].y), num(triangle[1].x), num(triangle[1].y), num(triangle[2].x), num(triangle[2].y)
+                ).unwrap();
+            }
+            writeln!(svg, r#"<path d="{}" {}/>"#, path, fill(color)).unwrap();
+        },
+        Shape::Circle { center, radius, stroke: Some(width), color } => {
+            writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="none" {} stroke-width="{}"/>"#,
+                num(center.x), num(center.y), num(*radius), stroke(color), num(*width)
+            ).unwrap();
+        },
+        Shape::Circle { center, radius, stroke: None, color } => {
+            writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, num(center.x), num(center.y), num(*radius), fill(color)).unwrap();
+        },
+        Shape::Rectangle { min, size, color } => {
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                num(min.x), num(min.y), num(size.x), num(size.y), fill(color)
+            ).unwrap();
+        },
+        Shape::Raster { pixels, width, height, .. } => {
+            let mut png = vec![];
+            if PngEncoder::new(&mut png).write_image(pixels, *width, *height, ColorType::Rgba8).is_ok() {
+                writeln!(
+                    svg,
+                    r#"<image width="{}" height="{}" href="data:image/png;base64,{}"/>"#,
+                    width, height, BASE64.encode(&png)
+                ).unwrap();
+            }
+        },
+    }
+}
+
+fn num(value: f32) -> String {
+    let formatted = format!("{:.3}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn points_list(points: &[Vec2]) -> String {
+    points
+        .iter()
+        .map(|pt| format!("{},{}", num(pt.x), num(pt.y)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn rgb(color: &Rgba) -> String {
+    let [r, g, b, _] = color.to_rgba8();
+    format!("rgb({},{},{})", r, g, b)
+}
+
+fn fill(color: &Rgba) -> String {
+    format!(r#"fill="{}" fill-opacity="{}""#, rgb(color), num(color.a))
+}
+
+fn stroke(color: &Rgba) -> String {
+    format!(r#"stroke="{}" stroke-opacity="{}""#, rgb(color), num(color.a))
+}
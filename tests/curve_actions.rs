@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+use lissajous::{tr_in, CurveRegistry, LANGUAGES};
+
+#[test]
+fn every_curve_action_is_valid() {
+    for curve in CurveRegistry::default().instantiate() {
+        let mut ids = HashSet::new();
+        for action in curve.actions() {
+            assert!(ids.insert(action.id), "{} is declared twice", action.id);
+            for input in action.default_inputs() {
+                assert!(action.accepts(&input), "{} cannot be bound to {}", action.id, input);
+            }
+            for language in LANGUAGES {
+                assert_ne!(tr_in(language, action.id), action.id, "{} has no label", action.id);
+            }
+        }
+    }
+}
+
+#[test]
+fn gamepad_roles_only_name_actions_of_their_curve() {
+    for curve in CurveRegistry::default().instantiate() {
+        let ids: HashSet<&str> = curve.actions().iter().map(|action| action.id).collect();
+        for role in curve.gamepad_roles() {
+            for action in role.actions {
+                assert!(ids.contains(action), "{} is not an action of {}", action, curve.name());
+            }
+            for language in LANGUAGES {
+                assert_ne!(tr_in(language, role.id), role.id, "{} has no label", role.id);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+#[test]
+fn lissajou_points_stay_in_the_slider_range() {
+    use lissajous::{InteractiveCurve, Lissajou};
+    let mut curve = Lissajou::new();
+    for _ in 0..10 {
+        curve.on_trigger("action.lissajou.points_down", glam::Vec2::ZERO);
+    }
+    assert_eq!(curve.save_state()["params"]["nb_points"], 2);
+    for _ in 0..300 {
+        curve.on_trigger("action.lissajou.points_up", glam::Vec2::ZERO);
+    }
+    assert_eq!(curve.save_state()["params"]["nb_points"], 20_000);
+}
+
+#[cfg(feature = "gui")]
+#[test]
+fn lissajou_triggers_keep_the_params_in_their_range() {
+    use lissajous::{ActionKind, InteractiveCurve, Lissajou};
+    let mut curve = Lissajou::new();
+    for action in curve.actions().into_iter().filter(|action| action.kind == ActionKind::Trigger) {
+        for _ in 0..300 {
+            curve.on_trigger(action.id, glam::Vec2::ZERO);
+        }
+        for spec in curve.param_specs() {
+            let value = curve.param(spec.id).unwrap();
+            assert!((spec.min..=spec.max).contains(&value), "{} is {} after {}", spec.id, value, action.id);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use lissajous::{CurveRegistry, DeJongAttractor, DeJongParams, InteractiveCurve, Lissajou, LissajouParams};
+
+#[test]
+fn every_curve_state_round_trips() {
+    for mut curve in CurveRegistry::default().instantiate() {
+        let state = curve.save_state();
+        assert!(!state.is_null(), "{} has no state", curve.name());
+        curve.restore_state(&state).unwrap();
+        assert_eq!(curve.save_state(), state);
+    }
+}
+
+#[test]
+fn restoring_a_state_changes_the_parameters() {
+    let tuned = DeJongAttractor::with_params(DeJongParams { a: 0.5, ..DeJongParams::default() });
+    let mut curve = DeJongAttractor::new();
+    curve.restore_state(&tuned.save_state()).unwrap();
+    assert_eq!(curve.params().a, 0.5);
+}
+
+#[test]
+fn invalid_states_are_rejected() {
+    let mut curve = DeJongAttractor::new();
+    assert!(curve.restore_state(&serde_json::json!({"a": "not a number"})).is_err());
+    assert_eq!(*curve.params(), DeJongParams::default());
+}
+
+#[test]
+fn every_param_drives_the_curve() {
+    for mut curve in CurveRegistry::default().instantiate() {
+        for spec in curve.param_specs() {
+            curve.set_param_position(spec.id, 0.0);
+            let low = curve.save_state();
+            curve.set_param_position(spec.id, 1.0);
+            assert_ne!(curve.save_state(), low, "{} does not change {}", spec.id, curve.name());
+
+            let value = spec.snap(spec.value_at(0.3));
+            curve.set_param(spec.id, value);
+            let read = curve.param(spec.id).unwrap();
+            assert!((read - value).abs() <= value.abs() * 1e-4, "{} is {} instead of {}", spec.id, read, value);
+            assert!((curve.param_position(spec.id).unwrap() - spec.position(value)).abs() < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn lissajou_screenshots_of_different_2d_drawings_have_different_names() {
+    let mut curve = Lissajou::new();
+    let name = curve.screenshot_file_name();
+    curve.set_param("lissajou.phase_y", 1.0);
+    assert_ne!(curve.screenshot_file_name(), name);
+}
+
+#[test]
+fn lissajou_z_params_are_only_listed_in_3d() {
+    let ids = |curve: &Lissajou| curve.param_specs().iter().map(|spec| spec.id).collect::<Vec<_>>();
+    let mut curve = Lissajou::with_params(LissajouParams { three_d: true, ..LissajouParams::default() });
+    assert!(ids(&curve).contains(&"lissajou.freq_z"));
+    assert!(ids(&curve).contains(&"lissajou.phase_z"));
+    curve.set_param("lissajou.phase_z", 1.0);
+    assert_eq!(curve.param("lissajou.phase_z"), Some(1.0));
+
+    let curve = Lissajou::new();
+    assert!(!ids(&curve).contains(&"lissajou.freq_z"));
+    assert!(ids(&curve).contains(&"lissajou.points"));
+}
+
+#[test]
+fn lissajou_points_stay_in_their_range() {
+    let mut curve = Lissajou::new();
+    let spec = curve.param_specs().into_iter().find(|spec| spec.id == "lissajou.points").unwrap();
+    curve.set_param(spec.id, 1e9);
+    assert_eq!(curve.param(spec.id), Some(spec.max));
+    curve.set_param(spec.id, -5.0);
+    assert_eq!(curve.param(spec.id), Some(spec.min));
+}
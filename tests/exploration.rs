@@ -0,0 +1,57 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::collections::BTreeMap;
+use lissajous::{CurveRegistry, Exploration};
+
+const VARIANTS: usize = 4;
+const SIZE: u32 = 32;
+const DEFAULT_STRENGTH: f32 = 0.1;
+
+#[test]
+fn every_curve_can_be_explored() {
+    let registry = CurveRegistry::default();
+    let mut rng = StdRng::seed_from_u64(3);
+    for registration in registry.registrations() {
+        let curve = (registration.factory)();
+        let parent = curve.save_state();
+        let mut exploration = Exploration::new(registration, parent.clone(), VARIANTS, DEFAULT_STRENGTH, &mut rng).unwrap();
+        assert_eq!(exploration.states.len(), VARIANTS + 1);
+        assert_eq!(exploration.states[0], parent);
+        assert!(exploration.states[1..].iter().all(|state| *state != parent), "{} variants do not change", registration.name);
+
+        let thumbnails = exploration.render_thumbnails(registration, SIZE).unwrap();
+        assert_eq!(thumbnails.len(), VARIANTS + 1);
+        assert!(thumbnails.iter().all(|image| image.width() == SIZE && image.height() == SIZE));
+
+        let chosen = exploration.states[2].clone();
+        exploration.breed(registration, 2, VARIANTS, DEFAULT_STRENGTH, &mut rng).unwrap();
+        assert_eq!(exploration.states[0], chosen);
+        assert_eq!(exploration.generation, 1);
+    }
+}
+
+#[test]
+fn crossed_over_params_come_from_the_favorites() {
+    let registry = CurveRegistry::default();
+    let registration = registry.registrations().iter().find(|registration| registration.name == "curve.harmonograph").unwrap();
+    let mut rng = StdRng::seed_from_u64(5);
+    let parent = (registration.factory)().save_state();
+    let mut exploration = Exploration::new(registration, parent, VARIANTS, 0.5, &mut rng).unwrap();
+    let values_of = |state: &serde_json::Value| {
+        let mut curve = (registration.factory)();
+        curve.restore_state(state).unwrap();
+        curve.param_specs().iter().map(|spec| (spec.id, curve.param(spec.id).unwrap())).collect::<BTreeMap<_, _>>()
+    };
+    let first = values_of(&exploration.states[1]);
+    let second = values_of(&exploration.states[2]);
+
+    exploration.toggle_favorite(1);
+    exploration.toggle_favorite(2);
+    exploration.cross(registration, VARIANTS, DEFAULT_STRENGTH, &mut rng).unwrap();
+    assert!(exploration.favorites.is_empty());
+    // Amplitudes are locked by pairs, so only the other params are checked
+    let child = values_of(&exploration.states[0]);
+    for (id, value) in child.iter().filter(|(id, _)| !id.ends_with(".amp")) {
+        assert!(*value == first[id] || *value == second[id], "{} is {}", id, value);
+    }
+}
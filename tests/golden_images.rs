@@ -0,0 +1,59 @@
+// Renders each curve with its default parameters through the headless rasterizer,
+// and compares the result with the reference images in tests/golden.
+// Run with UPDATE_GOLDEN=1 to regenerate the references after an intended change.
+
+use std::path::PathBuf;
+use lissajous::{render_raster, DeJongAttractor, Harmonograph, InteractiveCurve, Lissajou, MandelbrotSet};
+
+const SIZE: u32 = 256;
+const CHANNEL_TOLERANCE: u8 = 8;
+const MAX_DIFFERENT_PIXELS_RATIO: f32 = 0.002;
+
+fn check_golden(name: &str, mut curve: Box<dyn InteractiveCurve>) {
+    let image = render_raster(curve.as_mut(), SIZE, SIZE);
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        image.save_png(&path).unwrap();
+        return;
+    }
+
+    let golden = ::image::open(&path)
+        .unwrap_or_else(|err| panic!("Cannot read {}: {}", path.display(), err))
+        .to_rgba8();
+    assert_eq!((golden.width(), golden.height()), (SIZE, SIZE));
+
+    let different_pixels = golden
+        .as_raw()
+        .chunks(4)
+        .zip(image.pixels().chunks(4))
+        .filter(|(expected, actual)|
+            expected.iter().zip(actual.iter()).any(|(e, a)| e.abs_diff(*a) > CHANNEL_TOLERANCE)
+        )
+        .count();
+    let ratio = different_pixels as f32 / (SIZE * SIZE) as f32;
+    assert!(
+        ratio <= MAX_DIFFERENT_PIXELS_RATIO,
+        "{}: {} pixels differ from {}", name, different_pixels, path.display()
+    );
+}
+
+#[test]
+fn dejong_matches_golden_image() {
+    check_golden("dejong", Box::new(DeJongAttractor::new()));
+}
+
+#[test]
+fn lissajou_matches_golden_image() {
+    check_golden("lissajou", Box::new(Lissajou::new()));
+}
+
+#[test]
+fn harmonograph_matches_golden_image() {
+    check_golden("harmonograph", Box::new(Harmonograph::new()));
+}
+
+#[test]
+fn mandelbrot_matches_golden_image() {
+    check_golden("mandelbrot", Box::new(MandelbrotSet::new()));
+}
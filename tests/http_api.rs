@@ -0,0 +1,88 @@
+// Scripts the API over loopback, as a notebook would, with the headless rasterizer in place of the window.
+#![cfg(feature = "http")]
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+use serde_json::{json, Value};
+use lissajous::{render_raster, CurveRegistry, HttpApi, WindowRequest};
+
+const FRAME_SIZE: u32 = 64;
+
+// The status and the body of the response
+fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Vec<u8>) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+        method, path, body.len(), body,
+    ).unwrap();
+    let mut response = vec![];
+    stream.read_to_end(&mut response).unwrap();
+    let split = response.windows(4).position(|window| window == b"\r\n\r\n").unwrap();
+    let status = String::from_utf8_lossy(&response[..split]).split(' ').nth(1).unwrap().parse().unwrap();
+    (status, response[split + 4..].to_vec())
+}
+
+fn json_request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+    let (status, body) = request(address, method, path, body);
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[test]
+fn a_script_drives_the_curves_and_fetches_the_frame() {
+    let registry = CurveRegistry::default();
+    let mut curves = registry.instantiate();
+    let mut curve_index = 1;
+    let mut api = HttpApi::bind("127.0.0.1:0").unwrap();
+    let address = api.local_addr().unwrap();
+
+    let script = std::thread::spawn(move || {
+        let (status, list) = json_request(address, "GET", "/curves", "");
+        assert_eq!(status, 200);
+        assert_eq!(list.as_array().unwrap().len(), 4);
+        assert_eq!(list[1]["current"], true);
+
+        let (status, curve) = json_request(address, "PUT", "/curve", r#"{"curve": "curve.dejong"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(curve["number"], 1);
+
+        let (status, state) = json_request(address, "PATCH", "/curve/state", r#"{"a": 0.5}"#);
+        assert_eq!(status, 200);
+        assert_eq!(state["a"], 0.5);
+        let (_, state) = json_request(address, "GET", "/curve/state", "");
+        assert_eq!(state["a"], 0.5);
+
+        let (status, params) = json_request(address, "GET", "/curve/params", "");
+        assert_eq!(status, 200);
+        assert_eq!(params[0]["id"], "action.dejong.a");
+        assert_eq!(params[0]["value"], 0.5);
+        let (status, values) = json_request(address, "PUT", "/curve/params", r#"{"action.dejong.b": 9.0}"#);
+        assert_eq!(status, 200);
+        assert_eq!(values["action.dejong.b"], json!(std::f32::consts::PI));
+        assert_eq!(json_request(address, "PUT", "/curve/params", r#"{"nothing": 1.0}"#).0, 400);
+
+        let (status, error) = json_request(address, "PATCH", "/curve/state", "not json");
+        assert_eq!(status, 400);
+        assert!(error["error"].is_string());
+        assert_eq!(json_request(address, "GET", "/nothing", "").0, 404);
+
+        let (status, png) = request(address, "GET", "/frame.png", "");
+        assert_eq!(status, 200);
+        let frame = ::image::load_from_memory(&png).unwrap();
+        assert_eq!((frame.width(), frame.height()), (FRAME_SIZE, FRAME_SIZE));
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !script.is_finished() && Instant::now() < deadline {
+        for pending in api.handle(&registry, &mut curves, &mut curve_index) {
+            assert_eq!(pending.kind, WindowRequest::Frame);
+            let image = render_raster(curves[curve_index].as_mut(), FRAME_SIZE, FRAME_SIZE);
+            pending.respond_png(image.encode_png().unwrap());
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    script.join().unwrap();
+    assert_eq!(curve_index, 0);
+    assert_eq!(curves[0].save_state()["a"], json!(0.5));
+}
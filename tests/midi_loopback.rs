@@ -0,0 +1,30 @@
+#![cfg(all(feature = "midi", unix))]
+
+use std::thread;
+use std::time::{Duration, Instant};
+use midir::MidiOutput;
+use midir::os::unix::VirtualOutput;
+use lissajous::{MidiControl, MidiInputs};
+
+// Sends a control change through a virtual port to the inputs of the application.
+// Skipped where there is no MIDI backend, e.g. without the ALSA sequencer.
+#[test]
+fn control_changes_arrive_through_a_virtual_port() {
+    let Ok(output) = MidiOutput::new("lissajous loopback test") else {
+        eprintln!("No MIDI backend, skipped");
+        return;
+    };
+    let mut port = output.create_virtual("loopback").expect("Virtual output port");
+    let inputs = MidiInputs::open("lissajous test").expect("MIDI inputs");
+    assert!(inputs.ports.iter().any(|name| name.contains("loopback")), "Not connected to {:?}", inputs.ports);
+
+    let control = MidiControl { channel: 2, controller: 21 };
+    port.send(&control.message(64)).unwrap();
+    let start = Instant::now();
+    let mut received = vec![];
+    while received.is_empty() && start.elapsed() < Duration::from_secs(2) {
+        thread::sleep(Duration::from_millis(10));
+        received = inputs.poll();
+    }
+    assert_eq!(received, [(control, 64)]);
+}
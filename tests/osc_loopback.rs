@@ -0,0 +1,86 @@
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+use lissajous::{CurveRegistry, OscArg, OscMessage, OscRemote};
+
+fn receive(client: &UdpSocket) -> Vec<OscMessage> {
+    let mut messages = vec![];
+    let mut buffer = [0; 1024];
+    while let Ok(size) = client.recv(&mut buffer) {
+        messages.extend(OscMessage::decode_packet(&buffer[..size]).unwrap());
+    }
+    messages
+}
+
+#[test]
+fn a_client_drives_the_curves_and_receives_their_state() {
+    let registry = CurveRegistry::default();
+    let mut curves = registry.instantiate();
+    let mut curve_index = 1;
+    let mut remote = OscRemote::bind("127.0.0.1:0").unwrap();
+
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+    client.connect(remote.local_addr()).unwrap();
+    client.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+    let messages = [
+        OscMessage::new("/curve/select", vec![OscArg::Str("curve.dejong".to_string())]),
+        OscMessage::new("/dejong/a", vec![OscArg::Float(0.75)]),
+        OscMessage::new("/unknown", vec![]),
+        OscMessage::new("/action", vec![OscArg::Str("action.app.undo".to_string())]),
+    ];
+    for message in &messages {
+        client.send(&message.encode()).unwrap();
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut actions = vec![];
+    while actions.is_empty() && Instant::now() < deadline {
+        actions = remote.apply(&registry, &mut curves, &mut curve_index);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(actions, ["action.app.undo"]);
+    assert_eq!(curve_index, 0);
+    let position = curves[0].param_position("action.dejong.a").unwrap();
+    assert!((position - 0.75).abs() < 1e-4);
+
+    remote.send_feedback(&registry, curve_index, curves[curve_index].as_ref());
+    let feedback = receive(&client);
+    assert_eq!(feedback[0], OscMessage::new("/curve/selected", vec![
+        OscArg::Int(1), OscArg::Str("curve.dejong".to_string()),
+    ]));
+    let a = feedback.iter().find(|message| message.address == "/dejong/a").unwrap();
+    assert!((a.args[0].as_f32().unwrap() - 0.75).abs() < 1e-4);
+
+    // Only the changes are sent again
+    remote.send_feedback(&registry, curve_index, curves[curve_index].as_ref());
+    assert!(receive(&client).is_empty());
+}
+
+#[test]
+fn invalid_values_are_ignored() {
+    let registry = CurveRegistry::default();
+    let mut curves = registry.instantiate();
+    let mut curve_index = registry.find("curve.lissajou").unwrap();
+    let mut remote = OscRemote::bind("127.0.0.1:0").unwrap();
+    let jitter = curves[curve_index].param("lissajou.jitter");
+
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+    client.connect(remote.local_addr()).unwrap();
+    let messages = [
+        OscMessage::new("/lissajou/jitter", vec![OscArg::Float(f32::NAN)]),
+        OscMessage::new("/lissajou/jitter", vec![OscArg::Float(f32::INFINITY)]),
+        OscMessage::new("/action", vec![OscArg::Str("action.app.undo".to_string())]),
+    ];
+    for message in &messages {
+        client.send(&message.encode()).unwrap();
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut actions = vec![];
+    while actions.is_empty() && Instant::now() < deadline {
+        actions = remote.apply(&registry, &mut curves, &mut curve_index);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(actions, ["action.app.undo"]);
+    assert_eq!(curves[curve_index].param("lissajou.jitter"), jitter);
+    curves[curve_index].compute_drawables(glam::Vec2::ZERO, glam::Vec2::splat(100.0));
+}
@@ -0,0 +1,30 @@
+use lissajous::{export_xy_wav, CurveRegistry, Lissajou};
+
+#[test]
+fn pen_curves_are_written_as_stereo_wav() {
+    let path = std::env::temp_dir().join("lissajous_oscilloscope_test.wav");
+    export_xy_wav(&Lissajou::new(), &path, 8000, 0.5, 50.0).unwrap();
+    let mut reader = hound::WavReader::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(reader.spec().channels, 2);
+    assert_eq!(reader.spec().sample_rate, 8000);
+    let samples: Vec<i16> = reader.samples::<i16>().map(Result::unwrap).collect();
+    assert_eq!(samples.len(), 2 * 4000);
+    // The figure fills the scale on both axes, with some headroom
+    for channel in 0..2 {
+        let peak = samples.iter().skip(channel).step_by(2).map(|sample| sample.unsigned_abs()).max().unwrap();
+        assert!(peak > i16::MAX as u16 / 10 * 8 && peak < i16::MAX as u16, "{}", peak);
+    }
+}
+
+#[test]
+fn only_pen_curves_have_a_path() {
+    let with_path: Vec<String> = CurveRegistry::default()
+        .instantiate()
+        .iter()
+        .filter(|curve| !curve.pen_path().is_empty())
+        .map(|curve| curve.name().to_string())
+        .collect();
+    assert_eq!(with_path, ["curve.lissajou", "curve.harmonograph"]);
+}